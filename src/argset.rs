@@ -66,6 +66,24 @@ pub(crate) fn push_conflicts_arg() -> clap::Arg {
         .action(clap::ArgAction::Set)
 }
 
+/// The `--conflict-style` option determining the style of conflict markers.
+pub(crate) fn conflict_style_arg() -> Arg {
+    Arg::new("conflict-style")
+        .long("conflict-style")
+        .help("Conflict marker style: merge, diff3, or zdiff3")
+        .long_help(
+            "Write conflict markers in the given style when pushing a patch results \
+             in merge conflicts, regardless of the \"merge.conflictStyle\" \
+             configuration variable. The \"merge\" style shows the conflicting \
+             \"ours\" and \"theirs\" sections, \"diff3\" additionally shows the base \
+             section, and \"zdiff3\" is like \"diff3\" but moves lines common to both \
+             sides out of the conflict region.",
+        )
+        .value_name("style")
+        .value_parser(clap::value_parser!(crate::stupid::ConflictStyle))
+        .num_args(1)
+}
+
 pub(crate) fn committer_date_is_author_date_arg() -> clap::Arg {
     Arg::new("committer-date-is-author-date")
         .long("committer-date-is-author-date")
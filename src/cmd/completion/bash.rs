@@ -561,34 +561,34 @@ _remotes ()
     test "$g" && __git show-ref  | grep ' refs/remotes/' | sed 's,.* refs/remotes/,,'
 }
 
-_stg_series ()
+_stg_list_patches ()
 {
-    __stg series ${__branch:+"--branch=${__branch}"} "$@"
+    __stg completion --list-patches ${__branch:+"--branch=${__branch}"} "$@"
 }
 
 _all_patches ()
 {
-    _stg_series --no-description --noprefix --all
+    _stg_list_patches
 }
 
 _applied_patches ()
 {
-    _stg_series --no-description --noprefix --applied
+    _stg_list_patches --applied
 }
 
 _unapplied_patches ()
 {
-    _stg_series --no-description --noprefix --unapplied
+    _stg_list_patches --unapplied
 }
 
 _visible_patches ()
 {
-    _stg_series --no-description --noprefix --applied --unapplied
+    _stg_list_patches --applied --unapplied
 }
 
 _hidden_patches ()
 {
-    _stg_series --no-description --noprefix --hidden
+    _stg_list_patches --hidden
 }
 
 _conflicting_files ()
@@ -656,6 +656,9 @@ _stg ()
         --color)
             ((i++))
             ;;
+        -c)
+            ((i++))
+            ;;
         -C)
             __C_args[C_args_count++]=-C
             ((i++))
@@ -673,6 +676,8 @@ _stg ()
         -C)
             mapfile -t COMPREPLY < <(compgen -A directory -- "$cur")
             return;;
+        -c)
+            return;;
         --color)
             mapfile -t COMPREPLY < <(compgen -W "always ansi auto never" -- "$cur")
             return;;
@@ -76,7 +76,7 @@ end
 function __fish_stg_needs_command
     set -l cmd (commandline -opc)
     set -e cmd[1]
-    argparse -s C=+ color= h/help version -- $cmd 2>/dev/null
+    argparse -s C=+ c=+ color= h/help version -- $cmd 2>/dev/null
     or return 0
     set -q _flag_version; and return 1
     if set -q argv[1]
@@ -135,12 +135,7 @@ function __fish_stg_stg_branches
 end
 
 function __fish_stg_patches
-    set -l index 1
-    __fish_stg series --color=never --no-description $argv 2>/dev/null | \
-    while read -d ' ' sigil patch
-        echo -e "$patch\t$index $sigil"
-        set index (math $index + 1)
-    end
+    __fish_stg completion --list-patches --description $argv 2>/dev/null
 end
 
 function __fish_stg_tags
@@ -194,6 +189,7 @@ end
 
 complete -c stg -n __fish_stg_needs_command -xa '(stg completion list commands-and-aliases --style=fish)'
 complete -c stg -n __fish_stg_needs_command -s C -xa '(__fish_complete_directories)' -d 'Run as if started in this directory'
+complete -c stg -n __fish_stg_needs_command -s c -x -d 'Pass a configuration parameter to git'
 complete -c stg -n __fish_stg_needs_command -l color -a 'auto always ansi never' -d 'When to colorize output'
 complete -c stg -n __fish_stg_needs_command -l version -d 'Print version information'
 complete -c stg -n __fish_stg_needs_command -s h -l help -d 'Print help information'
@@ -366,9 +362,11 @@ fn get_arg_completion_params(arg: &clap::Arg) -> ShStream {
             "git-diff-opt" => params.word("-xa '(__fish_stg_git_diff_opts)'"),
             "git-format-patch-opt" => params.word("-xa '(__fish_stg_git_format_patch_opts)'"),
             "git-send-email-opt" => params.word("-xa '(__fish_stg_git_send_email_opts)'"),
-            "patch" | "patchranges" => params.word("-kxa '(__fish_stg_patches -A -U)'"),
+            "patch" | "patchranges" => {
+                params.word("-kxa '(__fish_stg_patches --applied --unapplied)'")
+            }
             "patchranges-all" | "set-tree" | "stgit-revision" => {
-                params.word("-kxa '(__fish_stg_patches --all)'");
+                params.word("-kxa '(__fish_stg_patches)'");
             }
             "patchranges-applied" => params.word("-kxa '(__fish_stg_patches --applied)'"),
             "patchranges-hidden" => params.word("-kxa '(__fish_stg_patches --hidden)'"),
@@ -5,8 +5,14 @@
 use std::str::FromStr;
 
 use anyhow::Result;
+use bstr::ByteSlice;
 
-use crate::cmd::STGIT_COMMANDS;
+use crate::{
+    branchloc::BranchLocator,
+    cmd::STGIT_COMMANDS,
+    ext::RepositoryExtended,
+    stack::{InitializationPolicy, Stack, StackStateAccess},
+};
 
 pub(super) fn command() -> clap::Command {
     clap::Command::new("list")
@@ -79,6 +85,47 @@ pub(super) fn dispatch(matches: &clap::ArgMatches) -> Result<()> {
     }
 }
 
+/// Output the names of the patches of the current (or `--branch`) stack.
+///
+/// Applied, unapplied, and hidden patch names are output in stack order, one per line.
+/// When any of `--applied`, `--unapplied`, or `--hidden` are given, only those patches
+/// are output. With `--description`, each name is followed by a tab and the patch's
+/// short description.
+pub(super) fn list_patches(matches: &clap::ArgMatches) -> Result<()> {
+    let mut output = super::get_output_stream(matches)?;
+    let repo = gix::Repository::open()?;
+    let stack = Stack::from_branch_locator(
+        &repo,
+        matches.get_one::<BranchLocator>("branch"),
+        InitializationPolicy::AllowUninitialized,
+    )?;
+    let show_applied = matches.get_flag("applied");
+    let show_unapplied = matches.get_flag("unapplied");
+    let show_hidden = matches.get_flag("hidden");
+    let show_all = !(show_applied || show_unapplied || show_hidden);
+    let show_description = matches.get_flag("description");
+
+    for (patchnames, show) in [
+        (stack.applied(), show_applied),
+        (stack.unapplied(), show_unapplied),
+        (stack.hidden(), show_hidden),
+    ] {
+        if !(show || show_all) {
+            continue;
+        }
+        for patchname in patchnames {
+            if show_description {
+                let commit_ref = stack.get_patch_commit(patchname).decode()?;
+                let summary = commit_ref.message_summary();
+                writeln!(output, "{patchname}\t{}", summary.to_str_lossy())?;
+            } else {
+                writeln!(output, "{patchname}")?;
+            }
+        }
+    }
+    Ok(())
+}
+
 fn list_aliases(
     output: &mut Box<dyn std::io::Write>,
     style: OutputStyle,
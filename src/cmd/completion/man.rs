@@ -7,21 +7,44 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use unicode_width::UnicodeWidthStr;
 
-const WIDTH: usize = 80;
+const DEFAULT_WIDTH: u16 = 80;
+const MIN_WIDTH: i64 = 40;
 
 pub(super) fn command() -> clap::Command {
     clap::Command::new("man")
-        .about("Generate asciidoc man pages")
+        .about("Generate man pages")
         .long_about(
-            "Generate man pages in asciidoc format. The generated asciidoc files may \
-             be further processed by asciidoc or asciidoctor to produce roff, html, or \
-             other output formats.\n\
+            "Generate man pages in asciidoc or roff format. The generated asciidoc \
+             files may be further processed by asciidoc or asciidoctor to produce roff, \
+             html, or other output formats. With '--format=roff', man pages are \
+             instead rendered directly to roff, for use in environments where \
+             asciidoc and asciidoctor are not available.\n\
              \n\
              One file is generated for each `stg` command. The output directory \
              defaults to the current directory, but may be specified with '--output'. \
-             The output files are named `stg-<command>.txt`.",
+             The output files are named `stg-<command>.txt`, or `stg-<command>.1` for \
+             roff.\n\
+             \n\
+             With '--aliases', an additional `stg-aliases.txt` (or `stg-aliases.1`) \
+             file is generated which documents the built-in and configured aliases \
+             along with their expansions.\n\
+             \n\
+             With '--index', an additional `stg.txt` (or `stg.1`) file is generated \
+             which lists every command along with a one-line description and a link \
+             to the command's man page.\n\
+             \n\
+             The footer of each page includes the StGit version, unless \
+             '--no-version' is given.\n\
+             \n\
+             Each generated file ends with exactly one newline, or with none when \
+             '--no-final-newline' is given, such that the output is byte-stable.\n\
+             \n\
+             With '--check', no files are written. Instead, each generated page is \
+             compared with the existing file in the output directory and the paths \
+             of any missing or out of date files are reported.",
         )
         .arg(
             clap::Arg::new("output")
@@ -32,8 +55,103 @@ pub(super) fn command() -> clap::Command {
                 .value_hint(clap::ValueHint::DirPath)
                 .value_parser(clap::value_parser!(PathBuf)),
         )
+        .arg(
+            clap::Arg::new("format")
+                .long("format")
+                .help("Output format of the man pages")
+                .long_help(
+                    "Output format of the generated man pages. The 'asciidoc' format is \
+                     suitable for processing with asciidoc or asciidoctor. The 'roff' \
+                     format uses man macros and may be viewed directly with man(1).",
+                )
+                .value_name("format")
+                .value_parser(["asciidoc", "roff"])
+                .default_value("asciidoc"),
+        )
+        .arg(
+            clap::Arg::new("width")
+                .long("width")
+                .help("Wrap text to <cols> columns")
+                .long_help(
+                    "Wrap descriptive text in the generated man pages to <cols> \
+                     columns. The width must be at least 40 columns.",
+                )
+                .value_name("cols")
+                .default_value(DEFAULT_WIDTH.to_string())
+                .value_parser(clap::value_parser!(u16).range(MIN_WIDTH..)),
+        )
+        .arg(
+            clap::Arg::new("aliases")
+                .long("aliases")
+                .help("Also generate a page documenting aliases")
+                .long_help(
+                    "Also generate `stg-aliases.txt`, documenting the aliases \
+                     available in the current repository, including aliases \
+                     configured with `stgit.alias.*`, and their expansions.",
+                )
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("index")
+                .long("index")
+                .help("Also generate an index page listing all commands")
+                .long_help(
+                    "Also generate `stg.txt`, an index page listing each `stg` \
+                     command with its one-line description and a link to the \
+                     command's man page.",
+                )
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("no-final-newline")
+                .long("no-final-newline")
+                .help("Omit the newline at the end of each generated file")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("no-version")
+                .long("no-version")
+                .help("Omit the StGit version from the generated pages")
+                .long_help(
+                    "Omit the StGit version from the footer of the generated man \
+                     pages. This allows the pages to be reproduced independent of \
+                     the version of StGit used to generate them.",
+                )
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("check")
+                .long("check")
+                .help("Check that existing man pages are up to date")
+                .long_help(
+                    "Check that the man pages in the output directory are up to date \
+                     without modifying them. The paths of stale or missing pages are \
+                     printed and the command fails if there are any.",
+                )
+                .action(clap::ArgAction::SetTrue),
+        )
 }
 
+/// Options common to the generation of all man pages.
+#[derive(Clone, Copy)]
+struct PageOptions {
+    /// Width, in columns, to wrap descriptive text to.
+    width: usize,
+
+    /// StGit version to include in each page's footer, if any.
+    version: Option<&'static str>,
+}
+
+/// Generates a command's man page given the command and the page options.
+type PageGenerator = fn(&mut clap::Command, PageOptions) -> String;
+
+/// Generates the aliases man page given the aliases and the page options.
+type AliasesPageGenerator = fn(&crate::alias::Aliases, PageOptions) -> String;
+
+/// Generates the index page given the `(name, about)` pairs of each command and the
+/// page options.
+type IndexPageGenerator = fn(&[(String, String)], PageOptions) -> String;
+
 pub(super) fn dispatch(matches: &clap::ArgMatches) -> Result<()> {
     let output_dir = if let Some(path) = matches.get_one::<PathBuf>("output").map(PathBuf::as_path)
     {
@@ -42,23 +160,244 @@ pub(super) fn dispatch(matches: &clap::ArgMatches) -> Result<()> {
         Path::new("")
     };
 
-    std::fs::create_dir_all(output_dir)?;
+    let width = usize::from(
+        *matches
+            .get_one::<u16>("width")
+            .expect("width has default value"),
+    );
+
+    let check = matches.get_flag("check");
+    let final_newline = !matches.get_flag("no-final-newline");
+    let options = PageOptions {
+        width,
+        version: if matches.get_flag("no-version") {
+            None
+        } else {
+            Some(env!("CARGO_PKG_VERSION"))
+        },
+    };
+
+    let (generate_page, generate_aliases_page, generate_index_page, extension): (
+        PageGenerator,
+        AliasesPageGenerator,
+        IndexPageGenerator,
+        &str,
+    ) = match matches
+        .get_one::<String>("format")
+        .map(String::as_str)
+        .expect("format has default value")
+    {
+        "roff" => (
+            generate_roff,
+            generate_aliases_roff,
+            generate_index_roff,
+            "1",
+        ),
+        _ => (
+            generate_asciidoc,
+            generate_aliases_asciidoc,
+            generate_index_asciidoc,
+            "txt",
+        ),
+    };
+
+    if !check {
+        std::fs::create_dir_all(output_dir)?;
+    }
 
     let mut stg = crate::get_full_command(&crate::alias::Aliases::new(), None);
     stg.build();
 
-    for command in stg.get_subcommands_mut() {
-        let asciidoc = generate_asciidoc(command);
-        let path = output_dir.join(format!("stg-{}.txt", command.get_name()));
-        if std::fs::read_to_string(&path).ok().as_ref() != Some(&asciidoc) {
-            std::fs::write(path, asciidoc)?;
+    let index_entries: Option<Vec<(String, String)>> = matches.get_flag("index").then(|| {
+        stg.get_subcommands()
+            .map(|command| {
+                (
+                    command.get_name().to_string(),
+                    command.get_about().unwrap_or_default().to_string(),
+                )
+            })
+            .collect()
+    });
+
+    // Each command's page is independent, so the pages are generated and written by
+    // a pool of scoped threads, each handling a contiguous chunk of the commands.
+    let mut commands: Vec<&mut clap::Command> = stg.get_subcommands_mut().collect();
+    let num_threads = std::thread::available_parallelism().map_or(1, usize::from);
+    let chunk_size = ((commands.len() + num_threads - 1) / num_threads).max(1);
+    let mut stale_paths = std::thread::scope(|scope| -> Result<Vec<PathBuf>> {
+        let handles: Vec<_> = commands
+            .chunks_mut(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || -> Result<Vec<PathBuf>> {
+                    let mut stale_paths = Vec::new();
+                    for command in chunk {
+                        let page =
+                            normalize_final_newline(generate_page(command, options), final_newline);
+                        let path =
+                            output_dir.join(format!("stg-{}.{extension}", command.get_name()));
+                        if update_page(&path, &page, check)? {
+                            stale_paths.push(path);
+                        }
+                    }
+                    Ok(stale_paths)
+                })
+            })
+            .collect();
+        let mut stale_paths = Vec::new();
+        for handle in handles {
+            stale_paths.extend(
+                handle
+                    .join()
+                    .map_err(|_| anyhow!("panic while generating man pages"))??,
+            );
+        }
+        Ok(stale_paths)
+    })?;
+
+    if matches.get_flag("aliases") {
+        let (aliases, _) = crate::get_aliases()?;
+        let page = normalize_final_newline(generate_aliases_page(&aliases, options), final_newline);
+        let path = output_dir.join(format!("stg-aliases.{extension}"));
+        if update_page(&path, &page, check)? {
+            stale_paths.push(path);
+        }
+    }
+
+    if let Some(index_entries) = index_entries {
+        let page =
+            normalize_final_newline(generate_index_page(&index_entries, options), final_newline);
+        let path = output_dir.join(format!("stg.{extension}"));
+        if update_page(&path, &page, check)? {
+            stale_paths.push(path);
         }
     }
 
+    if check && !stale_paths.is_empty() {
+        for path in &stale_paths {
+            println!("{}", path.display());
+        }
+        return Err(anyhow!(
+            "{} man page{} out of date",
+            stale_paths.len(),
+            if stale_paths.len() == 1 {
+                " is"
+            } else {
+                "s are"
+            }
+        ));
+    }
+
     Ok(())
 }
 
-fn generate_asciidoc(command: &mut clap::Command) -> String {
+/// Ensure `page` ends with exactly one newline, or with none if `final_newline` is false.
+fn normalize_final_newline(mut page: String, final_newline: bool) -> String {
+    page.truncate(page.trim_end_matches('\n').len());
+    if final_newline {
+        page.push('\n');
+    }
+    page
+}
+
+/// Determine whether the file at `path` differs from `contents`, and update it if so.
+///
+/// Unchanged files are left untouched, preserving their modification times, which
+/// avoids needless rebuilds of the documentation. When `check` is true, stale files
+/// are only detected and never written.
+fn update_page(path: &Path, contents: &str, check: bool) -> Result<bool> {
+    if std::fs::read_to_string(path).ok().as_deref() == Some(contents) {
+        Ok(false)
+    } else {
+        if !check {
+            std::fs::write(path, contents)?;
+        }
+        Ok(true)
+    }
+}
+
+const ALIASES_DESCRIPTION: &str = "Aliases are alternative names for StGit commands or shell \
+                                   commands. StGit aliases expand to a `stg` command line, \
+                                   while shell aliases, configured with a leading '!', are \
+                                   run by the shell. Aliases may be configured with \
+                                   `stgit.alias.<name>`.";
+
+fn generate_aliases_asciidoc(aliases: &crate::alias::Aliases, options: PageOptions) -> String {
+    let width = options.width;
+    let mut page = String::new();
+
+    write_underlined(&mut page, "stg-aliases(1)", '=');
+    page.push('\n');
+
+    write_underlined(&mut page, "NAME", '-');
+    page.push_str("stg-aliases - StGit command aliases\n\n");
+
+    write_underlined(&mut page, "DESCRIPTION", '-');
+    page.push('\n');
+    for line in wrap(ALIASES_DESCRIPTION, width) {
+        page.push_str(line);
+        page.push('\n');
+    }
+    page.push('\n');
+
+    if !aliases.is_empty() {
+        write_underlined(&mut page, "ALIASES", '-');
+        page.push('\n');
+        for (name, alias) in aliases {
+            writeln!(page, "{name}::").unwrap();
+            let expansion = alias.make().get_about().unwrap_or_default().to_string();
+            let expansion = escape_text(&expansion, &mut false);
+            for line in wrap(&expansion, width - 4) {
+                page.push_str("    ");
+                page.push_str(line);
+                page.push('\n');
+            }
+            page.push('\n');
+        }
+    }
+
+    write_asciidoc_footer(&mut page, options.version);
+
+    page
+}
+
+fn generate_index_asciidoc(entries: &[(String, String)], options: PageOptions) -> String {
+    let width = options.width;
+    let mut page = String::new();
+
+    write_underlined(&mut page, "stg(1)", '=');
+    page.push('\n');
+
+    write_underlined(&mut page, "NAME", '-');
+    page.push_str("stg - Manage stacks of patches using the Git content tracker\n\n");
+
+    write_underlined(&mut page, "COMMANDS", '-');
+    page.push('\n');
+    for (name, about) in entries {
+        writeln!(page, "linkstg:{name}[]::").unwrap();
+        let about = escape_text(about, &mut false);
+        for line in wrap(&about, width - 4) {
+            page.push_str("    ");
+            page.push_str(line);
+            page.push('\n');
+        }
+        page.push('\n');
+    }
+
+    write_asciidoc_footer(&mut page, options.version);
+
+    page
+}
+
+fn write_asciidoc_footer(page: &mut String, version: Option<&str>) {
+    write_underlined(page, "StGit", '-');
+    page.push_str("Part of the StGit suite - see linkman:stg[1]\n");
+    if let Some(version) = version {
+        writeln!(page, "\nStGit version {version}").unwrap();
+    }
+}
+
+fn generate_asciidoc(command: &mut clap::Command, options: PageOptions) -> String {
+    let width = options.width;
     let mut page = String::new();
     let name = command.get_name().to_string();
 
@@ -89,7 +428,7 @@ fn generate_asciidoc(command: &mut clap::Command) -> String {
             page.push_str(para);
             page.push('\n');
         } else {
-            for line in wrap(para, WIDTH) {
+            for line in wrap(para, width) {
                 page.push_str(line);
                 page.push('\n');
             }
@@ -97,34 +436,71 @@ fn generate_asciidoc(command: &mut clap::Command) -> String {
         page.push('\n');
     }
 
-    if let Some(commands_section) = get_commands_section(command) {
+    if let Some(commands_section) = get_commands_section(command, width) {
         write_underlined(&mut page, "COMMANDS", '-');
         page.push('\n');
         page.push_str(&commands_section);
     }
 
-    if let Some(options_section) = get_options_section(command) {
+    if let Some(options_section) = get_options_section(command, width) {
         page.push_str(&options_section);
     }
 
+    if let Some(environment_section) = get_environment_section(&name, width) {
+        write_underlined(&mut page, "ENVIRONMENT", '-');
+        page.push('\n');
+        page.push_str(&environment_section);
+    }
+
     // TODO use command.get_after_long_help()
 
-    write_underlined(&mut page, "StGit", '-');
-    page.push_str("Part of the StGit suite - see linkman:stg[1]\n");
+    write_asciidoc_footer(&mut page, options.version);
 
     page
 }
 
 fn get_usage(command: &mut clap::Command) -> String {
     let mut usage = String::new();
-    let name_stack = vec!["stg".to_string()];
-    add_usage(&mut usage, command, &name_stack);
+    for usage_line in get_usage_lines(command) {
+        usage.push('\'');
+        usage.push_str(&usage_line.command);
+        usage.push('\'');
+        for usage_word in &usage_line.args {
+            usage.push(' ');
+            if let Some(rest) = usage_word.strip_prefix("<-") {
+                usage.push_str("\\<-");
+                usage.push_str(rest);
+            } else {
+                usage.push_str(usage_word);
+            }
+        }
+        usage.push('\n');
+    }
     usage
 }
 
-fn add_usage(usage: &mut String, command: &mut clap::Command, name_stack: &[String]) {
+/// A line of a command's usage, split into the command words and its arguments.
+struct UsageLine {
+    /// The command, e.g. `stg branch --create`.
+    command: String,
+    /// The remaining words of the usage line.
+    args: Vec<String>,
+}
+
+fn get_usage_lines(command: &mut clap::Command) -> Vec<UsageLine> {
+    let mut usage_lines = Vec::new();
+    let name_stack = vec!["stg".to_string()];
+    add_usage_lines(&mut usage_lines, command, &name_stack);
+    usage_lines
+}
+
+fn add_usage_lines(
+    usage_lines: &mut Vec<UsageLine>,
+    command: &mut clap::Command,
+    name_stack: &[String],
+) {
     let usage_string = command.render_usage().to_string();
-    let usage_lines = usage_string
+    let rendered_lines = usage_string
         .strip_prefix("Usage: ")
         .expect("Usage starts with 'Usage: '")
         .lines()
@@ -132,57 +508,48 @@ fn add_usage(usage: &mut String, command: &mut clap::Command, name_stack: &[Stri
         .filter(|line| !line.is_empty())
         .collect::<Vec<_>>();
 
-    if usage_lines.len() == 1 {
+    if rendered_lines.len() == 1 {
         let mut has_subcommands = false;
         let mut sub_name_stack = name_stack.to_vec();
         sub_name_stack.push(command.get_name().to_string());
         for subcmd in command.get_subcommands_mut() {
             has_subcommands = true;
-            add_usage(usage, subcmd, &sub_name_stack);
+            add_usage_lines(usage_lines, subcmd, &sub_name_stack);
         }
         if has_subcommands {
             return;
         }
     }
 
-    for usage_line in &usage_lines {
-        usage.push('\'');
-        let mut usage_line_words = usage_line.split(' ');
+    for rendered_line in &rendered_lines {
+        let mut command_words = Vec::new();
+        let mut usage_line_words = rendered_line.split(' ');
         for stack_word in name_stack {
             let usage_word = usage_line_words
                 .next()
                 .expect("usage line has more words than name_stack");
             assert!(usage_word == stack_word);
-            usage.push_str(usage_word);
-            usage.push(' ');
+            command_words.push(usage_word);
         }
         let usage_word = usage_line_words
             .next()
             .expect("usage line has more words than name_stack");
-        usage.push_str(usage_word);
-        usage.push('\'');
-
-        for usage_word in usage_line_words {
-            usage.push(' ');
-            if let Some(rest) = usage_word.strip_prefix("<-") {
-                usage.push_str("\\<-");
-                usage.push_str(rest);
-            } else {
-                usage.push_str(usage_word);
-            }
-        }
-        usage.push('\n');
+        command_words.push(usage_word);
+        usage_lines.push(UsageLine {
+            command: command_words.join(" "),
+            args: usage_line_words.map(str::to_string).collect(),
+        });
     }
 }
 
-fn get_commands_section(command: &clap::Command) -> Option<String> {
+fn get_commands_section(command: &clap::Command, width: usize) -> Option<String> {
     let mut section = String::new();
     for subcmd in command
         .get_subcommands()
         .filter(|&subcmd| subcmd.get_name() != "help")
     {
         let subcmd_stack = vec![];
-        add_command_stanza(&mut section, subcmd, &subcmd_stack);
+        add_command_stanza(&mut section, subcmd, &subcmd_stack, width);
     }
     if section.is_empty() {
         None
@@ -191,7 +558,7 @@ fn get_commands_section(command: &clap::Command) -> Option<String> {
     }
 }
 
-fn add_command_stanza(section: &mut String, command: &clap::Command, stack: &[&str]) {
+fn add_command_stanza(section: &mut String, command: &clap::Command, stack: &[&str], width: usize) {
     let name = command.get_name();
     let mut has_subcommands = false;
     for subcmd in command
@@ -201,7 +568,7 @@ fn add_command_stanza(section: &mut String, command: &clap::Command, stack: &[&s
         has_subcommands = true;
         let mut stack = stack.to_vec();
         stack.push(name);
-        add_command_stanza(section, subcmd, &stack);
+        add_command_stanza(section, subcmd, &stack, width);
     }
 
     if !has_subcommands {
@@ -226,7 +593,7 @@ fn add_command_stanza(section: &mut String, command: &clap::Command, stack: &[&s
                 section.push_str("+\n");
             }
             for line in para.lines() {
-                let wrap_width = if i == 0 { WIDTH - 4 } else { WIDTH };
+                let wrap_width = if i == 0 { width - 4 } else { width };
                 for wrapped_line in wrap(line, wrap_width) {
                     if i == 0 {
                         section.push_str("    ");
@@ -241,14 +608,38 @@ fn add_command_stanza(section: &mut String, command: &clap::Command, stack: &[&s
     }
 }
 
-fn get_options_section(command: &clap::Command) -> Option<String> {
+fn get_environment_section(name: &str, width: usize) -> Option<String> {
+    let envvars = crate::cmd::STGIT_COMMANDS
+        .iter()
+        .find(|command| command.name == name)
+        .map(|command| command.envvars)
+        .unwrap_or_default();
+
+    if envvars.is_empty() {
+        return None;
+    }
+
+    let mut section = String::new();
+    for (envvar, description) in envvars {
+        writeln!(section, "{envvar}::").unwrap();
+        for line in wrap(&make_links(description), width - 4) {
+            section.push_str("    ");
+            section.push_str(line);
+            section.push('\n');
+        }
+        section.push('\n');
+    }
+    Some(section)
+}
+
+fn get_options_section(command: &clap::Command, width: usize) -> Option<String> {
     let mut section = String::new();
-    add_options(&mut section, command, "OPTIONS", '-');
+    add_options(&mut section, command, "OPTIONS", '-', width);
     for subcmd in command
         .get_subcommands()
         .filter(|&subcmd| subcmd.get_name() != "help")
     {
-        add_subcommand_options(&mut section, subcmd, &[]);
+        add_subcommand_options(&mut section, subcmd, &[], width);
     }
 
     if section.is_empty() {
@@ -258,7 +649,12 @@ fn get_options_section(command: &clap::Command) -> Option<String> {
     }
 }
 
-fn add_subcommand_options(section: &mut String, command: &clap::Command, stack: &[&str]) {
+fn add_subcommand_options(
+    section: &mut String,
+    command: &clap::Command,
+    stack: &[&str],
+    width: usize,
+) {
     let name = command.get_name();
     let mut has_subcommands = false;
     for subcmd in command
@@ -268,7 +664,7 @@ fn add_subcommand_options(section: &mut String, command: &clap::Command, stack:
         has_subcommands = true;
         let mut stack = stack.to_vec();
         stack.push(name);
-        add_subcommand_options(section, subcmd, &stack);
+        add_subcommand_options(section, subcmd, &stack, width);
     }
 
     if !has_subcommands {
@@ -279,7 +675,7 @@ fn add_subcommand_options(section: &mut String, command: &clap::Command, stack:
         }
         header.push_str(&command.get_name().to_uppercase());
         header.push_str(" OPTIONS");
-        add_options(section, command, &header, '~');
+        add_options(section, command, &header, '~', width);
     }
 }
 
@@ -288,6 +684,7 @@ fn add_options(
     command: &clap::Command,
     header_name: &str,
     header_underline: char,
+    width: usize,
 ) {
     for (i, arg) in command
         .get_arguments()
@@ -301,39 +698,7 @@ fn add_options(
         if i == 0 {
             write_underlined(section, header_name, header_underline);
         }
-        let value_str = if arg.get_action().takes_values() {
-            if let Some(value_names) = arg.get_value_names() {
-                let mut value_str = String::new();
-                for (i, name) in value_names.iter().enumerate() {
-                    if i > 0 {
-                        value_str.push(' ');
-                    }
-                    value_str.push('<');
-                    value_str.push_str(name);
-                    value_str.push('>');
-                }
-                value_str
-            } else {
-                let possible_values = arg
-                    .get_value_parser()
-                    .possible_values()
-                    .expect("arg that takes value has either value names or possible values");
-                let mut value_str = String::new();
-                value_str.push('(');
-                for (i, possible_value) in
-                    possible_values.filter(|pv| !pv.is_hide_set()).enumerate()
-                {
-                    if i > 0 {
-                        value_str.push('|');
-                    }
-                    value_str.push_str(possible_value.get_name());
-                }
-                value_str.push(')');
-                value_str
-            }
-        } else {
-            String::new()
-        };
+        let value_str = option_value_str(arg);
         if let Some(shorts) = arg.get_short_and_visible_aliases() {
             for short in shorts {
                 if value_str.is_empty() {
@@ -361,20 +726,14 @@ fn add_options(
                 }
             }
         }
-        let help = make_links(
-            arg.get_long_help()
-                .or_else(|| arg.get_help())
-                .unwrap()
-                .to_string()
-                .as_str(),
-        );
+        let help = make_links(&option_help(arg));
         for (i, para) in paragraphs(&help).enumerate() {
             if i > 0 {
                 section.push_str("+\n");
             }
             for line in para.lines() {
-                let width = if i == 0 { WIDTH - 4 } else { WIDTH };
-                for wrapped_line in wrap(line, width) {
+                let wrap_width = if i == 0 { width - 4 } else { width };
+                for wrapped_line in wrap(line, wrap_width) {
                     if i == 0 {
                         section.push_str("    ");
                     }
@@ -387,58 +746,563 @@ fn add_options(
     }
 }
 
+/// Format the value placeholder of an option, e.g. `<name>` or `(a|b)`.
+///
+/// Returns an empty string for options that do not take a value.
+fn option_value_str(arg: &clap::Arg) -> String {
+    if arg.get_action().takes_values() {
+        if let Some(value_names) = arg.get_value_names() {
+            let mut value_str = String::new();
+            for (i, name) in value_names.iter().enumerate() {
+                if i > 0 {
+                    value_str.push(' ');
+                }
+                value_str.push('<');
+                value_str.push_str(name);
+                value_str.push('>');
+            }
+            value_str
+        } else {
+            let possible_values = arg
+                .get_value_parser()
+                .possible_values()
+                .expect("arg that takes value has either value names or possible values");
+            let mut value_str = String::new();
+            value_str.push('(');
+            for (i, possible_value) in possible_values.filter(|pv| !pv.is_hide_set()).enumerate() {
+                if i > 0 {
+                    value_str.push('|');
+                }
+                value_str.push_str(possible_value.get_name());
+            }
+            value_str.push(')');
+            value_str
+        }
+    } else {
+        String::new()
+    }
+}
+
+/// Get the help text of an option, including any default value.
+fn option_help(arg: &clap::Arg) -> String {
+    let mut help = arg
+        .get_long_help()
+        .or_else(|| arg.get_help())
+        .unwrap()
+        .to_string();
+    let default_values = arg.get_default_values();
+    if arg.get_action().takes_values()
+        && !default_values.is_empty()
+        && !arg.is_hide_default_value_set()
+    {
+        let default_values = default_values
+            .iter()
+            .map(|value| value.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(", ");
+        help.truncate(help.trim_end().len());
+        write!(help, " (default: {default_values})").unwrap();
+    }
+    help
+}
+
+/// Convert command references into asciidoc link macros and escape the remaining text.
+///
+/// References of the form `git-<command>(<section>)`, `stg-<command>(<section>)`,
+/// `'git <command>'`, and `'stg <command>'` become `linkgit:` and `linkstg:` macros.
+/// All other text is escaped with [`escape_text()`].
 fn make_links(text: &str) -> String {
     let mut output = String::new();
+    let mut in_literal = false;
     let mut words = text.split_inclusive([' ', '\n']);
 
     while let Some(word) = words.next() {
-        if let Some(remainder) = word.strip_prefix("git-") {
-            if let Some((command_and_section, trailings)) = remainder.rsplit_once(')') {
-                if let Some((command, man_section)) = command_and_section.split_once('(') {
+        if let Some((program, remainder)) = ["git", "stg"].into_iter().find_map(|program| {
+            word.strip_prefix(program)
+                .and_then(|rest| rest.strip_prefix('-'))
+                .map(|remainder| (program, remainder))
+        }) {
+            if let Some((command, man_section, trailings)) =
+                remainder.split_once('(').and_then(|(command, rest)| {
+                    rest.split_once(')')
+                        .map(|(man_section, trailings)| (command, man_section, trailings))
+                })
+            {
+                if program == "git" {
                     output.push_str(&format!("linkgit:git-{command}[{man_section}]"));
-                    output.push_str(trailings);
                 } else {
-                    output.push_str(word);
+                    output.push_str(&format!("linkstg:{command}[]"));
                 }
+                output.push_str(&escape_text(trailings, &mut in_literal));
             } else {
-                output.push_str(word);
+                output.push_str(&escape_text(word, &mut in_literal));
             }
         } else if word.starts_with("'git") {
             if let Some(next_word) = words.next() {
                 if let Some((command, rest)) = next_word.split_once('\'') {
-                    let link = format!("linkgit:git-{command}[1]{rest}");
+                    let link = format!("linkgit:git-{command}[1]");
                     output.push_str(&link);
+                    output.push_str(&escape_text(rest, &mut in_literal));
                 } else {
-                    output.push_str(word);
-                    output.push_str(next_word);
+                    output.push_str(&escape_text(word, &mut in_literal));
+                    output.push_str(&escape_text(next_word, &mut in_literal));
                 }
             } else {
-                output.push_str(word);
+                output.push_str(&escape_text(word, &mut in_literal));
             }
         } else if word.starts_with("'stg") {
             if let Some(next_word) = words.next() {
                 if let Some((command, rest)) = next_word.split_once('\'') {
-                    let link = format!("linkstg:{command}[]{rest}");
+                    let link = format!("linkstg:{command}[]");
                     output.push_str(&link);
+                    output.push_str(&escape_text(rest, &mut in_literal));
+                } else {
+                    output.push_str(&escape_text(word, &mut in_literal));
+                    output.push_str(&escape_text(next_word, &mut in_literal));
+                }
+            } else {
+                output.push_str(&escape_text(word, &mut in_literal));
+            }
+        } else {
+            output.push_str(&escape_text(word, &mut in_literal));
+        }
+    }
+
+    output
+}
+
+/// Escape characters in help text that asciidoc would otherwise interpret as markup.
+///
+/// Text within backticks is literal and left as-is; `in_literal` tracks whether the
+/// text starts, and upon return ends, within such a literal. Elsewhere, an `{empty}`
+/// attribute reference is inserted to defuse potential markup. This is effective with
+/// both asciidoc and asciidoctor because attribute references are substituted only
+/// after quoted text is processed. The following are escaped:
+///
+/// - `*`, `_`, or `+` at the start of a word, which may begin constrained quoted text.
+/// - Doubled `**`, `__`, or `++`, which may begin unconstrained quoted text.
+/// - `[[`, which begins an anchor.
+/// - `.` at the start of a word, which may begin a block title if wrapped to the
+///   start of a line.
+fn escape_text(text: &str, in_literal: &mut bool) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut prev: Option<char> = None;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let next = chars.peek().copied();
+        if c == '`' {
+            *in_literal = !*in_literal;
+        } else if !*in_literal {
+            let at_word_start = prev.map_or(true, |p| p.is_whitespace() || "(\"'".contains(p));
+            let before_word = next.is_some_and(|n| !n.is_whitespace());
+            match c {
+                '*' | '_' | '+' if at_word_start && before_word => output.push_str("{empty}"),
+                '.' if at_word_start && next.is_some_and(|n| n != '.' && !n.is_whitespace()) => {
+                    output.push_str("{empty}");
+                }
+                _ => {}
+            }
+            output.push(c);
+            if ("*_+".contains(c) || c == '[') && next == Some(c) {
+                output.push_str("{empty}");
+            }
+            prev = Some(c);
+            continue;
+        }
+        output.push(c);
+        prev = Some(c);
+    }
+
+    output
+}
+
+fn generate_aliases_roff(aliases: &crate::alias::Aliases, options: PageOptions) -> String {
+    let width = options.width;
+    let mut page = String::new();
+
+    write_roff_title(&mut page, "stg-aliases");
+
+    page.push_str(".SH NAME\n");
+    page.push_str("stg\\-aliases \\- StGit command aliases\n");
+
+    page.push_str(".SH DESCRIPTION\n");
+    add_roff_paragraphs(&mut page, ALIASES_DESCRIPTION, ".PP", width);
+
+    if !aliases.is_empty() {
+        page.push_str(".SH ALIASES\n");
+        for (name, alias) in aliases {
+            writeln!(page, ".TP\n\\fB{}\\fR", roff_escape(name)).unwrap();
+            let expansion = alias.make().get_about().unwrap_or_default().to_string();
+            for line in wrap(&roff_escape(&expansion), width) {
+                push_roff_line(&mut page, line);
+            }
+        }
+    }
+
+    write_roff_footer(&mut page, options.version);
+
+    page
+}
+
+fn generate_index_roff(entries: &[(String, String)], options: PageOptions) -> String {
+    let width = options.width;
+    let mut page = String::new();
+
+    write_roff_title(&mut page, "stg");
+
+    page.push_str(".SH NAME\n");
+    page.push_str("stg \\- Manage stacks of patches using the Git content tracker\n");
+
+    page.push_str(".SH COMMANDS\n");
+    for (name, about) in entries {
+        writeln!(page, ".TP\n\\fBstg\\-{}\\fR(1)", roff_escape(name)).unwrap();
+        for line in wrap(&roff_text(about), width) {
+            push_roff_line(&mut page, line);
+        }
+    }
+
+    write_roff_footer(&mut page, options.version);
+
+    page
+}
+
+fn generate_roff(command: &mut clap::Command, options: PageOptions) -> String {
+    let width = options.width;
+    let mut page = String::new();
+    let name = command.get_name().to_string();
+
+    write_roff_title(&mut page, &format!("stg-{name}"));
+
+    page.push_str(".SH NAME\n");
+    let about = command.get_about().unwrap().to_string();
+    writeln!(
+        page,
+        "stg\\-{} \\- {}",
+        roff_escape(&name),
+        roff_text(&about)
+    )
+    .unwrap();
+
+    page.push_str(".SH SYNOPSIS\n.nf\n");
+    for usage_line in get_usage_lines(command) {
+        write!(page, "\\fB{}\\fR", roff_escape(&usage_line.command)).unwrap();
+        for usage_word in &usage_line.args {
+            write!(page, " {}", roff_escape(usage_word)).unwrap();
+        }
+        page.push('\n');
+    }
+    page.push_str(".fi\n");
+
+    page.push_str(".SH DESCRIPTION\n");
+    let about = command
+        .get_long_about()
+        .or_else(|| command.get_about())
+        .unwrap()
+        .to_string();
+    add_roff_paragraphs(&mut page, &about, ".PP", width);
+
+    let mut commands_section = String::new();
+    for subcmd in command
+        .get_subcommands()
+        .filter(|&subcmd| subcmd.get_name() != "help")
+    {
+        add_roff_command_stanza(&mut commands_section, subcmd, &[], width);
+    }
+    if !commands_section.is_empty() {
+        page.push_str(".SH COMMANDS\n");
+        page.push_str(&commands_section);
+    }
+
+    add_roff_options(&mut page, command, ".SH", "OPTIONS", width);
+    for subcmd in command
+        .get_subcommands()
+        .filter(|&subcmd| subcmd.get_name() != "help")
+    {
+        add_roff_subcommand_options(&mut page, subcmd, &[], width);
+    }
+
+    let envvars = crate::cmd::STGIT_COMMANDS
+        .iter()
+        .find(|command| command.name == name)
+        .map(|command| command.envvars)
+        .unwrap_or_default();
+    if !envvars.is_empty() {
+        page.push_str(".SH ENVIRONMENT\n");
+        for (envvar, description) in envvars {
+            writeln!(page, ".TP\n\\fB{}\\fR", roff_escape(envvar)).unwrap();
+            add_roff_paragraphs(&mut page, description, ".IP", width);
+        }
+    }
+
+    write_roff_footer(&mut page, options.version);
+
+    page
+}
+
+fn add_roff_command_stanza(
+    section: &mut String,
+    command: &clap::Command,
+    stack: &[&str],
+    width: usize,
+) {
+    let name = command.get_name();
+    let mut has_subcommands = false;
+    for subcmd in command
+        .get_subcommands()
+        .filter(|&subcmd| subcmd.get_name() != "help")
+    {
+        has_subcommands = true;
+        let mut stack = stack.to_vec();
+        stack.push(name);
+        add_roff_command_stanza(section, subcmd, &stack, width);
+    }
+
+    if !has_subcommands {
+        let mut words = stack.to_vec();
+        words.push(name);
+        writeln!(section, ".TP\n\\fB{}\\fR", roff_escape(&words.join(" "))).unwrap();
+        let about = command
+            .get_long_about()
+            .or_else(|| command.get_about())
+            .unwrap()
+            .to_string();
+        add_roff_paragraphs(section, &about, ".IP", width);
+    }
+}
+
+fn add_roff_subcommand_options(
+    section: &mut String,
+    command: &clap::Command,
+    stack: &[&str],
+    width: usize,
+) {
+    let name = command.get_name();
+    let mut has_subcommands = false;
+    for subcmd in command
+        .get_subcommands()
+        .filter(|&subcmd| subcmd.get_name() != "help")
+    {
+        has_subcommands = true;
+        let mut stack = stack.to_vec();
+        stack.push(name);
+        add_roff_subcommand_options(section, subcmd, &stack, width);
+    }
+
+    if !has_subcommands {
+        let mut header = String::new();
+        for &word in stack {
+            header.push_str(&word.to_uppercase());
+            header.push(' ');
+        }
+        header.push_str(&name.to_uppercase());
+        header.push_str(" OPTIONS");
+        add_roff_options(section, command, ".SS", &header, width);
+    }
+}
+
+fn add_roff_options(
+    section: &mut String,
+    command: &clap::Command,
+    header_macro: &str,
+    header_name: &str,
+    width: usize,
+) {
+    for (i, arg) in command
+        .get_arguments()
+        .filter(|arg| {
+            !["help", "color"].contains(&arg.get_id().as_str())
+                && !arg.is_hide_set()
+                && !arg.is_positional()
+        })
+        .enumerate()
+    {
+        if i == 0 {
+            writeln!(section, "{header_macro} \"{}\"", roff_escape(header_name)).unwrap();
+        }
+        let value_str = roff_escape(&option_value_str(arg));
+        let mut terms = Vec::new();
+        if let Some(shorts) = arg.get_short_and_visible_aliases() {
+            for short in shorts {
+                terms.push(if value_str.is_empty() {
+                    format!("\\fB\\-{short}\\fR")
+                } else if arg.is_require_equals_set() {
+                    format!("\\fB\\-{short}\\fR[=\\fI{value_str}\\fR]")
+                } else {
+                    format!("\\fB\\-{short}\\fR \\fI{value_str}\\fR")
+                });
+            }
+        }
+        if let Some(longs) = arg.get_long_and_visible_aliases() {
+            for long in longs {
+                let long = roff_escape(long);
+                terms.push(if value_str.is_empty() {
+                    format!("\\fB\\-\\-{long}\\fR")
+                } else if arg.is_require_equals_set() {
+                    format!("\\fB\\-\\-{long}\\fR[=\\fI{value_str}\\fR]")
                 } else {
-                    output.push_str(word);
-                    output.push_str(next_word);
+                    format!("\\fB\\-\\-{long}\\fR=\\fI{value_str}\\fR")
+                });
+            }
+        }
+        writeln!(section, ".TP\n{}", terms.join(", ")).unwrap();
+        add_roff_paragraphs(section, &option_help(arg), ".IP", width);
+    }
+}
+
+fn write_roff_title(page: &mut String, name: &str) {
+    writeln!(
+        page,
+        ".TH \"{}\" \"1\" \"\" \"StGit\" \"StGit Manual\"",
+        roff_escape(&name.to_uppercase())
+    )
+    .unwrap();
+}
+
+fn write_roff_footer(page: &mut String, version: Option<&str>) {
+    page.push_str(".SH STGIT\n");
+    page.push_str("Part of the StGit suite \\- see \\fBstg\\fR(1)\n");
+    if let Some(version) = version {
+        writeln!(page, ".PP\nStGit version {}", roff_escape(version)).unwrap();
+    }
+}
+
+/// Add the paragraphs of help text to a roff page.
+///
+/// Paragraphs after the first are introduced with `para_macro`, e.g. `.PP` or `.IP`
+/// to keep the indentation of a `.TP` stanza. Indented paragraphs are output verbatim
+/// as literal blocks, otherwise text is converted with [`roff_text()`] and wrapped.
+fn add_roff_paragraphs(page: &mut String, text: &str, para_macro: &str, width: usize) {
+    for (i, para) in paragraphs(text).enumerate() {
+        if i > 0 {
+            page.push_str(para_macro);
+            page.push('\n');
+        }
+        if para.starts_with(' ') {
+            page.push_str(".RS 4\n.nf\n");
+            for line in para.lines() {
+                push_roff_line(page, &roff_escape(line));
+            }
+            page.push_str(".fi\n.RE\n");
+        } else {
+            let para = roff_text(para);
+            for line in para.lines() {
+                for wrapped_line in wrap(line, width) {
+                    push_roff_line(page, wrapped_line);
+                }
+            }
+        }
+    }
+}
+
+/// Push a line of text to a roff page, guarding against it being taken as a request.
+fn push_roff_line(page: &mut String, line: &str) {
+    if line.starts_with(['.', '\'']) {
+        page.push_str("\\&");
+    }
+    page.push_str(line);
+    page.push('\n');
+}
+
+/// Convert command references into roff man page references and escape the text.
+///
+/// The same references as recognized by [`make_links()`] become bold command names
+/// followed by the man page section, e.g. `\fBgit\-rebase\fR(1)`. Text within
+/// backticks is emboldened. All other text is escaped with [`roff_escape()`].
+fn roff_text(text: &str) -> String {
+    let mut output = String::new();
+    let mut in_literal = false;
+    let mut words = text.split_inclusive([' ', '\n']);
+
+    while let Some(word) = words.next() {
+        if let Some((program, command, man_section, trailings)) =
+            ["git", "stg"].into_iter().find_map(|program| {
+                let remainder = word.strip_prefix(program)?.strip_prefix('-')?;
+                let (command, rest) = remainder.split_once('(')?;
+                let (man_section, trailings) = rest.split_once(')')?;
+                Some((program, command, man_section, trailings))
+            })
+        {
+            write!(
+                output,
+                "\\fB{program}\\-{}\\fR({man_section})",
+                roff_escape(command)
+            )
+            .unwrap();
+            output.push_str(&roff_literals(trailings, &mut in_literal));
+        } else if let Some(program) = ["'git", "'stg"]
+            .into_iter()
+            .find(|prefix| word.starts_with(prefix))
+        {
+            if let Some(next_word) = words.next() {
+                if let Some((command, rest)) = next_word.split_once('\'') {
+                    write!(
+                        output,
+                        "\\fB{}\\-{}\\fR(1)",
+                        &program[1..],
+                        roff_escape(command)
+                    )
+                    .unwrap();
+                    output.push_str(&roff_literals(rest, &mut in_literal));
+                } else {
+                    output.push_str(&roff_literals(word, &mut in_literal));
+                    output.push_str(&roff_literals(next_word, &mut in_literal));
                 }
             } else {
-                output.push_str(word);
+                output.push_str(&roff_literals(word, &mut in_literal));
             }
         } else {
-            output.push_str(word);
+            output.push_str(&roff_literals(word, &mut in_literal));
         }
     }
 
+    if in_literal {
+        output.push_str("\\fR");
+    }
+
+    output
+}
+
+/// Escape text for roff, emboldening text within backticks.
+///
+/// `in_literal` tracks whether the text starts, and upon return ends, within
+/// backticks.
+fn roff_literals(text: &str, in_literal: &mut bool) -> String {
+    let mut output = String::with_capacity(text.len());
+    for (i, part) in text.split('`').enumerate() {
+        if i > 0 {
+            *in_literal = !*in_literal;
+            output.push_str(if *in_literal { "\\fB" } else { "\\fR" });
+        }
+        output.push_str(&roff_escape(part));
+    }
+    output
+}
+
+/// Escape backslashes and hyphens for roff.
+///
+/// Hyphens are escaped such that they are rendered as ASCII minus signs, which keeps
+/// command line options copy-pasteable.
+fn roff_escape(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => output.push_str("\\e"),
+            '-' => output.push_str("\\-"),
+            _ => output.push(c),
+        }
+    }
     output
 }
 
+/// Write `line` followed by an underline matching the line's display width.
+///
+/// The display width is used, rather than the number of chars, such that headers with
+/// wide characters are fully underlined.
 fn write_underlined(stream: &mut String, line: &str, underline_char: char) {
     stream.push_str(line);
     stream.push('\n');
-    for _ in line.chars() {
+    for _ in 0..line.width() {
         stream.push(underline_char);
     }
     stream.push('\n');
@@ -469,6 +1333,11 @@ fn paragraphs(text: &str) -> Paragraphs<'_> {
     Paragraphs { text }
 }
 
+/// Iterator over the lines of text wrapped at a given width.
+///
+/// Lines are only broken at spaces. Spaces within the brackets of `linkgit:` and
+/// `linkstg:` macros are not considered, such that a link macro, along with any
+/// adjacent punctuation, never straddles a line boundary.
 struct WrappedLines<'a> {
     text: &'a str,
     width: usize,
@@ -483,8 +1352,15 @@ impl<'a> Iterator for WrappedLines<'a> {
         } else {
             let mut last_space_index = 0;
             let mut reached_end = true;
+            let mut in_link_macro = false;
             for (i, c) in self.text.char_indices() {
-                if c == ' ' {
+                if in_link_macro {
+                    in_link_macro = c != ']';
+                } else if self.text[i..].starts_with("linkgit:")
+                    || self.text[i..].starts_with("linkstg:")
+                {
+                    in_link_macro = true;
+                } else if c == ' ' {
                     if i < self.width {
                         last_space_index = i;
                     } else {
@@ -510,3 +1386,131 @@ impl<'a> Iterator for WrappedLines<'a> {
 fn wrap(text: &str, width: usize) -> WrappedLines<'_> {
     WrappedLines { text, width }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        add_roff_paragraphs, make_links, normalize_final_newline, roff_escape, roff_text, wrap,
+        write_underlined,
+    };
+
+    #[test]
+    fn links() {
+        assert_eq!(
+            make_links("See git-var(1) and 'git rebase' or 'stg push'."),
+            "See linkgit:git-var[1] and linkgit:git-rebase[1] or linkstg:push[]."
+        );
+        assert_eq!(
+            make_links("(see GIT_EDITOR in git-var(1)) to edit"),
+            "(see GIT_EDITOR in linkgit:git-var[1]) to edit"
+        );
+    }
+
+    #[test]
+    fn final_newline() {
+        for page in ["text", "text\n", "text\n\n\n"] {
+            assert_eq!(normalize_final_newline(page.to_string(), true), "text\n");
+            assert_eq!(normalize_final_newline(page.to_string(), false), "text");
+        }
+    }
+
+    #[test]
+    fn underline_wide_chars() {
+        let mut page = String::new();
+        write_underlined(&mut page, "NAME", '-');
+        write_underlined(&mut page, "名前 x", '=');
+        assert_eq!(page, "NAME\n----\n名前 x\n======\n");
+    }
+
+    #[test]
+    fn wrap_keeps_link_macros_intact() {
+        let text = make_links("Values are passed to git-rev-parse(1). Then more text.");
+        assert_eq!(
+            wrap(&text, 28).collect::<Vec<_>>(),
+            [
+                "Values are passed to",
+                "linkgit:git-rev-parse[1].",
+                "Then more text."
+            ]
+        );
+        assert_eq!(
+            wrap(
+                "see the linkgit:git-config[1, section two] page for details",
+                38
+            )
+            .collect::<Vec<_>>(),
+            [
+                "see the",
+                "linkgit:git-config[1, section two]",
+                "page for details"
+            ]
+        );
+    }
+
+    #[test]
+    fn escape_quoted_text() {
+        assert_eq!(
+            make_links("rejects in *.rej files"),
+            "rejects in {empty}*.rej files"
+        );
+        assert_eq!(
+            make_links("prefixed with '+' or _emphasis_"),
+            "prefixed with '{empty}+' or {empty}_emphasis_"
+        );
+        assert_eq!(make_links("C++ and a**b"), "C+{empty}+ and a*{empty}*b");
+        assert_eq!(make_links("GIT_EDITOR a + b"), "GIT_EDITOR a + b");
+    }
+
+    #[test]
+    fn escape_anchor_and_block_title() {
+        assert_eq!(make_links("an [[anchor]]"), "an [{empty}[anchor]]");
+        assert_eq!(
+            make_links("suffix \".patch\" or .rej but not ..."),
+            "suffix \"{empty}.patch\" or {empty}.rej but not ..."
+        );
+    }
+
+    #[test]
+    fn literal_text_not_escaped() {
+        assert_eq!(
+            make_links("configured with `stgit.alias.*` or `*.rej` and *.rej"),
+            "configured with `stgit.alias.*` or `*.rej` and {empty}*.rej"
+        );
+    }
+
+    #[test]
+    fn stg_man_page_links() {
+        assert_eq!(
+            make_links("See stg-push(1), git-am(1), or stg-x."),
+            "See linkstg:push[], linkgit:git-am[1], or stg-x."
+        );
+    }
+
+    #[test]
+    fn roff_escapes() {
+        assert_eq!(roff_escape("--foo a\\b"), "\\-\\-foo a\\eb");
+        assert_eq!(
+            roff_text("use `--all` with 'stg push', stg-pop(1), or git-rebase(1)."),
+            "use \\fB\\-\\-all\\fR with \\fBstg\\-push\\fR(1), \\fBstg\\-pop\\fR(1), or \\fBgit\\-rebase\\fR(1)."
+        );
+        assert_eq!(
+            roff_text("unterminated `literal"),
+            "unterminated \\fBliteral\\fR"
+        );
+    }
+
+    #[test]
+    fn roff_paragraphs() {
+        let mut page = String::new();
+        add_roff_paragraphs(
+            &mut page,
+            "First.\n\n.second 'quoted'\n\n  indented",
+            ".IP",
+            80,
+        );
+        assert_eq!(
+            page,
+            "First.\n.IP\n\\&.second 'quoted'\n.IP\n.RS 4\n.nf\n  indented\n.fi\n.RE\n"
+        );
+    }
+}
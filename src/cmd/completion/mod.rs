@@ -18,6 +18,7 @@ pub(super) const STGIT_COMMAND: super::StGitCommand = super::StGitCommand {
     category: super::CommandCategory::Administration,
     make,
     run,
+    envvars: &[],
 };
 
 fn make() -> clap::Command {
@@ -28,7 +29,9 @@ fn make() -> clap::Command {
              completion list' command for dynamically introspecting StGit's \
              commands and aliases.",
         )
-        .subcommand_required(true)
+        .subcommand_required(false)
+        .arg_required_else_help(true)
+        .args_conflicts_with_subcommands(true)
         .subcommand(bash::command())
         .subcommand(fish::command())
         .subcommand(zsh::command())
@@ -44,6 +47,58 @@ fn make() -> clap::Command {
                 .value_hint(clap::ValueHint::FilePath)
                 .value_parser(clap::value_parser!(PathBuf)),
         )
+        .arg(
+            clap::Arg::new("list-patches")
+                .long("list-patches")
+                .help("List the names of all patches, one per line")
+                .long_help(
+                    "List the names of the stack's applied, unapplied, and hidden \
+                     patches, one per line. This is used by the shell completion \
+                     scripts to dynamically complete patch names.\n\
+                     \n\
+                     The listed patches may be limited with '--applied', \
+                     '--unapplied', and '--hidden'.",
+                )
+                .hide(true)
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("applied")
+                .long("applied")
+                .help("List applied patches with '--list-patches'")
+                .hide(true)
+                .action(clap::ArgAction::SetTrue)
+                .requires("list-patches"),
+        )
+        .arg(
+            clap::Arg::new("unapplied")
+                .long("unapplied")
+                .help("List unapplied patches with '--list-patches'")
+                .hide(true)
+                .action(clap::ArgAction::SetTrue)
+                .requires("list-patches"),
+        )
+        .arg(
+            clap::Arg::new("hidden")
+                .long("hidden")
+                .help("List hidden patches with '--list-patches'")
+                .hide(true)
+                .action(clap::ArgAction::SetTrue)
+                .requires("list-patches"),
+        )
+        .arg(
+            clap::Arg::new("description")
+                .long("description")
+                .help("Follow each patch name with a tab and its short description")
+                .hide(true)
+                .action(clap::ArgAction::SetTrue)
+                .requires("list-patches"),
+        )
+        .arg(
+            crate::argset::branch_arg()
+                .hide(true)
+                .requires("list-patches"),
+        )
 }
 
 fn run(matches: &clap::ArgMatches) -> Result<()> {
@@ -53,6 +108,7 @@ fn run(matches: &clap::ArgMatches) -> Result<()> {
         Some(("zsh", sub_matches)) => zsh::dispatch(sub_matches),
         Some(("list", sub_matches)) => list::dispatch(sub_matches),
         Some(("man", sub_matches)) => man::dispatch(sub_matches),
+        None if matches.get_flag("list-patches") => list::list_patches(matches),
         _ => panic!("valid subcommand is required"),
     }
 }
@@ -20,6 +20,7 @@ pub(super) const STGIT_COMMAND: super::StGitCommand = super::StGitCommand {
     category: super::CommandCategory::PatchInspection,
     make,
     run,
+    envvars: &[],
 };
 
 fn make() -> clap::Command {
@@ -17,7 +17,7 @@ use clap::Arg;
 use crate::{
     argset,
     branchloc::BranchLocator,
-    ext::{CommitExtended, RepositoryExtended},
+    ext::{CommitExtended, RepositoryExtended, TimeExtended},
     patch::{patchrange, PatchRange, RangeConstraint},
     stack::{InitializationPolicy, Stack, StackAccess, StackStateAccess},
     stupid::Stupid,
@@ -28,6 +28,7 @@ pub(super) const STGIT_COMMAND: super::StGitCommand = super::StGitCommand {
     category: super::CommandCategory::StackInspection,
     make,
     run,
+    envvars: &[],
 };
 
 fn make() -> clap::Command {
@@ -51,6 +52,7 @@ fn make() -> clap::Command {
              \n    %(authname)s    - author name\
              \n    %(authemail)s   - author email\
              \n    %(authdate)s    - patch creation date (ISO-8601 format)\
+             \n    %(authdate_rfc2822)s - patch creation date (RFC 2822 format)\
              \n    %(commname)s    - committer name\
              \n    %(commemail)s   - committer email",
         )
@@ -240,6 +242,10 @@ fn run(matches: &clap::ArgMatches) -> Result<()> {
                     .into(),
             ),
         );
+        replacements.insert(
+            "authdate_rfc2822",
+            Cow::Owned(author.time()?.rfc2822_string().into()),
+        );
         let committer = patch_commit.committer()?;
         replacements.insert("commname", Cow::Borrowed(committer.name));
         replacements.insert("commemail", Cow::Borrowed(committer.email));
@@ -20,6 +20,7 @@ pub(super) const STGIT_COMMAND: super::StGitCommand = super::StGitCommand {
     category: super::CommandCategory::PatchInspection,
     make,
     run,
+    envvars: &[],
 };
 
 fn make() -> clap::Command {
@@ -72,11 +73,17 @@ fn run(matches: &ArgMatches) -> Result<()> {
     let mut output = repo.stupid().diff_tree_files_status(
         parent.tree_id()?.detach(),
         commit.tree_id()?.detach(),
+        None::<&[&str]>,
         matches.get_flag("stat"),
         matches.get_flag("bare"),
         crate::color::use_color(matches),
     )?;
 
+    write_files_status(&mut output)
+}
+
+/// Write `git diff-tree --name-status` output to stdout in `stg files` format.
+pub(super) fn write_files_status(output: &mut [u8]) -> Result<()> {
     let stdout = std::io::stdout();
     let mut stdout = stdout.lock();
     for line in output.split_inclusive_mut(|b| *b == b'\t') {
@@ -21,6 +21,7 @@ pub(super) const STGIT_COMMAND: super::StGitCommand = super::StGitCommand {
     category: super::CommandCategory::PatchManipulation,
     make,
     run,
+    envvars: &[],
 };
 
 fn make() -> clap::Command {
@@ -147,7 +148,13 @@ fn run(matches: &clap::ArgMatches) -> Result<()> {
         };
         let applied_tree_id = stupid.write_tree()?;
         stupid.read_tree_checkout(applied_tree_id, orig_head_tree_id)?;
-        if !stupid.merge_recursive(base_tree_id, orig_head_tree_id, applied_tree_id)? {
+        if !stupid.merge_recursive(
+            base_tree_id,
+            orig_head_tree_id,
+            applied_tree_id,
+            std::iter::empty::<&str>(),
+            None,
+        )? {
             return Err(super::Error::CausedConflicts("merge conflicts".to_string()).into());
         }
         if !applied_cleanly {
@@ -0,0 +1,95 @@
+// SPDX-License-Identifier: GPL-2.0-only
+
+//! `stg hook` implementation.
+
+use anyhow::{anyhow, Result};
+use clap::{Arg, ArgMatches};
+
+use crate::ext::RepositoryExtended;
+
+pub(super) const STGIT_COMMAND: super::StGitCommand = super::StGitCommand {
+    name: "hook",
+    category: super::CommandCategory::Administration,
+    make,
+    run,
+    envvars: &[],
+};
+
+fn make() -> clap::Command {
+    clap::Command::new(STGIT_COMMAND.name)
+        .about("Inspect the git hooks used by StGit")
+        .long_about(
+            "Inspect the git hooks used by StGit.\n\
+             \n\
+             StGit runs the 'pre-commit' and 'commit-msg' hooks when creating or \
+             editing patches. The 'path' subcommand may be used to determine where \
+             StGit looks for a hook script, which can be helpful when a hook does not \
+             run as expected.",
+        )
+        .subcommand_required(true)
+        .subcommand(
+            clap::Command::new("path")
+                .about("Show the path of a hook script")
+                .long_about(
+                    "Show the resolved path of the executable script for the given \
+                     hook. It is an error if no executable script is found for the \
+                     hook. Hook scripts are found in the directory specified by the \
+                     'core.hooksPath' config variable, if set, or in the repository's \
+                     'hooks' directory otherwise. Any directories specified by the \
+                     'stgit.hooksPath' config variable, which may be given multiple \
+                     times, are searched afterwards in the order they are configured.",
+                )
+                .arg(
+                    Arg::new("hook-name")
+                        .help("Name of the hook, e.g. 'pre-commit'")
+                        .value_name("hook-name")
+                        .required(true),
+                ),
+        )
+}
+
+fn run(matches: &ArgMatches) -> Result<()> {
+    match matches.subcommand() {
+        Some(("path", sub_matches)) => show_path(sub_matches),
+        _ => panic!("valid subcommand is required"),
+    }
+}
+
+fn show_path(matches: &ArgMatches) -> Result<()> {
+    let repo = gix::Repository::open()?;
+    let hook_name = matches
+        .get_one::<String>("hook-name")
+        .expect("required argument");
+
+    let hooks_dirs = crate::hook::get_hooks_dirs(&repo)?;
+    let describe = |hooks_dir: &crate::hook::HooksDir| {
+        if let Some(source) = hooks_dir.source {
+            format!("`{}` (set by {source})", hooks_dir.path.display())
+        } else {
+            format!("`{}`", hooks_dir.path.display())
+        }
+    };
+
+    if let Some(hook_path) = crate::hook::get_hook_path(&repo, hook_name)? {
+        if let Some(hooks_dir) = hooks_dirs.iter().find(|hooks_dir| {
+            hooks_dir.source.is_some()
+                && gix::path::realpath(hooks_dir.path.join(hook_name))
+                    .ok()
+                    .as_ref()
+                    == Some(&hook_path)
+        }) {
+            crate::print_info_message(
+                matches,
+                &format!("using hooks directory {}", describe(hooks_dir)),
+            );
+        }
+        println!("{}", hook_path.display());
+        Ok(())
+    } else {
+        let searched: Vec<String> = hooks_dirs.iter().map(describe).collect();
+        Err(anyhow!(
+            "no executable `{hook_name}` hook found in {}",
+            searched.join(", ")
+        ))
+    }
+}
@@ -17,6 +17,7 @@ pub(super) const STGIT_COMMAND: super::StGitCommand = super::StGitCommand {
     category: super::CommandCategory::StackManipulation,
     make,
     run,
+    envvars: &[],
 };
 
 fn make() -> clap::Command {
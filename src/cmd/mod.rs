@@ -21,6 +21,7 @@ pub(crate) mod float;
 pub(crate) mod fold;
 pub(crate) mod goto;
 pub(crate) mod hide;
+pub(crate) mod hook;
 pub(crate) mod id;
 pub(crate) mod import;
 pub(crate) mod init;
@@ -77,6 +78,11 @@ pub(crate) struct StGitCommand {
 
     /// Function pointer for running the StGit subcommand.
     pub run: fn(&clap::ArgMatches) -> anyhow::Result<()>,
+
+    /// Environment variables read by the subcommand, as `(name, description)` pairs.
+    ///
+    /// These are documented in the subcommand's man page.
+    pub envvars: &'static [(&'static str, &'static str)],
 }
 
 /// Builtin [`StGitCommand`]'s.
@@ -98,6 +104,7 @@ pub(crate) const STGIT_COMMANDS: &[StGitCommand] = &[
     fold::STGIT_COMMAND,
     goto::STGIT_COMMAND,
     hide::STGIT_COMMAND,
+    hook::STGIT_COMMAND,
     id::STGIT_COMMAND,
     import::STGIT_COMMAND,
     init::STGIT_COMMAND,
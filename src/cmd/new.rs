@@ -21,6 +21,7 @@ pub(super) const STGIT_COMMAND: super::StGitCommand = super::StGitCommand {
     category: super::CommandCategory::PatchManipulation,
     make,
     run,
+    envvars: crate::patch::patchedit::EDITOR_ENVVARS,
 };
 
 fn make() -> clap::Command {
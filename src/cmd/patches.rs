@@ -24,6 +24,7 @@ pub(super) const STGIT_COMMAND: super::StGitCommand = super::StGitCommand {
     category: super::CommandCategory::StackInspection,
     make,
     run,
+    envvars: &[],
 };
 
 fn make() -> clap::Command {
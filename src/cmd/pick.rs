@@ -29,6 +29,7 @@ pub(super) const STGIT_COMMAND: super::StGitCommand = super::StGitCommand {
     category: super::CommandCategory::StackManipulation,
     make,
     run,
+    envvars: &[],
 };
 
 fn make() -> clap::Command {
@@ -214,7 +215,7 @@ fn fold_picks(stack: &Stack, matches: &clap::ArgMatches, picks: &[StGitRevision]
         } else {
             assert!(matches.get_flag("update"));
             let branch_head = stack.get_branch_head();
-            diff_files = stupid.diff_tree_files(
+            diff_files = stupid.diff_tree_names(
                 branch_head.get_parent_commit()?.tree_id()?.detach(),
                 branch_head.tree_id()?.detach(),
             )?;
@@ -11,7 +11,7 @@ use crate::{
     ext::RepositoryExtended,
     patch::{patchrange, PatchName, PatchRange, RangeConstraint},
     stack::{InitializationPolicy, Stack, StackStateAccess},
-    stupid::Stupid,
+    stupid::{ConflictStyle, Stupid},
 };
 
 pub(super) const STGIT_COMMAND: super::StGitCommand = super::StGitCommand {
@@ -19,6 +19,7 @@ pub(super) const STGIT_COMMAND: super::StGitCommand = super::StGitCommand {
     category: super::CommandCategory::StackManipulation,
     make,
     run,
+    envvars: &[],
 };
 
 fn make() -> clap::Command {
@@ -114,6 +115,7 @@ fn make() -> clap::Command {
         .arg(argset::merged_arg())
         .arg(argset::committer_date_is_author_date_arg())
         .arg(argset::push_conflicts_arg())
+        .arg(argset::conflict_style_arg())
 }
 
 fn run(matches: &ArgMatches) -> Result<()> {
@@ -191,6 +193,7 @@ fn run(matches: &ArgMatches) -> Result<()> {
         .setup_transaction()
         .use_index_and_worktree(true)
         .allow_push_conflicts(allow_push_conflicts)
+        .conflict_style(matches.get_one::<ConflictStyle>("conflict-style").copied())
         .committer_date_is_author_date(matches.get_flag("committer-date-is-author-date"))
         .with_output_stream(get_color_stdout(matches))
         .transact(|trans| {
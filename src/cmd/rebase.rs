@@ -23,6 +23,7 @@ pub(super) const STGIT_COMMAND: super::StGitCommand = super::StGitCommand {
     category: super::CommandCategory::StackManipulation,
     make,
     run,
+    envvars: crate::patch::patchedit::EDITOR_ENVVARS,
 };
 
 fn make() -> clap::Command {
@@ -512,11 +513,17 @@ fn interactive_pushback(
                 let target_patchname = &squash_patchnames[0];
 
                 let dummy_squash_command = clap::Command::new("dummy-squash");
-                let dummy_squash_command = patchedit::add_args(dummy_squash_command, true, false);
+                let dummy_squash_command = patchedit::add_args(
+                    super::squash::add_squash_args(dummy_squash_command),
+                    true,
+                    false,
+                );
                 let squash_matches = match instruction.action {
-                    Action::Squash => {
-                        dummy_squash_command.try_get_matches_from(["dummy-squash", "--edit"])
-                    }
+                    Action::Squash => dummy_squash_command.try_get_matches_from([
+                        "dummy-squash",
+                        "--edit",
+                        "--keep-empty",
+                    ]),
                     Action::Fixup => {
                         let commit = stack.get_patch_commit(target_patchname);
                         let message = commit.message_raw()?.to_str().map_err(|_| {
@@ -524,6 +531,8 @@ fn interactive_pushback(
                         })?;
                         dummy_squash_command.try_get_matches_from([
                             "dummy-squash",
+                            "--keep-empty",
+                            "--no-coauthors",
                             "--message",
                             message,
                         ])
@@ -541,7 +550,9 @@ fn interactive_pushback(
                             &squash_matches,
                             &squash_patchnames,
                             Some(target_patchname),
+                            None,
                             false,
+                            None,
                         )?;
                         instructions[index - 1] = Instruction {
                             action: Action::Keep,
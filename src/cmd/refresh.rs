@@ -15,10 +15,10 @@ use crate::{
     argset,
     color::get_color_stdout,
     ext::{CommitExtended, RepositoryExtended, SignatureExtended},
-    hook::run_pre_commit_hook,
+    hook::{run_pre_commit_hook, PreCommitHookOutcome},
     patch::{patchedit, LocationConstraint, PatchLocator, PatchName},
     stack::{InitializationPolicy, Stack, StackAccess, StackStateAccess},
-    stupid::{Status, StatusOptions, Statuses, Stupid, StupidContext},
+    stupid::{DiffFileStatus, Status, StatusOptions, Statuses, Stupid, StupidContext},
     wrap::Message,
 };
 
@@ -27,6 +27,7 @@ pub(super) const STGIT_COMMAND: super::StGitCommand = super::StGitCommand {
     category: super::CommandCategory::PatchManipulation,
     make,
     run,
+    envvars: crate::patch::patchedit::EDITOR_ENVVARS,
 };
 
 fn make() -> clap::Command {
@@ -350,7 +351,13 @@ fn determine_refresh_paths(
         let parent_tree_id = patch_commit.get_parent_commit()?.tree_id()?.detach();
         let diff_files =
             stupid.diff_tree_files(parent_tree_id, patch_commit.tree_id()?.detach())?;
-        let patch_paths = diff_files.iter().collect::<IndexSet<_>>();
+        let mut patch_paths = IndexSet::new();
+        for (status, path) in &diff_files {
+            if let DiffFileStatus::Renamed { from } = status {
+                patch_paths.insert(from.as_path());
+            }
+            patch_paths.insert(path.as_path());
+        }
 
         statuses
             .iter()
@@ -469,14 +476,25 @@ pub(crate) fn assemble_refresh_tree(
 
     let tree_id = write_tree(stack, &refresh_paths, is_path_limiting)?;
 
-    let tree_id = if matches.get_flag("no-verify")
-        || !run_pre_commit_hook(stack.repo, matches.get_flag("edit"))?
-        || !stupid.diff_index_quiet(tree_id)?
-    {
-        tree_id
-    } else {
+    let hook_outcome = run_pre_commit_hook(
+        stack.repo,
+        matches.get_flag("edit"),
+        matches.get_flag("no-verify"),
+    )?;
+    let tree_id = match hook_outcome {
+        PreCommitHookOutcome::NotRun | PreCommitHookOutcome::Unchanged => tree_id,
         // Update index and rewrite tree if hook updated files in index
-        write_tree(stack, &refresh_paths, is_path_limiting)?
+        PreCommitHookOutcome::IndexChanged => write_tree(stack, &refresh_paths, is_path_limiting)?,
+        PreCommitHookOutcome::WorktreeChanged => {
+            // As with `git commit`, changes the hook made only to the worktree are not
+            // included in the refreshed patch.
+            crate::print_warning_message(
+                matches,
+                "`pre-commit` hook modified the worktree; \
+                 unstaged changes are not included in the refresh",
+            );
+            tree_id
+        }
     };
 
     Ok(tree_id)
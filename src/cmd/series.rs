@@ -24,6 +24,7 @@ pub(super) const STGIT_COMMAND: super::StGitCommand = super::StGitCommand {
     category: super::CommandCategory::StackInspection,
     make,
     run,
+    envvars: &[],
 };
 
 fn make() -> clap::Command {
@@ -541,7 +542,7 @@ fn run(matches: &ArgMatches) -> Result<()> {
         Default::default()
     };
 
-    let mut stdout = crate::color::get_color_stdout(matches);
+    let mut stdout = crate::color::get_color_stdout_writer(matches);
     let mut color_spec = termcolor::ColorSpec::new();
 
     if matches.get_flag("reverse") {
@@ -11,9 +11,10 @@ use crate::{
     argset,
     branchloc::BranchLocator,
     ext::RepositoryExtended,
+    pager::Pager,
     patch::{RangeConstraint, RangeRevisionSpec},
     stack::{InitializationPolicy, Stack, StackAccess, StackStateAccess},
-    stupid::Stupid,
+    stupid::{ShowMode, Stupid},
 };
 
 pub(super) const STGIT_COMMAND: super::StGitCommand = super::StGitCommand {
@@ -21,6 +22,7 @@ pub(super) const STGIT_COMMAND: super::StGitCommand = super::StGitCommand {
     category: super::CommandCategory::PatchInspection,
     make,
     run,
+    envvars: &[],
 };
 
 fn make() -> clap::Command {
@@ -83,6 +85,25 @@ fn make() -> clap::Command {
                 .help("Show a diffstat summary instead of the full diff")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("name-status")
+                .long("name-status")
+                .help("Show only the names and status of changed files")
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with("stat"),
+        )
+        .arg(
+            Arg::new("follow")
+                .long("follow")
+                .help("Show renamed files as renames")
+                .long_help(
+                    "Detect renames such that renamed files are shown as renames \
+                     instead of as a deletion and an addition. When limited to a \
+                     single <path>, the rename is shown even if the rename source is \
+                     outside of <path>.",
+                )
+                .action(clap::ArgAction::SetTrue),
+        )
         .arg(argset::diff_opts_arg())
         .next_help_heading("Selection Options")
         .arg(
@@ -114,7 +135,13 @@ fn run(matches: &ArgMatches) -> Result<()> {
     let stack =
         Stack::from_branch_locator(&repo, opt_branch, InitializationPolicy::AllowUninitialized)?;
 
-    let stat_flag = matches.get_flag("stat");
+    let mode = if matches.get_flag("stat") {
+        ShowMode::Stat
+    } else if matches.get_flag("name-status") {
+        ShowMode::NameStatus
+    } else {
+        ShowMode::Patch
+    };
     let applied_flag = matches.get_flag("applied");
     let unapplied_flag = matches.get_flag("unapplied");
     let hidden_flag = matches.get_flag("hidden");
@@ -157,8 +184,10 @@ fn run(matches: &ArgMatches) -> Result<()> {
     repo.stupid().show(
         oids,
         matches.get_many::<PathBuf>("pathspecs"),
-        stat_flag,
-        crate::color::use_color(matches),
+        mode,
+        crate::color::get_color_choice(Some(matches)),
+        matches.get_flag("follow"),
         argset::get_diff_opts(matches, &repo.config_snapshot(), false, false),
+        &mut Pager::start(&repo),
     )
 }
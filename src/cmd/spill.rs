@@ -2,17 +2,23 @@
 
 //! `stg spill` implementation.
 
-use std::path::PathBuf;
+use std::{ffi::OsString, path::PathBuf, rc::Rc};
 
-use anyhow::Result;
-use clap::{Arg, ArgMatches};
+use anyhow::{anyhow, Result};
+use bstr::ByteSlice;
+use clap::{Arg, ArgMatches, ValueHint};
 
 use crate::{
     argset,
     color::get_color_stdout,
     ext::{CommitExtended, RepositoryExtended},
-    stack::{InitializationPolicy, Stack, StackStateAccess},
+    patch::{patchedit, LocationConstraint, PatchLocator, PatchName},
+    stack::{
+        compute_spilled_tree, InitializationPolicy, Stack, StackAccess, StackState,
+        StackStateAccess,
+    },
     stupid::Stupid,
+    wrap::Message,
 };
 
 pub(super) const STGIT_COMMAND: super::StGitCommand = super::StGitCommand {
@@ -20,6 +26,7 @@ pub(super) const STGIT_COMMAND: super::StGitCommand = super::StGitCommand {
     category: super::CommandCategory::PatchManipulation,
     make,
     run,
+    envvars: crate::patch::patchedit::EDITOR_ENVVARS,
 };
 
 fn make() -> clap::Command {
@@ -29,8 +36,22 @@ fn make() -> clap::Command {
             "Spill changes from the topmost patch. Changes are removed from the patch, \
              but remain in the index and worktree.\n\
              \n\
+             Use '--patch' to spill an applied patch other than the topmost patch. \
+             Any patches above the spilled patch are popped and pushed back, which \
+             will fail if the spilled changes are needed by those patches.\n\
+             \n\
              Spilling a patch may be useful for reselecting the files/hunks to be \
-             included in the patch.",
+             included in the patch.\n\
+             \n\
+             With '--to-patch', the spilled changes are instead moved into a new \
+             patch inserted below the spilled patch, leaving the index and worktree \
+             untouched.\n\
+             \n\
+             Use '--edit' to revise the patch's message to reflect its reduced \
+             set of changes.\n\
+             \n\
+             Use '--undo' to re-absorb the changes of the most recent spill of the \
+             patch.",
         )
         .arg(
             Arg::new("annotate")
@@ -40,6 +61,17 @@ fn make() -> clap::Command {
                 .num_args(1..)
                 .value_name("note"),
         )
+        .arg(
+            Arg::new("patch")
+                .long("patch")
+                .short('p')
+                .help("Spill (applied) <patch> instead of the top patch")
+                .num_args(1)
+                .value_name("patch")
+                .value_hint(ValueHint::Other)
+                .allow_hyphen_values(true)
+                .value_parser(clap::value_parser!(PatchLocator)),
+        )
         .arg(
             Arg::new("reset")
                 .long("reset")
@@ -52,6 +84,97 @@ fn make() -> clap::Command {
                 )
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("keep-index")
+                .long("keep-index")
+                .help("Keep the spilled changes in the index")
+                .long_help(
+                    "Keep the spilled changes in both the index and worktree. This \
+                     is the default behavior; the option is the explicit inverse of \
+                     '--reset', which leaves the spilled changes only in the worktree.",
+                )
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with("reset")
+                .conflicts_with("to-patch"),
+        )
+        .arg(
+            Arg::new("to-patch")
+                .long("to-patch")
+                .help("Spill changes into a new patch below the spilled patch")
+                .long_help(
+                    "Instead of leaving the spilled changes in the index and worktree, \
+                     move them into a new patch named <name> which is inserted \
+                     immediately below the spilled patch.",
+                )
+                .value_name("name")
+                .value_parser(clap::value_parser!(PatchName))
+                .conflicts_with("reset"),
+        )
+        .arg(
+            Arg::new("interactive")
+                .long("interactive")
+                .short('i')
+                .help("Interactively select hunks to spill")
+                .long_help(
+                    "Interactively select the hunks of the patch to be spilled. Each \
+                     hunk is presented in the manner of 'git reset --patch'; selected \
+                     hunks are spilled while the remaining hunks are kept in the patch. \
+                     Pathspecs may be used to limit which files' hunks are presented.",
+                )
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with_all(["to-patch", "dry-run"]),
+        )
+        .arg(
+            Arg::new("dry-run")
+                .long("dry-run")
+                .short('n')
+                .help("List the files that would be spilled")
+                .long_help(
+                    "List the files that would be spilled along with their change \
+                     status, without modifying the stack, index, or worktree.",
+                )
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("edit")
+                .long("edit")
+                .short('e')
+                .help("Invoke editor for the spilled patch's description")
+                .long_help(
+                    "Invoke an editor on the spilled patch's description such that \
+                     it may be updated to reflect the changes that remain in the \
+                     patch.",
+                )
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with_all(["to-patch", "dry-run"]),
+        )
+        .arg(
+            Arg::new("undo")
+                .long("undo")
+                .help("Restore the patch as it was before it was last spilled")
+                .long_help(
+                    "Restore the patch to its state from before it was most recently \
+                     spilled, as recorded in the stack's log, thus re-absorbing the \
+                     spilled changes. The re-absorbed paths are reset in the index to \
+                     match the restored patch while other staged changes and the \
+                     worktree are left untouched.\n\
+                     \n\
+                     The patch must not have been modified since it was spilled. A \
+                     spill with '--to-patch' cannot be undone this way; use \
+                     'stg squash' or 'stg undo' instead.",
+                )
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with_all([
+                    "reset",
+                    "keep-index",
+                    "to-patch",
+                    "interactive",
+                    "dry-run",
+                    "edit",
+                    "committer-date-is-author-date",
+                    "pathspecs",
+                ]),
+        )
         .arg(argset::committer_date_is_author_date_arg())
         .arg(
             Arg::new("pathspecs")
@@ -70,37 +193,58 @@ fn run(matches: &ArgMatches) -> Result<()> {
     repo.check_repository_state()?;
     let statuses = stupid.statuses(None)?;
     statuses.check_conflicts()?;
-    statuses.check_index_clean()?;
+    // Spilled changes are expected to remain in the index when undoing a spill.
+    if !matches.get_flag("undo") {
+        statuses.check_index_clean()?;
+    }
     stack.check_head_top_mismatch()?;
 
-    let patchname = stack
-        .applied()
-        .last()
-        .ok_or(super::Error::NoAppliedPatches)?
-        .clone();
+    let patchname = if let Some(patch_loc) = matches.get_one::<PatchLocator>("patch") {
+        patch_loc
+            .resolve_name(&stack)?
+            .constrain(&stack, LocationConstraint::Applied)?
+    } else {
+        stack
+            .applied()
+            .last()
+            .ok_or(super::Error::NoAppliedPatches)?
+            .clone()
+    };
+
+    if matches.get_flag("undo") {
+        return undo_spill(stack, &patchname, matches);
+    }
+
+    let to_patchname = if let Some(new_patchname) = matches.get_one::<PatchName>("to-patch") {
+        if let Some(colliding_patchname) = stack.collides(new_patchname) {
+            return Err(anyhow!("patch `{colliding_patchname}` already exists"));
+        }
+        Some(new_patchname.clone())
+    } else {
+        None
+    };
+
     let patch_commit = stack.get_patch_commit(&patchname);
     let patch_commit_ref = patch_commit.decode()?;
     let parent = patch_commit.get_parent_commit()?;
     let parent_commit_ref = parent.decode()?;
 
-    let tree_id = if let Some(pathspecs) = matches.get_many::<PathBuf>("pathspecs") {
-        stupid.with_temp_index(|stupid_temp| {
-            stupid_temp.read_tree(patch_commit_ref.tree())?;
-            stupid_temp.apply_pathlimited_treediff_to_index(
-                patch_commit_ref.tree(),
-                parent_commit_ref.tree(),
-                true,
-                pathspecs,
-            )?;
-            stupid_temp.write_tree()
-        })?
-    } else {
-        parent_commit_ref.tree()
-    };
+    if matches.get_flag("dry-run") {
+        let mut output = stupid.diff_tree_files_status(
+            parent_commit_ref.tree(),
+            patch_commit_ref.tree(),
+            matches.get_many::<PathBuf>("pathspecs"),
+            false,
+            false,
+            crate::color::use_color(matches),
+        )?;
+        return super::files::write_files_status(&mut output);
+    }
 
     let author = patch_commit.author_strict()?;
     let default_committer = repo.get_committer()?;
-    let committer = if matches.get_flag("committer-date-is-author-date") {
+    let committer_date_is_author_date = matches.get_flag("committer-date-is-author-date");
+    let committer = if committer_date_is_author_date {
         let mut committer = default_committer.to_owned()?;
         committer.time = author.time;
         committer
@@ -108,32 +252,293 @@ fn run(matches: &ArgMatches) -> Result<()> {
         default_committer.to_owned()?
     };
 
-    let commit_id = repo.commit_ex(
-        &author,
-        &committer,
-        &patch_commit.message_ex(),
-        tree_id,
-        patch_commit_ref.parents(),
-    )?;
+    let mut reflog_msg = format!("spill {patchname}");
+    if let Some(pathspecs) = matches.get_many::<PathBuf>("pathspecs") {
+        reflog_msg.push_str(" --");
+        for pathspec in pathspecs {
+            reflog_msg.push(' ');
+            reflog_msg.push_str(&pathspec.to_string_lossy());
+        }
+    }
+    if let Some(annotation) = matches.get_one::<String>("annotate") {
+        reflog_msg.push_str("\n\n");
+        reflog_msg.push_str(annotation);
+    }
+
+    if let Some(new_patchname) = to_patchname {
+        // The new patch gets the spilled changes applied to the original parent
+        // while the spilled patch keeps its tree, but is reparented onto the new
+        // patch.
+        let new_tree_id = if let Some(pathspecs) = matches.get_many::<PathBuf>("pathspecs") {
+            let (new_tree_id, unmatched) = stupid.with_temp_index(|stupid_temp| {
+                stupid_temp.read_tree(parent_commit_ref.tree())?;
+                let (_, unmatched) = stupid_temp.apply_pathlimited_treediff_to_index(
+                    parent_commit_ref.tree(),
+                    patch_commit_ref.tree(),
+                    true,
+                    pathspecs,
+                )?;
+                Ok((stupid_temp.write_tree()?, unmatched))
+            })?;
+            warn_unmatched_pathspecs(matches, &unmatched);
+            new_tree_id
+        } else {
+            patch_commit_ref.tree()
+        };
+
+        let new_commit_id = repo.commit_ex(
+            &author,
+            &committer,
+            &Message::from(new_patchname.to_string()),
+            new_tree_id,
+            [parent.id],
+        )?;
+
+        drop(patch_commit_ref);
 
-    drop(patch_commit_ref);
+        stack
+            .setup_transaction()
+            .use_index_and_worktree(false)
+            .committer_date_is_author_date(committer_date_is_author_date)
+            .with_output_stream(get_color_stdout(matches))
+            .transact(|trans| {
+                // Patches above the spilled patch keep their trees as well since the
+                // spilled patch's tree is unchanged.
+                let popped_extra = trans.pop_patches(|pn| pn == &patchname)?;
+                trans.new_applied(&new_patchname, new_commit_id)?;
+                let to_push: Vec<&PatchName> =
+                    std::iter::once(&patchname).chain(&popped_extra).collect();
+                trans.push_tree_patches(&to_push)
+            })
+            .execute(&reflog_msg)?;
 
-    let reflog_msg = if let Some(annotation) = matches.get_one::<String>("annotate") {
-        format!("spill {patchname}\n\n{annotation}")
+        return Ok(());
+    }
+
+    let tree_id = if matches.get_flag("interactive") {
+        stupid.with_temp_index(|stupid_temp| {
+            stupid_temp.read_tree(patch_commit_ref.tree())?;
+            stupid_temp.reset_patch(
+                parent_commit_ref.tree(),
+                matches.get_many::<PathBuf>("pathspecs"),
+            )?;
+            stupid_temp.write_tree()
+        })?
     } else {
-        format!("spill {patchname}")
+        let (tree_id, unmatched) = compute_spilled_tree(
+            &repo,
+            patch_commit_ref.tree(),
+            parent_commit_ref.tree(),
+            matches.get_many::<PathBuf>("pathspecs"),
+        )?;
+        warn_unmatched_pathspecs(matches, &unmatched);
+        tree_id
     };
 
-    stack
+    let above: Vec<PatchName> = stack
+        .applied()
+        .iter()
+        .skip_while(|pn| *pn != &patchname)
+        .skip(1)
+        .cloned()
+        .collect();
+
+    let (new_patchname, commit_id) = if matches.get_flag("edit") {
+        drop(patch_commit_ref);
+        let mut edit_args = vec!["dummy-spill", "--edit"];
+        if committer_date_is_author_date {
+            edit_args.push("--committer-date-is-author-date");
+        }
+        let edit_matches = patchedit::add_args(clap::Command::new("dummy-spill"), false, false)
+            .try_get_matches_from(edit_args)
+            .expect("dummy command has valid arguments");
+        match patchedit::EditBuilder::default()
+            .original_patchname(Some(&patchname))
+            .existing_patch_commit(patch_commit)
+            .override_tree_id(tree_id)
+            .allow_diff_edit(false)
+            .allow_template_save(false)
+            .edit(&stack, &repo, &edit_matches)?
+        {
+            patchedit::EditOutcome::Edited {
+                new_patchname,
+                new_commit_id,
+            } => (new_patchname, new_commit_id),
+            patchedit::EditOutcome::TemplateSaved(_) => panic!("not allowed for spill"),
+        }
+    } else {
+        let commit_id = repo.commit_ex(
+            &author,
+            &committer,
+            &patch_commit.message_ex(),
+            tree_id,
+            patch_commit_ref.parents(),
+        )?;
+        drop(patch_commit_ref);
+        (None, Some(commit_id))
+    };
+
+    let head_tree_id = stack.get_branch_head().tree_id()?.detach();
+
+    let stack = stack
         .setup_transaction()
         .use_index_and_worktree(false)
+        .committer_date_is_author_date(committer_date_is_author_date)
         .with_output_stream(get_color_stdout(matches))
-        .transact(|trans| trans.update_patch(&patchname, commit_id))
+        .transact(|trans| {
+            // Patches above the spilled patch are popped and then pushed back onto
+            // the spilled patch. The spilled changes thus remain only in the index
+            // and worktree.
+            let popped_extra = trans.pop_patches(|pn| above.contains(pn))?;
+            assert!(
+                popped_extra.is_empty(),
+                "only patches above should be popped"
+            );
+            if let Some(commit_id) = commit_id {
+                trans.update_patch(&patchname, commit_id)?;
+            }
+            if let Some(new_patchname) = new_patchname.as_ref() {
+                trans.rename_patch(&patchname, new_patchname)?;
+            }
+            trans.push_patches(&above, false)
+        })
         .execute(&reflog_msg)?;
 
     if matches.get_flag("reset") {
-        stupid.read_tree(tree_id)?;
+        // The index is known to match the old head's tree, so removing the spilled
+        // changes from it is equivalent to resetting it to the new head's tree.
+        let new_head_tree_id = stack.get_branch_head().tree_id()?.detach();
+        if !stupid.apply_treediff_to_default_index(head_tree_id, new_head_tree_id, false)? {
+            stupid.read_tree(new_head_tree_id)?;
+        }
     }
 
     Ok(())
 }
+
+/// Restore `patchname` to its commit from before it was most recently spilled.
+fn undo_spill(stack: Stack, patchname: &PatchName, matches: &ArgMatches) -> Result<()> {
+    let repo = stack.repo;
+    let pre_spill_commit = find_pre_spill_commit(&stack, patchname)?;
+
+    let mut reflog_msg = format!("spill --undo {patchname}");
+    if let Some(annotation) = matches.get_one::<String>("annotate") {
+        reflog_msg.push_str("\n\n");
+        reflog_msg.push_str(annotation);
+    }
+
+    let above: Vec<PatchName> = stack
+        .applied()
+        .iter()
+        .skip_while(|pn| *pn != patchname)
+        .skip(1)
+        .cloned()
+        .collect();
+
+    let stupid = repo.stupid();
+    let head_tree_id = stack.get_branch_head().tree_id()?.detach();
+    let index_tree_id = stupid.write_tree()?;
+
+    let stack = stack
+        .setup_transaction()
+        .use_index_and_worktree(false)
+        .with_output_stream(get_color_stdout(matches))
+        .transact(|trans| {
+            let popped_extra = trans.pop_patches(|pn| above.contains(pn))?;
+            assert!(
+                popped_extra.is_empty(),
+                "only patches above should be popped"
+            );
+            trans.update_patch(patchname, pre_spill_commit.id)?;
+            trans.push_patches(&above, false)
+        })
+        .execute(&reflog_msg)?;
+
+    // Only the re-absorbed paths are reset in the index to match the restored patch,
+    // leaving any other staged changes in place.
+    let new_head_tree_id = stack.get_branch_head().tree_id()?.detach();
+    let absorbed_paths: Vec<OsString> = stupid
+        .diff_tree_names(head_tree_id, new_head_tree_id)?
+        .iter()
+        .map(|path| {
+            let mut pathspec = OsString::from(":(literal)");
+            pathspec.push(path);
+            pathspec
+        })
+        .collect();
+    if !absorbed_paths.is_empty() {
+        let (applied, _) = stupid.apply_pathlimited_treediff_to_index(
+            index_tree_id,
+            new_head_tree_id,
+            false,
+            absorbed_paths,
+        )?;
+        if !applied {
+            return Err(anyhow!("failed to update the index for the restored patch"));
+        }
+    }
+    Ok(())
+}
+
+/// Find the commit of `patchname` from before it was most recently spilled.
+///
+/// The stack's state log is searched for the most recent spill of the patch. The patch
+/// must be unmodified since that spill and still have the same parent, otherwise
+/// restoring its prior commit would discard changes or rebase the patch.
+fn find_pre_spill_commit<'repo>(
+    stack: &Stack<'repo>,
+    patchname: &PatchName,
+) -> Result<Rc<gix::Commit<'repo>>> {
+    let current_commit = stack.get_patch_commit(patchname);
+    let spill_msg = format!("spill {patchname}");
+    let mut state_commit = Rc::new(
+        stack
+            .repo
+            .find_reference(stack.get_stack_refname())?
+            .peel_to_commit()?,
+    );
+    loop {
+        let state = StackState::from_commit(stack.repo, &state_commit)?;
+        if !state.has_patch(patchname) {
+            return Err(anyhow!("no spill of `{patchname}` found in the stack log"));
+        } else if state.get_patch_commit(patchname).id != current_commit.id {
+            return Err(anyhow!(
+                "`{patchname}` was modified since it was last spilled"
+            ));
+        }
+        let message = state_commit.message_raw()?;
+        let is_spill = message.lines().next().is_some_and(|subject| {
+            subject
+                .strip_prefix(spill_msg.as_bytes())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(b" "))
+        });
+        let prev_state_commit = state
+            .prev
+            .ok_or_else(|| anyhow!("no spill of `{patchname}` found in the stack log"))?;
+        if is_spill {
+            let prev_state = StackState::from_commit(stack.repo, &prev_state_commit)?;
+            if !prev_state.has_patch(patchname) {
+                return Err(anyhow!("no spill of `{patchname}` found in the stack log"));
+            }
+            let pre_spill_commit = prev_state.get_patch_commit(patchname).clone();
+            if pre_spill_commit.parent_ids().next() != current_commit.parent_ids().next() {
+                return Err(anyhow!(
+                    "cannot undo spill of `{patchname}` because its parent changed, \
+                     e.g. due to `--to-patch`"
+                ));
+            }
+            return Ok(pre_spill_commit);
+        }
+        state_commit = prev_state_commit;
+    }
+}
+
+/// Warn about each pathspec that did not match any of the patch's changes.
+fn warn_unmatched_pathspecs(matches: &ArgMatches, unmatched: &[OsString]) {
+    for pathspec in unmatched {
+        crate::print_warning_message(
+            matches,
+            &format!("no changes matched path '{}'", pathspec.to_string_lossy()),
+        );
+    }
+}
@@ -2,18 +2,27 @@
 
 //! `stg squash` implementation.
 
-use std::fmt::Write;
+use std::{fmt::Write, path::PathBuf};
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
+use bstr::ByteSlice;
 use clap::{Arg, ArgMatches};
 
 use crate::{
-    color::get_color_stdout,
+    argset,
+    color::{get_color_stderr, get_color_stdout},
     ext::{CommitExtended, RepositoryExtended, SignatureExtended},
-    patch::{patchedit, patchrange, PatchName, PatchRange, RangeConstraint},
-    print_info_message,
-    stack::{InitializationPolicy, Stack, StackStateAccess, StackTransaction},
-    stupid::Stupid,
+    patch::{patchedit, patchrange, PatchName, PatchRange, RangeConstraint, SingleRevisionSpec},
+    print_info_message, print_warning_message,
+    stack::{
+        InitializationPolicy, PushMergeOptions, Stack, StackAccess, StackStateAccess,
+        StackTransaction,
+    },
+    stupid::{
+        ConflictStyle, GpgSign, Stupid, TrailerIfExists, TrailerIfMissing, TrailerOptions,
+        TrailerPlacement,
+    },
+    wrap::Message,
 };
 
 pub(super) const STGIT_COMMAND: super::StGitCommand = super::StGitCommand {
@@ -21,6 +30,7 @@ pub(super) const STGIT_COMMAND: super::StGitCommand = super::StGitCommand {
     category: super::CommandCategory::StackManipulation,
     make,
     run,
+    envvars: crate::patch::patchedit::EDITOR_ENVVARS,
 };
 
 fn make() -> clap::Command {
@@ -56,7 +66,49 @@ fn make() -> clap::Command {
                 .num_args(1..)
                 .allow_hyphen_values(true)
                 .value_parser(clap::value_parser!(PatchRange))
-                .required(true),
+                .required_unless_present("stdin"),
+        )
+        .arg(
+            Arg::new("stdin")
+                .long("stdin")
+                .help("Read patches to squash from stdin")
+                .long_help(
+                    "Read the patches to squash from stdin instead of from the command \
+                     line. Each line names a patch or patch range; blank lines are \
+                     ignored.",
+                )
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with("patchranges"),
+        )
+        .arg(
+            Arg::new("prefix-match")
+                .long("prefix-match")
+                .help("Allow patches to be named by unambiguous prefixes")
+                .long_help(
+                    "Allow patches to be named by a prefix of their name, e.g. \
+                     'stg squash fea bug' for patches 'feature-x' and 'bugfix-y'. \
+                     Names that exactly match a patch, or otherwise locate a patch, \
+                     take precedence over prefix matches. It is an error for a \
+                     prefix to match more than one patch.",
+                )
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("reverse")
+                .long("reverse")
+                .help("Squash the patches in reverse order")
+                .long_help(
+                    "Squash the given patches in the reverse of the order they were \
+                     given. The patches are pushed in reverse order and the default \
+                     message of the squashed patch combines the patches' messages in \
+                     reverse order. The squashed patch is based on the parent of the \
+                     last given patch, which is pushed first.\n\
+                     \n\
+                     When the patches apply cleanly in either order, the resulting \
+                     tree is the same as without this option. Otherwise, pushing the \
+                     patches in reverse order may result in conflicts.",
+                )
+                .action(clap::ArgAction::SetTrue),
         )
         .arg(
             Arg::new("name")
@@ -66,11 +118,349 @@ fn make() -> clap::Command {
                 .value_name("name")
                 .allow_hyphen_values(true)
                 .value_parser(clap::value_parser!(PatchName)),
+        )
+        .arg(
+            Arg::new("porcelain")
+                .long("porcelain")
+                .help("Print machine-readable summary of the squash")
+                .long_help(
+                    "Print a machine-readable summary line to stdout upon success. The \
+                     summary has the form \"squashed <patch>,<patch>,... -> <name> \
+                     <short-id>\", where <name> is the name of the new, squashed patch. \
+                     The usual stack output is suppressed.\n\
+                     \n\
+                     Conflicts are not allowed when this option is used; the squash \
+                     fails if pushing any patch would result in conflicts.",
+                )
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with("save-template"),
+        )
+        .arg(
+            Arg::new("allow-single")
+                .long("allow-single")
+                .help("Allow squashing a single patch")
+                .long_help(
+                    "Allow a single patch to be given. The patch is then recommitted \
+                     as if squashed, which allows its message to be regenerated, \
+                     e.g. with '--template', or its author to be changed with \
+                     '--author'. Unless '--name' or '--keep' is given, the patch keeps \
+                     its name.",
+                )
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("onto")
+                .long("onto")
+                .help("Place the squashed patch on top of <target>")
+                .long_help(
+                    "Place the squashed patch directly on top of <target>, which must \
+                     be an applied patch other than the squashed patches or the stack's \
+                     base. The patches above <target> are popped before the squashed \
+                     patch is pushed onto it, and are pushed back afterwards.\n\
+                     \n\
+                     The combined changes of the squashed patches are thus rebased onto \
+                     <target>. Any conflicts are left to be resolved as with \
+                     'stg push'.",
+                )
+                .value_name("target")
+                .value_parser(clap::value_parser!(SingleRevisionSpec)),
+        )
+        .arg(
+            Arg::new("autostash")
+                .long("autostash")
+                .help("Stash changes before the squash and reapply them after")
+                .long_help(
+                    "Automatically create a temporary stash before the squash begins, \
+                     and apply it after the squash completes. This allows a squash to \
+                     be performed on a dirty work tree. Note however that the final \
+                     stash application may result in non-trivial conflicts.\n\
+                     \n\
+                     If the squash fails, the stash is left in place to be applied \
+                     manually.",
+                )
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("quiet")
+                .long("quiet")
+                .short('q')
+                .help("Suppress informational output")
+                .long_help(
+                    "Suppress informational messages and only report conflicts from \
+                     the stack changes made by the squash.",
+                )
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("strategy")
+                .long("strategy")
+                .help("Use merge strategy option <option> for conflicting pushes")
+                .long_help(
+                    "When the squashed patches cannot simply be combined, they are \
+                     pushed one by one, which may require merging. Pass <option> as a \
+                     strategy option to git-merge-recursive(1) for any merge needed to \
+                     push a patch during the squash. For example, 'ours' or 'theirs' \
+                     resolve conflicting hunks in favor of one side.",
+                )
+                .value_name("option")
+                .num_args(1),
+        )
+        .arg(
+            Arg::new("ours")
+                .long("ours")
+                .help("Resolve conflicting pushes in favor of the stack")
+                .long_help(
+                    "Resolve conflicting hunks in favor of the stack being pushed onto \
+                     for any merge needed to push a patch during the squash. This is \
+                     shorthand for '--strategy=ours'.",
+                )
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with_all(["strategy", "theirs"]),
+        )
+        .arg(
+            Arg::new("theirs")
+                .long("theirs")
+                .help("Resolve conflicting pushes in favor of the pushed patch")
+                .long_help(
+                    "Resolve conflicting hunks in favor of the patch being pushed for \
+                     any merge needed to push a patch during the squash. This is \
+                     shorthand for '--strategy=theirs'.",
+                )
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with("strategy"),
+        )
+        .arg(argset::conflict_style_arg())
+        .arg(
+            Arg::new("expose")
+                .long("expose")
+                .help("Write the squashed tree to <ref> instead of squashing")
+                .long_help(
+                    "Instead of squashing the patches, create a commit with the \
+                     combined tree of the patches and write it to <ref>, e.g. \
+                     `refs/heads/squash-preview`. The stack is not modified. The \
+                     commit's parent is the parent of the bottom-most patch, making \
+                     it suitable for inspecting the would-be squashed patch with, \
+                     e.g., `git show`.\n\
+                     \n\
+                     The patches' changes must combine without conflicts; otherwise \
+                     an error is returned and <ref> is not written. An existing <ref> \
+                     is not overwritten unless '--force' is given. The current \
+                     branch and the stack's own refs may never be written.",
+                )
+                .value_name("ref")
+                .conflicts_with_all(["save-template", "porcelain"]),
+        )
+        .arg(
+            Arg::new("force")
+                .long("force")
+                .help("Overwrite an existing <ref> with '--expose'")
+                .action(clap::ArgAction::SetTrue)
+                .requires("expose"),
+        )
+        .arg(
+            Arg::new("preview")
+                .long("preview")
+                .help("Show the combined diff of the patches instead of squashing")
+                .long_help(
+                    "Instead of squashing the patches, show the diff between the parent \
+                     of the bottom-most patch and the combined tree of the patches, \
+                     i.e. the diff of the would-be squashed patch. The stack is not \
+                     modified. The diff may be limited with '--preview-path'.\n\
+                     \n\
+                     The patches' changes must combine without conflicts; otherwise \
+                     an error is returned.",
+                )
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with_all(["save-template", "porcelain", "expose"]),
+        )
+        .arg(
+            Arg::new("dry-run")
+                .long("dry-run")
+                .help("Report what squashing would do without changing anything")
+                .long_help(
+                    "Instead of squashing the patches, report how the squash would be \
+                     performed. The stack, index, and worktree are not modified.\n\
+                     \n\
+                     The report shows the squashed patches and the squashed patch's \
+                     name, if known; whether the patches' changes combine cleanly \
+                     ('method: combine') or whether the patches must first be pushed \
+                     onto each other ('method: push'), which may result in conflicts; \
+                     the squashed patch's author and any co-authors; and the patches \
+                     that would be popped and pushed.",
+                )
+                .action(clap::ArgAction::SetTrue)
+                .conflicts_with_all(["save-template", "porcelain", "expose", "preview"]),
+        )
+        .arg(
+            Arg::new("preview-path")
+                .long("preview-path")
+                .help("Limit the preview diff to files matching <path>")
+                .long_help(
+                    "Limit the diff shown by '--preview' to files matching <path>. \
+                     This option may be repeated.",
+                )
+                .value_name("path")
+                .action(clap::ArgAction::Append)
+                .value_hint(clap::ValueHint::AnyPath)
+                .value_parser(clap::value_parser!(PathBuf))
+                .requires("preview"),
+        )
+        .arg(
+            Arg::new("template")
+                .long("template")
+                .help("Use <file> to seed the squashed patch's message")
+                .long_help(
+                    "Use the contents of <file> as the default message of the squashed \
+                     patch instead of the concatenated messages of the squashed \
+                     patches. The squashed patches' messages are appended as comments \
+                     below the template's contents for reference.\n\
+                     \n\
+                     A message provided with '--message' or '--file' takes precedence \
+                     over the template.\n\
+                     \n\
+                     Without this option, '--message', or '--file', the contents of the \
+                     file named by the \"commit.template\" configuration variable, if \
+                     set, are prepended to the squashed patches' messages.",
+                )
+                .value_name("file")
+                .value_hint(clap::ValueHint::FilePath)
+                .value_parser(clap::value_parser!(PathBuf)),
         );
-    patchedit::add_args(command, true, true)
+    let command = add_squash_args(command);
+    patchedit::add_args(command, true, true).mut_arg("diff", |arg| {
+        arg.visible_alias("verbose")
+            .visible_short_alias('v')
+            .long_help(
+                "Show the combined diff of the squashed patches below the message \
+                 when editing the squashed patch's description, as with \
+                 'git commit --verbose'. The diff is for reference only and is not \
+                 included in the squashed patch's message.",
+            )
+    })
+}
+
+/// Add the options affecting how the squashed patch's commit is created.
+///
+/// These options are shared with `stg rebase --interactive`, which also squashes
+/// patches.
+pub(super) fn add_squash_args(command: clap::Command) -> clap::Command {
+    command
+        .arg(
+            Arg::new("keep")
+                .long("keep")
+                .help("Keep the original patches as hidden patches")
+                .long_help(
+                    "Keep the original patches as hidden patches instead of deleting \
+                     them. The original patches may later be restored with \
+                     'stg unhide'.",
+                )
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("keep-empty")
+                .long("keep-empty")
+                .help("Allow the squashed patch to be empty")
+                .long_help(
+                    "Allow the squashed patch to be empty. By default, squashing \
+                     patches whose changes cancel each other out is refused.",
+                )
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("date")
+                .long("date")
+                .help("Take the author date from the squashed patches")
+                .long_help(
+                    "Take the author date of the squashed patch from the squashed \
+                     patches. With 'first', the author date of the first of the \
+                     squashed patches is used. With 'earliest', the earliest author \
+                     date of any of the squashed patches is used. By default, the \
+                     author date is that of the first patch when all of the squashed \
+                     patches have the same author, otherwise the current time.\n\
+                     \n\
+                     An author date given with '--authdate' takes precedence.",
+                )
+                .value_name("which")
+                .value_parser(["first", "earliest"]),
+        )
+        .arg(
+            Arg::new("no-coauthors")
+                .long("no-coauthors")
+                .help("Do not add Co-authored-by trailers")
+                .long_help(
+                    "Do not add Co-authored-by trailers to the message of the squashed \
+                     patch. The trailers are added to the default message as well as \
+                     to a message given with '--message' or '--file'.\n\
+                     \n\
+                     The author of the squashed patch is, in order of precedence: \
+                     the author given with '--author'; the common author when all of \
+                     the squashed patches have the same author; or the current user \
+                     when the squashed patches have differing authors. By default, a \
+                     Co-authored-by trailer is added for each author of the squashed \
+                     patches other than the resulting author. Thus, when '--author' is \
+                     given, each displaced original author is credited with a trailer. \
+                     A trailer key other than \"Co-authored-by\" may be configured \
+                     with the \"stgit.squash.coauthortrailer\" configuration option.",
+                )
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("gpg-sign")
+                .long("gpg-sign")
+                .help("GPG-sign the squashed patch")
+                .long_help(
+                    "GPG-sign the squashed patch's commit. By default, the squashed \
+                     patch is signed according to the `commit.gpgSign` configuration.\n\
+                     \n\
+                     The optional <keyid> selects the signing key. Without <keyid>, \
+                     the key is determined by the `user.signingKey` configuration or \
+                     the committer identity.\n\
+                     \n\
+                     N.B. the GPG signatures of the squashed patches are never \
+                     preserved; a warning is printed when any of the squashed patches \
+                     were signed.",
+                )
+                .value_name("keyid")
+                .num_args(0..=1)
+                .require_equals(true)
+                .default_missing_value("")
+                .overrides_with("no-gpg-sign"),
+        )
+        .arg(
+            Arg::new("no-gpg-sign")
+                .long("no-gpg-sign")
+                .help("Do not GPG-sign the squashed patch")
+                .long_help(
+                    "Do not GPG-sign the squashed patch's commit, even if \
+                     `commit.gpgSign` is enabled.",
+                )
+                .action(clap::ArgAction::SetTrue)
+                .overrides_with("gpg-sign"),
+        )
 }
 
 fn run(matches: &ArgMatches) -> Result<()> {
+    // Stdin can only supply one of the patches or the message. This is checked before
+    // anything is read from stdin.
+    if matches.get_flag("stdin")
+        && matches
+            .get_one::<PathBuf>("file")
+            .is_some_and(|path| path.as_os_str() == "-")
+    {
+        return Err(make()
+            .error(
+                clap::error::ErrorKind::ArgumentConflict,
+                format!(
+                    "the argument '{}--stdin{}' cannot be used with '{}--file -{}'",
+                    anstyle::AnsiColor::Yellow.on_default().render(),
+                    anstyle::Reset.render(),
+                    anstyle::AnsiColor::Yellow.on_default().render(),
+                    anstyle::Reset.render(),
+                ),
+            )
+            .into());
+    }
+
     let repo = gix::Repository::open()?;
     let stack = Stack::current(&repo, InitializationPolicy::AllowUninitialized)?;
     let stupid = repo.stupid();
@@ -80,28 +470,95 @@ fn run(matches: &ArgMatches) -> Result<()> {
     statuses.check_conflicts()?;
     stack.check_head_top_mismatch()?;
 
-    let squash_patchnames: Vec<PatchName> = patchrange::resolve_names(
-        &stack,
+    let patchranges: Vec<PatchRange> = if matches.get_flag("stdin") {
+        read_stdin_patchranges()?
+    } else {
         matches
             .get_many::<PatchRange>("patchranges")
-            .expect("clap ensures two or more patches"),
-        RangeConstraint::All,
-    )?;
+            .expect("clap ensures patches unless --stdin")
+            .cloned()
+            .collect()
+    };
+    let patchranges = if matches.get_flag("prefix-match") {
+        patchrange::expand_name_prefixes(&stack, &patchranges)?
+    } else {
+        patchranges
+    };
+    let mut squash_patchnames: Vec<PatchName> =
+        patchrange::resolve_names(&stack, &patchranges, RangeConstraint::All)?;
+
+    if matches.get_flag("reverse") {
+        squash_patchnames.reverse();
+    }
 
-    let patchname: Option<PatchName> = matches.get_one::<PatchName>("name").cloned();
+    // A single patch squashed with `--allow-single` keeps its name by default, unless
+    // the original patch is kept.
+    let patchname: Option<PatchName> =
+        matches.get_one::<PatchName>("name").cloned().or_else(|| {
+            match squash_patchnames.as_slice() {
+                [single_patchname] if !matches.get_flag("keep") => Some(single_patchname.clone()),
+                _ => None,
+            }
+        });
 
     if let Some(patchname) = patchname.as_ref() {
-        if !squash_patchnames.contains(patchname) {
+        // Invalid names are rejected when parsing the argument, but a leading '-' is
+        // only disallowed here since the squashed patch could otherwise not be
+        // distinguished from a relative patch offset.
+        let patchname_str: &str = patchname.as_ref();
+        if patchname_str.starts_with('-') {
+            return Err(anyhow!("patch name `{patchname}` may not start with '-'"));
+        }
+        // With `--keep`, the original patches retain their names.
+        if matches.get_flag("keep") || !squash_patchnames.contains(patchname) {
             if let Some(colliding_patchname) = stack.collides(patchname) {
                 return Err(anyhow!("patch name `{colliding_patchname}` already taken"));
             }
         }
     }
 
-    if squash_patchnames.len() < 2 {
+    if squash_patchnames.is_empty()
+        || (squash_patchnames.len() < 2 && !matches.get_flag("allow-single"))
+    {
         return Err(anyhow!("need at least two patches"));
     }
 
+    let onto = if let Some(onto_spec) = matches.get_one::<SingleRevisionSpec>("onto") {
+        let (target_patchname, target_id) = resolve_onto(&stack, onto_spec)?;
+        Some(if let Some(target_patchname) = target_patchname {
+            if squash_patchnames.contains(&target_patchname) {
+                return Err(anyhow!(
+                    "cannot squash onto `{target_patchname}`, which is being squashed"
+                ));
+            } else if !stack.is_applied(&target_patchname) {
+                return Err(anyhow!(
+                    "cannot squash onto unapplied patch `{target_patchname}`"
+                ));
+            }
+            SquashOnto::Patch(target_patchname)
+        } else if target_id == stack.base().id {
+            SquashOnto::Base
+        } else {
+            return Err(anyhow!(
+                "`--onto` target must be an applied patch or the stack's base"
+            ));
+        })
+    } else {
+        None
+    };
+
+    let template = if let Some(template_path) = matches.get_one::<PathBuf>("template") {
+        Some(MessageTemplate::Explicit(
+            std::fs::read_to_string(template_path).with_context(|| {
+                format!("reading template `{}`", template_path.to_string_lossy())
+            })?,
+        ))
+    } else if !matches.contains_id("message") && !matches.contains_id("file") {
+        read_commit_template(&repo)?.map(MessageTemplate::CommitTemplate)
+    } else {
+        None
+    };
+
     if matches.contains_id("save-template") {
         let first_patch_commit = stack.get_patch_commit(&squash_patchnames[0]);
         if let patchedit::EditOutcome::TemplateSaved(template_path) =
@@ -111,7 +568,11 @@ fn run(matches: &ArgMatches) -> Result<()> {
                 .allow_template_save(true)
                 .template_patchname(patchname.as_ref())
                 .default_author(repo.get_author()?.override_author(matches)?)
-                .default_message(prepare_message(&stack, &squash_patchnames)?)
+                .default_message(prepare_message(
+                    &stack,
+                    &squash_patchnames,
+                    template.as_ref(),
+                )?)
                 .edit(&stack, &repo, matches)?
         {
             let template_path = template_path.to_string_lossy();
@@ -123,75 +584,591 @@ fn run(matches: &ArgMatches) -> Result<()> {
         } else {
             panic!("expected template to be saved")
         }
+    } else if let Some(refname) = matches.get_one::<String>("expose") {
+        expose(
+            &stack,
+            &squash_patchnames,
+            refname,
+            matches.get_flag("force"),
+        )
+    } else if matches.get_flag("preview") {
+        preview(
+            &stack,
+            &squash_patchnames,
+            matches.get_many::<PathBuf>("preview-path"),
+            crate::color::use_color(matches),
+        )
+    } else if matches.get_flag("dry-run") {
+        dry_run(
+            &stack,
+            matches,
+            &squash_patchnames,
+            patchname.as_ref(),
+            onto.as_ref(),
+        )
     } else {
+        if !is_contiguous_in_applied_order(stack.applied(), &squash_patchnames) {
+            let patchnames = squash_patchnames
+                .iter()
+                .map(|pn| format!("`{pn}`"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            print_warning_message(
+                matches,
+                &format!(
+                    "squashed patches {patchnames} are not contiguous in applied order; \
+                     the patches between them are pushed on top of the squashed patch"
+                ),
+            );
+        }
+
+        let signed_patchnames: Vec<&PatchName> = squash_patchnames
+            .iter()
+            .filter(|pn| {
+                stack
+                    .get_patch_commit(pn)
+                    .decode()
+                    .is_ok_and(|commit_ref| commit_ref.extra_headers().pgp_signature().is_some())
+            })
+            .collect();
+        if !signed_patchnames.is_empty() {
+            let signed_patchnames = signed_patchnames
+                .iter()
+                .map(|pn| format!("`{pn}`"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            print_warning_message(
+                matches,
+                &format!(
+                    "GPG signatures of squashed patches are not preserved: {signed_patchnames}"
+                ),
+            );
+        }
+
         let should_push_squashed = stack
             .applied()
             .iter()
             .any(|pn| squash_patchnames.contains(pn));
 
-        stack
+        let porcelain = matches.get_flag("porcelain");
+        let mut squashed_patchname = None;
+
+        let builder = stack
             .setup_transaction()
-            .allow_conflicts(true)
+            .allow_conflicts(!porcelain)
+            .quiet(porcelain || matches.get_flag("quiet"))
             .use_index_and_worktree(true)
-            .committer_date_is_author_date(matches.get_flag("committer-date-is-author-date"))
-            .with_output_stream(get_color_stdout(matches))
+            .committer_date_is_author_date(matches.get_flag("committer-date-is-author-date"));
+        let builder = if porcelain {
+            builder
+                .allow_push_conflicts(false)
+                .with_output_stream(get_color_stderr(matches))
+        } else {
+            builder.with_output_stream(get_color_stdout(matches))
+        };
+
+        let using_stash = if matches.get_flag("autostash")
+            && statuses.check_index_and_worktree_clean().is_err()
+        {
+            stupid.stash_push()?;
+            true
+        } else {
+            false
+        };
+
+        let stack = builder
             .transact(|trans| {
-                squash(
+                squashed_patchname = Some(squash(
                     trans,
                     matches,
                     &squash_patchnames,
                     patchname.as_ref(),
+                    template.as_ref(),
                     should_push_squashed,
-                )?;
+                    onto.as_ref(),
+                )?);
                 Ok(())
             })
-            .execute("squash")?;
-        Ok(())
+            .execute("squash")
+            .map_err(|e| {
+                if using_stash {
+                    print_warning_message(
+                        matches,
+                        "changes stashed by `--autostash` were not reapplied; \
+                         use `git stash pop` to apply them",
+                    );
+                }
+                e
+            })?;
+
+        if porcelain {
+            let squashed_patchname =
+                squashed_patchname.expect("successful squash has new patch name");
+            let old_patchnames = squash_patchnames
+                .iter()
+                .map(PatchName::to_string)
+                .collect::<Vec<_>>()
+                .join(",");
+            let short_id = stack
+                .get_patch_commit(&squashed_patchname)
+                .id()
+                .shorten_or_id();
+            println!("squashed {old_patchnames} -> {squashed_patchname} {short_id}");
+        }
+
+        if using_stash && !stupid.stash_pop()? {
+            Err(super::Error::CausedConflicts("stash pop resulted in conflicts".to_string()).into())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Write a commit with the combined tree of the patches to `refname`.
+///
+/// An existing `refname` is only overwritten when `force` is set. The current branch,
+/// `HEAD`, and the stack's own references are never written.
+fn expose(stack: &Stack, patchnames: &[PatchName], refname: &str, force: bool) -> Result<()> {
+    let full_refname = gix::refs::FullName::try_from(refname)
+        .map_err(|_| anyhow!("invalid ref name `{refname}`"))?;
+    let full_refname_str = full_refname.as_bstr().to_str_lossy().into_owned();
+    if full_refname_str == "HEAD"
+        || full_refname.as_ref() == stack.get_branch_refname()
+        || full_refname_str == stack.get_stack_refname()
+        || full_refname_str.starts_with(&stack.patch_revspec(""))
+    {
+        return Err(anyhow!(
+            "cannot expose squashed tree to `{full_refname_str}`: \
+             the ref belongs to the current branch or its stack"
+        ));
+    }
+    let repo = stack.repo;
+    let patchnames = in_stack_order(stack, patchnames);
+    let tree_id = combine_trees(stack, repo, &patchnames)?.ok_or_else(|| {
+        anyhow!("cannot expose squashed tree: patches do not combine without conflicts")
+    })?;
+    let parent_id = stack
+        .get_patch_commit(&patchnames[0])
+        .parent_ids()
+        .next()
+        .expect("first patch has a parent")
+        .detach();
+    let patchnames_str = patchnames
+        .iter()
+        .map(PatchName::to_string)
+        .collect::<Vec<_>>()
+        .join(", ");
+    let message = format!("squash preview of {patchnames_str}\n");
+    let commit_id = repo.commit_ex(
+        &repo.get_author()?.to_owned()?,
+        &repo.get_committer()?.to_owned()?,
+        &Message::from(message),
+        tree_id,
+        [parent_id],
+    )?;
+    let previous_value = if force {
+        gix::refs::transaction::PreviousValue::Any
+    } else {
+        gix::refs::transaction::PreviousValue::MustNotExist
+    };
+    repo.reference(
+        full_refname,
+        commit_id,
+        previous_value,
+        format!("squash --expose {patchnames_str}"),
+    )
+    .map_err(|e| match e {
+        gix::reference::edit::Error::FileTransactionPrepare(
+            gix::refs::file::transaction::prepare::Error::MustNotExist { .. },
+        ) => anyhow!(
+            "cannot expose squashed tree to `{full_refname_str}`: the ref already exists; \
+             use `--force` to overwrite it"
+        ),
+        e => e.into(),
+    })?;
+    Ok(())
+}
+
+/// Get the patches in the order they appear in the stack.
+///
+/// The combined tree of patches given out of stack order, e.g. with `--reverse`, is
+/// computed from the patches in stack order, using the parent of the bottom-most
+/// patch as the base.
+fn in_stack_order<'repo>(
+    stack_state: &impl StackStateAccess<'repo>,
+    patchnames: &[PatchName],
+) -> Vec<PatchName> {
+    let mut patchnames = patchnames.to_vec();
+    patchnames.sort_by_key(|pn| stack_state.index_of(pn));
+    patchnames
+}
+
+/// Determine whether the patches are consecutive applied patches, in any order.
+///
+/// Squashing such patches leaves the rest of the stack as-is. Otherwise, the patches
+/// in between are reordered to come after the squashed patch. Patches that are not
+/// all applied are not considered to be reordered.
+fn is_contiguous_in_applied_order(applied: &[PatchName], patchnames: &[PatchName]) -> bool {
+    let mut positions = Vec::with_capacity(patchnames.len());
+    for pn in patchnames {
+        let Some(pos) = applied.iter().position(|applied_pn| applied_pn == pn) else {
+            return true;
+        };
+        positions.push(pos);
+    }
+    positions.sort_unstable();
+    positions.windows(2).all(|pair| pair[0] + 1 == pair[1])
+}
+
+/// Determine whether the patches are given in the order they appear in the stack.
+fn is_in_stack_order<'repo>(
+    stack_state: &impl StackStateAccess<'repo>,
+    patchnames: &[PatchName],
+) -> bool {
+    patchnames
+        .windows(2)
+        .all(|pair| stack_state.index_of(&pair[0]) < stack_state.index_of(&pair[1]))
+}
+
+/// Show the diff of the would-be squashed patch without modifying the stack.
+fn preview<'a>(
+    stack: &Stack,
+    patchnames: &[PatchName],
+    pathspecs: Option<impl IntoIterator<Item = &'a PathBuf>>,
+    use_color: bool,
+) -> Result<()> {
+    let repo = stack.repo;
+    let patchnames = in_stack_order(stack, patchnames);
+    let tree_id = combine_trees(stack, repo, &patchnames)?.ok_or_else(|| {
+        anyhow!("cannot preview squashed diff: patches do not combine without conflicts")
+    })?;
+    let parent_tree_id = stack
+        .get_patch_commit(&patchnames[0])
+        .get_parent_commit()?
+        .tree_id()?
+        .detach();
+    repo.stupid().diff(
+        &format!("{parent_tree_id}..{tree_id}"),
+        pathspecs,
+        false,
+        use_color,
+        std::iter::empty::<&str>(),
+    )
+}
+
+/// Report how the patches would be squashed without modifying the stack.
+///
+/// This mirrors the decisions made by [`squash()`]: whether the patches' changes
+/// combine cleanly, the resulting author and co-authors, and which patches are popped
+/// and pushed to put the squashed patch in place.
+fn dry_run(
+    stack: &Stack,
+    matches: &ArgMatches,
+    patchnames: &[PatchName],
+    patchname: Option<&PatchName>,
+    onto: Option<&SquashOnto>,
+) -> Result<()> {
+    let repo = stack.repo;
+    let squashed = patchname.map_or_else(|| "<squashed>".to_string(), PatchName::to_string);
+    let is_combinable =
+        is_in_stack_order(stack, patchnames) && combine_trees(stack, repo, patchnames)?.is_some();
+    let (author, coauthors) = squash_authors(stack, repo, matches, patchnames)?;
+
+    // The squashed patches are removed from the stack, popping any applied patches
+    // above the first of them, which are subsequently pushed back.
+    let applied = stack.applied();
+    let first_pos = applied
+        .iter()
+        .position(|pn| patchnames.contains(pn))
+        .unwrap_or(applied.len());
+    let mut pop_pos = first_pos;
+    let mut to_push: Vec<&PatchName> = applied[first_pos..]
+        .iter()
+        .filter(|pn| !patchnames.contains(pn))
+        .collect();
+    let mut squashed_push_pos = (first_pos < applied.len()).then_some(0);
+
+    if let Some(onto) = onto {
+        let onto_patchname = match onto {
+            SquashOnto::Patch(target_patchname) => Some(target_patchname),
+            SquashOnto::Base => None,
+        };
+        if let Some(pos) = onto_patchname
+            .and_then(|target_patchname| to_push.iter().position(|&pn| pn == target_patchname))
+        {
+            squashed_push_pos = Some(pos + 1);
+        } else {
+            pop_pos = onto_patchname.map_or(0, |target_patchname| {
+                applied
+                    .iter()
+                    .position(|pn| pn == target_patchname)
+                    .expect("onto target patch is applied")
+                    + 1
+            });
+            to_push.splice(0..0, &applied[pop_pos..first_pos]);
+            squashed_push_pos = Some(0);
+        }
+    }
+
+    let popped: Vec<String> = applied[pop_pos..]
+        .iter()
+        .filter(|pn| !patchnames.contains(pn))
+        .map(PatchName::to_string)
+        .collect();
+    let mut pushed: Vec<String> = to_push.into_iter().map(PatchName::to_string).collect();
+    if let Some(pos) = squashed_push_pos {
+        pushed.insert(pos, squashed.clone());
+    }
+
+    let patchnames_str = patchnames
+        .iter()
+        .map(PatchName::to_string)
+        .collect::<Vec<_>>()
+        .join(" ");
+    println!("squash: {patchnames_str} -> {squashed}");
+    println!("method: {}", if is_combinable { "combine" } else { "push" });
+    println!("author: {} <{}>", author.name, author.email);
+    if !matches.get_flag("no-coauthors") {
+        for (coauthor, count) in coauthors {
+            println!("co-author: {coauthor} ({count})");
+        }
+    }
+    println!(
+        "{}: {patchnames_str}",
+        if matches.get_flag("keep") {
+            "hide"
+        } else {
+            "delete"
+        }
+    );
+    if !popped.is_empty() {
+        println!("pop: {}", popped.join(" "));
+    }
+    if !pushed.is_empty() {
+        println!("push: {}", pushed.join(" "));
+    }
+    Ok(())
+}
+
+/// Template used to seed the squashed patch's default message.
+pub(super) enum MessageTemplate {
+    /// Template from `--template`, which supersedes the squashed patches' messages.
+    Explicit(String),
+
+    /// Template from the `commit.template` config, prepended to the squashed
+    /// patches' messages.
+    CommitTemplate(String),
+}
+
+impl MessageTemplate {
+    fn contents(&self) -> &str {
+        match self {
+            MessageTemplate::Explicit(contents) | MessageTemplate::CommitTemplate(contents) => {
+                contents
+            }
+        }
     }
 }
 
+/// Read the file named by the `commit.template` config variable, if set.
+///
+/// As with `git commit`, a relative path is relative to the top of the work tree.
+fn read_commit_template(repo: &gix::Repository) -> Result<Option<String>> {
+    let config = repo.config_snapshot();
+    let Some(template_path) = config.trusted_path("commit.template").transpose()? else {
+        return Ok(None);
+    };
+    let template_path = if let Some(workdir) = repo.workdir() {
+        workdir.join(template_path)
+    } else {
+        template_path.into_owned()
+    };
+    std::fs::read_to_string(&template_path)
+        .with_context(|| {
+            format!(
+                "reading `commit.template` file `{}`",
+                template_path.to_string_lossy()
+            )
+        })
+        .map(Some)
+}
+
+/// Prepare the default message for the squashed patch.
+///
+/// Without a template, the default message is the concatenation of the squashed
+/// patches' messages. With an explicit template, the template's contents are followed
+/// by the squashed patches' messages as comments. A `commit.template` template's
+/// contents are followed by the squashed patches' messages as-is.
 fn prepare_message<'repo>(
     stack_state: &impl StackStateAccess<'repo>,
     patchnames: &[PatchName],
+    template: Option<&MessageTemplate>,
 ) -> Result<String> {
     let mut squash_message = String::new();
+    let comment_messages = matches!(template, Some(MessageTemplate::Explicit(_)));
+    if let Some(template) = template {
+        let template = trim_trailing_blank_lines(template.contents());
+        if !template.is_empty() {
+            write!(squash_message, "{template}\n\n")?;
+        }
+    }
     for (i, patchname) in patchnames.iter().enumerate() {
         let commit = stack_state.get_patch_commit(patchname);
         let message = commit.message_ex();
         let message = message.decode()?;
-        let message = message.trim_end();
+        let message = trim_trailing_blank_lines(&message);
         let patch_number = i + 1;
-        write!(
+        writeln!(
             squash_message,
-            "# Commit message from patch #{patch_number}: {patchname}\n\
-             {message}\n\
-             \n"
+            "# Commit message from patch #{patch_number}: {patchname}"
         )?;
+        if comment_messages {
+            for line in message.lines() {
+                if line.is_empty() {
+                    writeln!(squash_message, "#")?;
+                } else {
+                    writeln!(squash_message, "# {line}")?;
+                }
+            }
+        } else {
+            writeln!(squash_message, "{message}")?;
+        }
+        writeln!(squash_message)?;
     }
     Ok(squash_message)
 }
 
+/// Remove trailing blank lines from `message`.
+///
+/// Unlike [`str::trim_end()`], the last non-blank line is kept intact, as is any
+/// indentation, so that messages ending with, e.g., an indented code block survive.
+fn trim_trailing_blank_lines(message: &str) -> &str {
+    if let Some(last_char_pos) = message.rfind(|c: char| !c.is_whitespace()) {
+        let end = message[last_char_pos..]
+            .find('\n')
+            .map_or(message.len(), |pos| last_char_pos + pos);
+        message[..end].trim_end_matches('\r')
+    } else {
+        ""
+    }
+}
+
+/// Append trailer lines to a squash message, normalizing its trailing whitespace.
+///
+/// When `extend_trailer_block` is true, the message is known to end with a trailer
+/// block and the lines are appended directly to that block. Otherwise, exactly one
+/// blank line separates the lines from the message body. In either case, the
+/// resulting message ends with exactly one newline.
+fn append_trailer_lines(
+    message: &str,
+    trailer_lines: &[String],
+    extend_trailer_block: bool,
+) -> String {
+    let mut appended = trim_trailing_blank_lines(message).to_string();
+    if !appended.is_empty() {
+        appended.push('\n');
+    }
+    if !trailer_lines.is_empty() {
+        if !appended.is_empty() && !extend_trailer_block {
+            appended.push('\n');
+        }
+        for line in trailer_lines {
+            appended.push_str(line);
+            appended.push('\n');
+        }
+    }
+    appended
+}
+
+/// Resolve the `--onto` target to its commit id and, if it names a patch, the patch name.
+///
+/// Patch specifications, including `{base}`, are resolved against the stack while other
+/// specifications are resolved to a commit with `git rev-parse`.
+fn resolve_onto(
+    stack: &Stack,
+    spec: &SingleRevisionSpec,
+) -> Result<(Option<PatchName>, gix::ObjectId)> {
+    match spec {
+        SingleRevisionSpec::GitLike(git_like) => {
+            Ok((None, stack.repo.stupid().rev_parse(git_like)?))
+        }
+        SingleRevisionSpec::PatchAndGitLike(patch_like, git_like) => {
+            match patch_like.resolve(stack.repo, stack) {
+                Ok(target) => Ok((target.patchname, target.commit.id)),
+                Err(e) => match stack.repo.stupid().rev_parse(git_like) {
+                    Ok(target_id) => Ok((None, target_id)),
+                    // A spec naming an object that is not a commit is reported as such
+                    // rather than as a missing patch.
+                    Err(rev_err) if stack.repo.rev_parse_single(git_like.as_str()).is_ok() => {
+                        Err(rev_err)
+                    }
+                    Err(_) => Err(e),
+                },
+            }
+        }
+        _ => {
+            let target = spec.resolve(stack.repo, Some(stack))?;
+            Ok((target.patchname, target.commit.id))
+        }
+    }
+}
+
+/// Target for placing the squashed patch with `--onto`.
+pub(super) enum SquashOnto {
+    /// Directly on top of an applied patch.
+    Patch(PatchName),
+
+    /// At the bottom of the stack, directly on the stack's base.
+    Base,
+}
+
 pub(super) fn squash(
     trans: &mut StackTransaction,
     matches: &ArgMatches,
     patchnames: &[PatchName],
     patchname: Option<&PatchName>,
+    template: Option<&MessageTemplate>,
     should_push_squashed: bool,
+    onto: Option<&SquashOnto>,
 ) -> Result<PatchName> {
+    // The constituent patches are either deleted or, with `--keep`, hidden.
+    let remove_constituents = |trans: &mut StackTransaction| -> Result<Vec<PatchName>> {
+        if matches.get_flag("keep") {
+            let to_push = trans.pop_patches(|pn| patchnames.contains(pn))?;
+            trans.hide_patches(patchnames)?;
+            Ok(to_push)
+        } else {
+            trans.delete_patches(|pn| patchnames.contains(pn))
+        }
+    };
+
     let (new_patchname, commit_id, to_push) = if let Some((new_patchname, commit_id)) =
-        try_squash(trans, matches, patchnames, patchname)?
+        try_squash(trans, matches, patchnames, patchname, template)?
     {
         // Squashed commit could be created with simple merges, so the
-        // constituent patches can just be deleted.
-        let to_push = trans.delete_patches(|pn| patchnames.contains(pn))?;
+        // constituent patches can just be removed.
+        let to_push = remove_constituents(trans)?;
         (new_patchname, commit_id, to_push)
     } else {
         // Simple approach failed, need to do pops and pushes...
         let to_push = trans.pop_patches(|pn| patchnames.contains(pn))?;
-        trans.push_patches(patchnames, false)?;
-        if let Some((new_patchname, commit_id)) = try_squash(trans, matches, patchnames, patchname)?
+        // The merge strategy and conflict style only apply to pushing the patches
+        // being squashed.
+        let merge_options = PushMergeOptions {
+            strategy_options: matches
+                .get_one::<String>("strategy")
+                .cloned()
+                .or_else(|| matches.get_flag("ours").then(|| "ours".to_string()))
+                .or_else(|| matches.get_flag("theirs").then(|| "theirs".to_string()))
+                .into_iter()
+                .collect(),
+            conflict_style: matches.get_one::<ConflictStyle>("conflict-style").copied(),
+        };
+        trans.push_patches_with_merge_options(patchnames, &merge_options)?;
+        if let Some((new_patchname, commit_id)) =
+            try_squash(trans, matches, patchnames, patchname, template)?
         {
-            let popped_extra = trans.delete_patches(|pn| patchnames.contains(pn))?;
+            let popped_extra = remove_constituents(trans)?;
             assert!(popped_extra.is_empty());
             (new_patchname, commit_id, to_push)
         } else {
@@ -205,7 +1182,34 @@ pub(super) fn squash(
 
     let mut to_push = to_push;
 
-    if should_push_squashed {
+    if let Some(onto) = onto {
+        let onto_patchname = match onto {
+            SquashOnto::Patch(target_patchname) => Some(target_patchname),
+            SquashOnto::Base => None,
+        };
+        if let Some(pos) = onto_patchname
+            .and_then(|target_patchname| to_push.iter().position(|pn| pn == target_patchname))
+        {
+            // The target was incidentally popped while squashing.
+            to_push.insert(pos + 1, new_patchname.clone());
+        } else {
+            // Pop the patches above the target such that the squashed patch is pushed
+            // directly onto it.
+            let above_pos = onto_patchname.map_or(0, |target_patchname| {
+                trans
+                    .applied()
+                    .iter()
+                    .position(|pn| pn == target_patchname)
+                    .expect("onto target patch is applied")
+                    + 1
+            });
+            let above = trans.applied()[above_pos..].to_vec();
+            let popped_extra = trans.pop_patches(|pn| above.contains(pn))?;
+            assert!(popped_extra.is_empty());
+            to_push.splice(0..0, above);
+            to_push.insert(0, new_patchname.clone());
+        }
+    } else if should_push_squashed {
         to_push.insert(0, new_patchname.clone());
     }
 
@@ -214,41 +1218,231 @@ pub(super) fn squash(
     Ok(new_patchname)
 }
 
+/// Combine the changes of the given patches into a single tree.
+///
+/// The changes of each subsequent patch are applied, in order, to the first patch's
+/// tree. Returns `None` if any patch's changes do not apply cleanly.
+fn combine_trees<'repo>(
+    stack_state: &impl StackStateAccess<'repo>,
+    repo: &gix::Repository,
+    patchnames: &[PatchName],
+) -> Result<Option<gix::ObjectId>> {
+    let base_tree_id = stack_state
+        .get_patch_commit(&patchnames[0])
+        .tree_id()?
+        .detach();
+    repo.stupid().with_temp_index(|stupid_temp| {
+        stupid_temp.read_tree(base_tree_id)?;
+        for commit in patchnames[1..]
+            .iter()
+            .map(|pn| stack_state.get_patch_commit(pn))
+        {
+            let tree_id = commit.tree_id()?.detach();
+            let parent_tree_id = commit.get_parent_commit()?.tree_id()?.detach();
+            if parent_tree_id != tree_id
+                && !stupid_temp.apply_treediff_to_index(parent_tree_id, tree_id, true)?
+            {
+                return Ok(None);
+            }
+        }
+        stupid_temp.write_tree().map(Some)
+    })
+}
+
+/// Read newline-separated patch names or ranges from stdin.
+fn read_stdin_patchranges() -> Result<Vec<PatchRange>> {
+    let input = std::io::read_to_string(std::io::stdin()).context("reading patches from stdin")?;
+    input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| line.parse::<PatchRange>().map_err(anyhow::Error::from))
+        .collect()
+}
+
+/// Determine whether any of the patches to be squashed carries changes.
+///
+/// When the squashed tree is identical to its parent's tree, this distinguishes patches
+/// whose changes cancel each other out from patches that were empty to begin with.
+fn any_patch_has_changes<'repo>(
+    stack_state: &impl StackStateAccess<'repo>,
+    patchnames: &[PatchName],
+) -> Result<bool> {
+    for pn in patchnames {
+        let commit = stack_state.get_patch_commit(pn);
+        if commit.tree_id()? != commit.get_parent_commit()?.tree_id()? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Get the trailer key used to credit the other authors of the squashed patches.
+///
+/// The key may be configured with `stgit.squash.coauthortrailer` and must be a valid
+/// trailer token, i.e. consist of only alphanumerics and '-'.
+fn get_coauthor_trailer_key(repo: &gix::Repository) -> Result<String> {
+    let config = repo.config_snapshot();
+    let Some(key) = config.string_by("stgit", Some("squash".into()), "coauthortrailer") else {
+        return Ok("Co-authored-by".to_string());
+    };
+    let key = key
+        .to_str()
+        .ok()
+        .map(str::trim)
+        .filter(|key| !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'))
+        .ok_or_else(|| {
+            anyhow!(
+                "invalid trailer key `{}` for `stgit.squash.coauthortrailer`",
+                key.to_str_lossy()
+            )
+        })?;
+    Ok(key.to_string())
+}
+
+/// Determine the squashed patch's author and the original authors it displaces.
+///
+/// The displaced authors are each distinct author of the squashed patches, in order of
+/// first appearance, that differs from the resulting author, whether due to differing
+/// authors or an `--author` override. Each is returned as `Name <email>` along with the
+/// number of squashed patches they authored.
+fn squash_authors<'repo>(
+    stack_state: &impl StackStateAccess<'repo>,
+    repo: &gix::Repository,
+    matches: &ArgMatches,
+    patchnames: &[PatchName],
+) -> Result<(gix::actor::Signature, Vec<(String, usize)>)> {
+    let base_author = stack_state.get_patch_commit(&patchnames[0]).author()?;
+    let mut use_base_author = true;
+    let mut earliest_time = base_author.time()?;
+    // Distinct authors, in order of first appearance, with their patch counts.
+    let mut author_counts = vec![(base_author, 1usize)];
+    for commit in patchnames[1..]
+        .iter()
+        .map(|pn| stack_state.get_patch_commit(pn))
+    {
+        let author = commit.author()?;
+        if author != base_author {
+            use_base_author = false;
+        }
+        let time = author.time()?;
+        if time.seconds < earliest_time.seconds {
+            earliest_time = time;
+        }
+        if let Some((_, count)) = author_counts
+            .iter_mut()
+            .find(|(a, _)| a.name == author.name && a.email == author.email)
+        {
+            *count += 1;
+        } else {
+            author_counts.push((author, 1));
+        }
+    }
+
+    let mut author = if use_base_author {
+        base_author
+    } else {
+        repo.get_author()?
+    }
+    .to_owned()?;
+    match matches.get_one::<String>("date").map(String::as_str) {
+        Some("first") => author.time = base_author.time()?,
+        Some("earliest") => author.time = earliest_time,
+        _ => {}
+    }
+    let author = author.override_author(matches)?;
+
+    let coauthors = author_counts
+        .iter()
+        .filter(|(a, _)| a.name != author.name || a.email != author.email)
+        .map(|(coauthor, count)| (format!("{} <{}>", coauthor.name, coauthor.email), *count))
+        .collect();
+
+    Ok((author, coauthors))
+}
+
 fn try_squash(
     trans: &StackTransaction,
     matches: &ArgMatches,
     patchnames: &[PatchName],
     patchname: Option<&PatchName>,
+    template: Option<&MessageTemplate>,
 ) -> Result<Option<(PatchName, gix::ObjectId)>> {
+    // The patches' changes are combined onto the first patch's tree, which already
+    // contains the changes of any patches below it. Patches given out of stack order,
+    // e.g. with `--reverse`, must thus first be pushed in the given order.
+    if !is_in_stack_order(trans, patchnames) {
+        return Ok(None);
+    }
     let repo = trans.repo();
     let base_commit = trans.get_patch_commit(&patchnames[0]);
-    let base_author = base_commit.author()?;
-    let mut use_base_author = true;
     let base_commit_ref = base_commit.decode()?;
-    if let Some(tree_id) = repo.stupid().with_temp_index(|stupid_temp| {
-        stupid_temp.read_tree(base_commit_ref.tree())?;
-        for commit in patchnames[1..].iter().map(|pn| trans.get_patch_commit(pn)) {
-            let commit_ref = commit.decode()?;
-            let author = commit.author()?;
-            if author != base_author {
-                use_base_author = false;
+    if let Some(tree_id) = combine_trees(trans, repo, patchnames)? {
+        if !matches.get_flag("keep-empty")
+            && tree_id == base_commit.get_parent_commit()?.tree_id()?
+            && any_patch_has_changes(trans, patchnames)?
+        {
+            return Err(anyhow!(
+                "changes in the squashed patches cancel out, leaving an empty patch; \
+                 use `--keep-empty` to override"
+            ));
+        }
+
+        let (author, coauthors) = squash_authors(trans, repo, matches, patchnames)?;
+
+        let mut message = prepare_message(trans, patchnames, template)?;
+        let mut coauthor_trailers = Vec::new();
+        if !matches.get_flag("no-coauthors") {
+            let trailer_key = get_coauthor_trailer_key(repo)?;
+            let mut coauthor_summaries = Vec::new();
+            for (coauthor, count) in coauthors {
+                coauthor_summaries.push(format!("{coauthor} ({count})"));
+                coauthor_trailers.push((trailer_key.clone(), coauthor));
             }
-            let parent = commit.get_parent_commit()?;
-            let parent_commit_ref = parent.decode()?;
-            if parent_commit_ref.tree() != commit_ref.tree()
-                && !stupid_temp.apply_treediff_to_index(
-                    parent_commit_ref.tree(),
-                    commit_ref.tree(),
-                    true,
-                )?
-            {
-                return Ok(None);
+            if !coauthor_summaries.is_empty() {
+                print_info_message(
+                    matches,
+                    &format!(
+                        "squashed patch authored by {} <{}>; co-authors: {}",
+                        author.name,
+                        author.email,
+                        coauthor_summaries.join(", ")
+                    ),
+                );
             }
         }
+        // A message supplied with `--message` or `--file` replaces the default message,
+        // so the co-authors are instead added to it as trailers. Co-authors already
+        // credited in the default message's trailers, e.g. from squashing previously
+        // squashed patches, are not credited again.
+        if !matches.contains_id("message") && !matches.contains_id("file") {
+            let existing_trailers = if coauthor_trailers.is_empty() {
+                Vec::new()
+            } else {
+                repo.stupid().parse_trailers(message.as_bytes())?
+            };
+            let trailer_lines: Vec<String> = coauthor_trailers
+                .drain(..)
+                .filter(|(trailer_key, value)| {
+                    !existing_trailers.iter().any(|(key, existing)| {
+                        key.eq_ignore_ascii_case(trailer_key) && existing == value
+                    })
+                })
+                .map(|(trailer_key, value)| format!("{trailer_key}: {value}"))
+                .collect();
+            message = append_trailer_lines(&message, &trailer_lines, !existing_trailers.is_empty());
+        }
+
+        // Trailers from the squashed patches' messages may already include, e.g.,
+        // the committer's sign-off, which should not be duplicated. Requested trailers
+        // are otherwise always added after the co-author trailers, regardless of any
+        // `trailer.where` or `trailer.ifMissing` configuration.
+        let mut trailer_options = TrailerOptions::default();
+        trailer_options
+            .placement(TrailerPlacement::End)
+            .if_exists(TrailerIfExists::AddIfDifferent)
+            .if_missing(TrailerIfMissing::Add);
 
-        let tree_id = stupid_temp.write_tree()?;
-        Ok(Some(tree_id))
-    })? {
         if let patchedit::EditOutcome::Edited {
             new_patchname,
             new_commit_id,
@@ -264,16 +1458,26 @@ fn try_squash(
             .allow_diff_edit(false)
             .allow_template_save(false)
             .template_patchname(patchname)
-            .extra_allowed_patchnames(patchnames)
-            .default_author(
-                if use_base_author {
-                    base_author
+            .extra_allowed_patchnames(if matches.get_flag("keep") {
+                &[]
+            } else {
+                patchnames
+            })
+            .default_author(author)
+            .default_message(message)
+            .extra_trailers(coauthor_trailers)
+            .trailer_options(trailer_options)
+            .gpgsign(if let Some(keyid) = matches.get_one::<String>("gpg-sign") {
+                Some(if keyid.is_empty() {
+                    GpgSign::DefaultKey
                 } else {
-                    repo.get_author()?
-                }
-                .override_author(matches)?,
-            )
-            .default_message(prepare_message(trans, patchnames)?)
+                    GpgSign::Key(keyid.clone())
+                })
+            } else if matches.get_flag("no-gpg-sign") {
+                Some(GpgSign::No)
+            } else {
+                None
+            })
             .edit(trans, repo, matches)?
         {
             Ok(Some((
@@ -287,3 +1491,44 @@ fn try_squash(
         Ok(None)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::append_trailer_lines;
+
+    #[test]
+    fn append_trailers_after_body() {
+        let trailers = ["Co-authored-by: A <a@example.com>".to_string()];
+        for message in ["body\n", "body\n\n", "body\n\n\n\n", "body\n \n\t\n"] {
+            assert_eq!(
+                append_trailer_lines(message, &trailers, false),
+                "body\n\nCo-authored-by: A <a@example.com>\n",
+                "{message:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn append_trailers_to_trailer_block() {
+        let trailers = ["Co-authored-by: A <a@example.com>".to_string()];
+        assert_eq!(
+            append_trailer_lines(
+                "body\n\nSigned-off-by: B <b@example.com>\n\n\n",
+                &trailers,
+                true
+            ),
+            "body\n\nSigned-off-by: B <b@example.com>\nCo-authored-by: A <a@example.com>\n",
+        );
+    }
+
+    #[test]
+    fn normalize_without_trailers() {
+        assert_eq!(append_trailer_lines("body\n\n\n", &[], false), "body\n");
+        assert_eq!(append_trailer_lines("body", &[], true), "body\n");
+        assert_eq!(append_trailer_lines("\n\n", &[], false), "");
+        assert_eq!(
+            append_trailer_lines("", &["Key: value".to_string()], false),
+            "Key: value\n"
+        );
+    }
+}
@@ -27,6 +27,7 @@ pub(super) const STGIT_COMMAND: super::StGitCommand = super::StGitCommand {
     category: super::CommandCategory::PatchManipulation,
     make,
     run,
+    envvars: &[],
 };
 
 fn make() -> clap::Command {
@@ -269,6 +270,8 @@ fn branch_merge_patch(
         ref_parent_ref.tree(),
         commit_ref.tree(),
         ref_commit_ref.tree(),
+        std::iter::empty::<&str>(),
+        None,
     )? {
         return Err(super::Error::CausedConflicts(format!(
             "merge conflicts syncing `{patchname}`"
@@ -327,7 +330,13 @@ fn series_merge_patch(
     let tree_id = stupid.write_tree()?;
 
     stupid.read_tree_checkout(tree_id, trans_head_tree_id)?;
-    if !stupid.merge_recursive(parent_commit_ref.tree(), trans_head_tree_id, tree_id)? {
+    if !stupid.merge_recursive(
+        parent_commit_ref.tree(),
+        trans_head_tree_id,
+        tree_id,
+        std::iter::empty::<&str>(),
+        None,
+    )? {
         return Err(super::Error::CausedConflicts(format!(
             "merge conflicts syncing `{patchname}`"
         ))
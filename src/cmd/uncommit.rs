@@ -21,6 +21,7 @@ pub(super) const STGIT_COMMAND: super::StGitCommand = super::StGitCommand {
     category: super::CommandCategory::StackManipulation,
     make,
     run,
+    envvars: &[],
 };
 
 fn make() -> clap::Command {
@@ -44,7 +45,8 @@ fn make() -> clap::Command {
              \n\
              The -t/--to option specifies that all commits up to and including the \
              given commit should be uncommitted. The -x/--exclusive option may be \
-             used to exclude the \"to\" commit.\n\
+             used to exclude the \"to\" commit. It is an error if the given commit is not \
+             part of the current branch's history.\n\
              \n\
              Only commits with exactly one parent can be uncommitted; in other words, \
              merge commits may not be uncommitted.",
@@ -101,13 +103,7 @@ fn run(matches: &ArgMatches) -> Result<()> {
     let patchname_len_limit = PatchName::get_length_limit(&config);
 
     let (commits, patchnames) = if let Some(committish) = matches.get_one::<String>("to") {
-        let mut target_commit = repo
-            .rev_parse_single(committish.as_str())
-            .map_err(|_| anyhow!("invalid committish `{committish}`"))?
-            .object()?
-            .peel_tags_to_end()?
-            .try_into_commit()
-            .map_err(|_| anyhow!("target `{committish}` does not resolve to a commit"))?;
+        let mut target_commit = repo.find_commit(repo.stupid().rev_parse(committish)?)?;
 
         let bases = repo
             .stupid()
@@ -115,9 +111,19 @@ fn run(matches: &ArgMatches) -> Result<()> {
 
         let exclusive = if bases.contains(&target_commit.id) {
             matches.get_flag("exclusive")
-        } else {
+        } else if repo
+            .stupid()
+            .merge_bases(target_commit.id, stack.get_branch_head().id)?
+            .contains(&target_commit.id)
+        {
+            // The target is one of the stack's applied patches, so there is nothing
+            // beyond the stack base to uncommit.
             target_commit = repo.find_commit(bases[0])?;
             true
+        } else {
+            return Err(anyhow!(
+                "target `{committish}` is not an ancestor of the stack base"
+            ));
         };
 
         if exclusive {
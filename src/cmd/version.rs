@@ -12,6 +12,7 @@ pub(crate) const STGIT_COMMAND: super::StGitCommand = super::StGitCommand {
     category: super::CommandCategory::Administration,
     make,
     run,
+    envvars: &[],
 };
 
 fn make() -> clap::Command {
@@ -6,7 +6,9 @@ use std::ffi::OsString;
 
 use clap::{Arg, ArgMatches};
 use is_terminal::IsTerminal;
-use termcolor::StandardStream;
+use termcolor::{StandardStream, WriteColor};
+
+use crate::ext::RepositoryExtended;
 
 pub(crate) fn get_color_arg() -> Arg {
     Arg::new("color")
@@ -55,21 +57,42 @@ pub(crate) fn termcolor_choice_to_clap(color_choice: termcolor::ColorChoice) ->
 }
 
 /// Get [`termcolor::StandardStream`] for stdout based on `--color` option.
+///
+/// When stdout is not a terminal, `auto` is downgraded to never unless the output is
+/// determined to be going to a color-capable pager; see [`pager_wants_color()`].
 pub(crate) fn get_color_stdout(matches: &ArgMatches) -> StandardStream {
-    let mut choice = get_color_choice(Some(matches));
-    if choice == termcolor::ColorChoice::Auto && !std::io::stdout().is_terminal() {
-        choice = termcolor::ColorChoice::Never;
-    }
-    StandardStream::stdout(choice)
+    StandardStream::stdout(stream_color_choice(
+        matches,
+        std::io::stdout().is_terminal() || pager_wants_color(),
+    ))
+}
+
+/// Get boxed [`termcolor::WriteColor`] for stdout based on `--color` option.
+///
+/// Code that writes colored output through `dyn WriteColor` may be exercised in tests
+/// with a [`termcolor::Buffer`] in place of the stream returned here.
+pub(crate) fn get_color_stdout_writer(matches: &ArgMatches) -> Box<dyn WriteColor> {
+    Box::new(get_color_stdout(matches))
 }
 
 /// Get [`termcolor::StandardStream`] for stderr based on `--color` option.
 pub(crate) fn get_color_stderr(matches: &ArgMatches) -> StandardStream {
-    let mut choice = get_color_choice(Some(matches));
-    if choice == termcolor::ColorChoice::Auto && !std::io::stderr().is_terminal() {
-        choice = termcolor::ColorChoice::Never;
+    StandardStream::stderr(stream_color_choice(
+        matches,
+        std::io::stderr().is_terminal(),
+    ))
+}
+
+/// Resolve `--color` option for an output stream.
+///
+/// The `auto` choice is downgraded to never when the stream is not a terminal.
+fn stream_color_choice(matches: &ArgMatches, is_terminal: bool) -> termcolor::ColorChoice {
+    let choice = get_color_choice(Some(matches));
+    if choice == termcolor::ColorChoice::Auto && !is_terminal {
+        termcolor::ColorChoice::Never
+    } else {
+        choice
     }
-    StandardStream::stderr(choice)
 }
 
 /// Get [`termcolor::ColorChoice`] from argument matches.
@@ -86,11 +109,75 @@ pub(crate) fn get_color_choice(maybe_matches: Option<&ArgMatches>) -> termcolor:
 pub(crate) fn use_color(matches: &ArgMatches) -> bool {
     match crate::color::get_color_choice(Some(matches)) {
         termcolor::ColorChoice::Always | termcolor::ColorChoice::AlwaysAnsi => true,
-        termcolor::ColorChoice::Auto => std::io::stdout().is_terminal(),
+        termcolor::ColorChoice::Auto => std::io::stdout().is_terminal() || pager_wants_color(),
         termcolor::ColorChoice::Never => false,
     }
 }
 
+/// Determine whether `auto` color should be used when stdout is piped to a pager.
+///
+/// Color is only considered when StGit is known to be running under a git-spawned
+/// pager, i.e. when `GIT_PAGER_IN_USE` is set in the environment. Output redirected to
+/// a file or other pipe thus remains uncolored. The `stgit.pager.color` config
+/// variable takes precedence over git's `color.pager`, which defaults to true.
+fn pager_wants_color() -> bool {
+    pager_in_use() && {
+        let config = pager_color_config();
+        config.stgit.or(config.git).unwrap_or(true)
+    }
+}
+
+/// Get the value of the `stgit.pager.color` config variable, if set.
+///
+/// When StGit spawns a pager for git's output, this value is passed on to git as
+/// `color.pager`.
+pub(crate) fn stgit_pager_color() -> Option<bool> {
+    pager_color_config().stgit
+}
+
+/// Pager color settings from `stgit.pager.color` and `color.pager`.
+struct PagerColorConfig {
+    stgit: Option<bool>,
+    git: Option<bool>,
+}
+
+/// Read the pager color config.
+///
+/// The config is read once and the result cached for the remainder of the process.
+fn pager_color_config() -> &'static PagerColorConfig {
+    static PAGER_COLOR: std::sync::OnceLock<PagerColorConfig> = std::sync::OnceLock::new();
+    PAGER_COLOR.get_or_init(|| {
+        let maybe_repo = gix::Repository::open().ok();
+        let maybe_config = maybe_repo.as_ref().map(|repo| repo.config_snapshot());
+        let global_config_file;
+        let config_file = if let Some(snapshot) = maybe_config.as_ref() {
+            Some(snapshot.plumbing())
+        } else {
+            global_config_file = gix::config::File::from_globals().ok();
+            global_config_file.as_ref()
+        };
+        let get_bool = |key: &str| {
+            config_file
+                .and_then(|config_file| config_file.boolean(key))
+                .and_then(Result::ok)
+        };
+        PagerColorConfig {
+            stgit: get_bool("stgit.pager.color"),
+            git: get_bool("color.pager"),
+        }
+    })
+}
+
+/// Determine whether git has indicated that output is going to a pager.
+fn pager_in_use() -> bool {
+    std::env::var_os("GIT_PAGER_IN_USE").is_some_and(|value| {
+        !matches!(
+            value.to_str().map(str::to_ascii_lowercase).as_deref(),
+            Some("" | "0" | "false" | "no" | "off")
+        )
+    })
+}
+
 /// Parse `argv` for `--color` option.
 ///
 /// This is done outside of [`clap`] in order to be able to setup the [`clap::Command`]
@@ -125,3 +212,49 @@ pub(crate) fn parse_color_choice(argv: &[OsString]) -> Option<termcolor::ColorCh
     }
     choice
 }
+
+#[cfg(test)]
+mod tests {
+    use termcolor::{Buffer, ColorSpec, WriteColor};
+
+    use super::*;
+
+    /// Make a [`Buffer`] standing in for a stream with the given terminal-ness.
+    fn color_buffer(color: &str, is_terminal: bool) -> Buffer {
+        let matches = clap::Command::new("test")
+            .arg(get_color_arg())
+            .try_get_matches_from(["test", "--color", color])
+            .expect("valid color choice");
+        match stream_color_choice(&matches, is_terminal) {
+            termcolor::ColorChoice::Never => Buffer::no_color(),
+            _ => Buffer::ansi(),
+        }
+    }
+
+    /// Write colored text through `dyn WriteColor`, as production code would.
+    fn write_colored(writer: &mut dyn WriteColor) -> std::io::Result<()> {
+        writer.set_color(ColorSpec::new().set_fg(Some(termcolor::Color::Green)))?;
+        write!(writer, "patch")?;
+        writer.reset()
+    }
+
+    #[test]
+    fn color_choices() {
+        for (color, is_terminal, expect_color) in [
+            ("always", false, true),
+            ("ansi", false, true),
+            ("never", true, false),
+            ("auto", true, true),
+            ("auto", false, false),
+        ] {
+            let mut buffer = color_buffer(color, is_terminal);
+            write_colored(&mut buffer).unwrap();
+            let output = String::from_utf8(buffer.into_inner()).unwrap();
+            if expect_color {
+                assert_eq!(output, "\x1b[0m\x1b[32mpatch\x1b[0m", "--color={color}");
+            } else {
+                assert_eq!(output, "patch", "--color={color}");
+            }
+        }
+    }
+}
@@ -0,0 +1,160 @@
+// SPDX-License-Identifier: GPL-2.0-only
+
+//! Support for config overrides given with `stg -c <name>=<value>`.
+//!
+//! As with `git -c`, overrides are communicated to git subprocesses via the
+//! `GIT_CONFIG_PARAMETERS` environment variable. Overrides are appended to any already
+//! present in the environment such that they take precedence. The same variable is
+//! parsed when opening repositories such that StGit's own view of the config is
+//! consistent with that of the git subprocesses it runs.
+
+use anyhow::{anyhow, Result};
+
+const CONFIG_PARAMETERS_ENVVAR: &str = "GIT_CONFIG_PARAMETERS";
+
+/// Add config overrides, of the form `<name>=<value>` or `<name>`, to the environment.
+///
+/// An override without a value sets the variable to boolean true, as with `git -c`.
+pub(crate) fn push_config_overrides<'a>(
+    overrides: impl IntoIterator<Item = &'a str>,
+) -> Result<()> {
+    let mut params = std::env::var(CONFIG_PARAMETERS_ENVVAR).unwrap_or_default();
+    for config_override in overrides {
+        let name = config_override
+            .split_once('=')
+            .map_or(config_override, |(name, _)| name);
+        if !name.contains('.') || name.starts_with('.') || name.ends_with('.') {
+            return Err(anyhow!(
+                "invalid config override `{config_override}`: \
+                 name must be of the form `<section>.<key>`"
+            ));
+        }
+        if !params.is_empty() {
+            params.push(' ');
+        }
+        push_quoted(&mut params, config_override);
+    }
+    std::env::set_var(CONFIG_PARAMETERS_ENVVAR, params);
+    Ok(())
+}
+
+/// Get config overrides from the environment as `<name>=<value>` or `<name>` strings.
+///
+/// The overrides are in the form expected by [`gix::open::Options::cli_overrides()`].
+pub(crate) fn get_config_overrides() -> Result<Vec<String>> {
+    if let Some(params) = std::env::var_os(CONFIG_PARAMETERS_ENVVAR) {
+        let params = params
+            .into_string()
+            .map_err(|_| anyhow!("`{CONFIG_PARAMETERS_ENVVAR}` is not valid UTF-8"))?;
+        parse_config_parameters(&params)
+            .ok_or_else(|| anyhow!("bogus format in `{CONFIG_PARAMETERS_ENVVAR}`"))
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+/// Append single-quoted `s` to `buf` such that it may be dequoted by git.
+fn push_quoted(buf: &mut String, s: &str) {
+    buf.push('\'');
+    for c in s.chars() {
+        if c == '\'' || c == '!' {
+            buf.push_str("'\\");
+            buf.push(c);
+            buf.push('\'');
+        } else {
+            buf.push(c);
+        }
+    }
+    buf.push('\'');
+}
+
+/// Dequote one single-quoted word from the start of `input`.
+///
+/// Returns the dequoted word and the remainder of the input, or `None` if the input
+/// does not start with a well-formed quoted word.
+fn dequote_word(input: &str) -> Option<(String, &str)> {
+    let mut rest = input.strip_prefix('\'')?;
+    let mut word = String::new();
+    loop {
+        let (quoted, after_quote) = rest.split_once('\'')?;
+        word.push_str(quoted);
+        // A closing quote may be followed by a backslash-escaped quote or exclamation
+        // mark and a reopening quote.
+        if let Some(escaped) = after_quote.strip_prefix('\\') {
+            let c = escaped.chars().next().filter(|&c| c == '\'' || c == '!')?;
+            word.push(c);
+            rest = escaped[1..].strip_prefix('\'')?;
+        } else {
+            return Some((word, after_quote));
+        }
+    }
+}
+
+/// Parse the contents of `GIT_CONFIG_PARAMETERS`.
+///
+/// Both the `'<name>=<value>'` and `'<name>'='<value>'` forms used by different git
+/// versions are supported. Returns `None` if the parameters are malformed.
+fn parse_config_parameters(params: &str) -> Option<Vec<String>> {
+    let mut overrides = Vec::new();
+    let mut rest = params.trim_start();
+    while !rest.is_empty() {
+        let (mut config_override, after_word) = dequote_word(rest)?;
+        rest = after_word;
+        if let Some(after_equals) = rest.strip_prefix('=') {
+            config_override.push('=');
+            if after_equals.starts_with('\'') {
+                let (value, after_value) = dequote_word(after_equals)?;
+                config_override.push_str(&value);
+                rest = after_value;
+            } else {
+                rest = after_equals;
+            }
+        }
+        if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+            return None;
+        }
+        rest = rest.trim_start();
+        overrides.push(config_override);
+    }
+    Some(overrides)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quote_roundtrip() {
+        let overrides = [
+            "user.name=A U Thor",
+            "stgit.namelength=5",
+            "core.bare",
+            "alias.x=!echo 'quoted' it's",
+            "section.key=",
+        ];
+        let mut params = String::new();
+        for config_override in overrides {
+            if !params.is_empty() {
+                params.push(' ');
+            }
+            push_quoted(&mut params, config_override);
+        }
+        assert_eq!(parse_config_parameters(&params).unwrap(), overrides);
+    }
+
+    #[test]
+    fn parse_split_name_and_value() {
+        assert_eq!(
+            parse_config_parameters("'user.name'='A U Thor' 'core.bare'= 'x.y'").unwrap(),
+            ["user.name=A U Thor", "core.bare=", "x.y"]
+        );
+    }
+
+    #[test]
+    fn parse_malformed() {
+        assert!(parse_config_parameters("'user.name=unterminated").is_none());
+        assert!(parse_config_parameters("user.name=unquoted").is_none());
+        assert!(parse_config_parameters("'a.b''c.d'").is_none());
+        assert!(parse_config_parameters("").unwrap().is_empty());
+    }
+}
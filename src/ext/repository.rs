@@ -6,15 +6,28 @@ use anyhow::{anyhow, Result};
 use bstr::BStr;
 
 use crate::{
-    stupid::Stupid,
+    stupid::{GpgSign, Stupid},
     wrap::{Branch, Message, PartialRefName},
 };
 
 /// Extends [`gix::Repository`] with additional methods.
 pub(crate) trait RepositoryExtended {
     /// Open git repository based on current directory and any environment overrides.
+    ///
+    /// Config overrides from `GIT_CONFIG_PARAMETERS`, e.g. from `stg -c`, are applied.
     fn open() -> Result<gix::Repository> {
-        Ok(gix::ThreadSafeRepository::discover_with_environment_overrides(".")?.into())
+        let config_overrides = crate::configparams::get_config_overrides()?;
+        let mut trust_map = gix::sec::trust::Mapping::<gix::open::Options>::default();
+        trust_map.full = trust_map.full.cli_overrides(config_overrides.clone());
+        trust_map.reduced = trust_map.reduced.cli_overrides(config_overrides);
+        Ok(
+            gix::ThreadSafeRepository::discover_with_environment_overrides_opts(
+                ".",
+                Default::default(),
+                trust_map,
+            )?
+            .into(),
+        )
     }
 
     /// Determine whether the repository is in a clean state.
@@ -76,6 +89,17 @@ pub(crate) trait RepositoryExtended {
         options: &CommitOptions<'_>,
     ) -> Result<gix::ObjectId>;
 
+    /// Prepare to create multiple commits sharing the same author, committer, and
+    /// [`CommitOptions`].
+    ///
+    /// See [`CommitBatch`].
+    fn commit_batch<'a>(
+        &'a self,
+        author: &'a gix::actor::Signature,
+        committer: &'a gix::actor::Signature,
+        options: &'a CommitOptions<'_>,
+    ) -> Result<CommitBatch<'a>>;
+
     /// [`gix::Repository::rev_parse_single()`] with StGit-specific error mapping.
     fn rev_parse_single_ex(&self, spec: &str) -> Result<gix::Id<'_>>;
 }
@@ -85,8 +109,62 @@ pub(crate) struct CommitOptions<'a> {
     /// The target encoding for the commit message.
     pub(crate) commit_encoding: Option<Cow<'a, BStr>>,
 
-    /// Determine whether the commit object should be signed with GPG.
-    pub(crate) gpgsign: bool,
+    /// Determine whether, and with which key, the commit object should be signed
+    /// with GPG.
+    pub(crate) gpgsign: GpgSign,
+}
+
+/// Creates commits sharing the same author, committer, and [`CommitOptions`].
+///
+/// The commit encoding is resolved once for the whole batch and unsigned commits are
+/// written in-process, thus avoiding per-commit overhead when creating many commits in
+/// a loop. Signed commits still require a `git commit-tree` process per commit.
+pub(crate) struct CommitBatch<'a> {
+    repo: &'a gix::Repository,
+    author: &'a gix::actor::Signature,
+    committer: &'a gix::actor::Signature,
+    commit_encoding: Option<&'static encoding_rs::Encoding>,
+    gpgsign: &'a GpgSign,
+}
+
+impl CommitBatch<'_> {
+    /// Create a new commit object in the repository.
+    pub(crate) fn commit(
+        &self,
+        message: &Message,
+        tree_id: gix::ObjectId,
+        parent_ids: impl IntoIterator<Item = gix::ObjectId>,
+    ) -> Result<gix::ObjectId> {
+        if self.gpgsign.is_enabled() {
+            // Use git for any commit that needs to be signed. The message is already
+            // determined, so git must never spawn an editor.
+            self.repo.stupid().use_editor(false).commit_tree(
+                self.author,
+                self.committer,
+                &message.encode_with(self.commit_encoding)?,
+                tree_id,
+                parent_ids,
+                self.gpgsign,
+            )
+        } else {
+            // Use gitoxide for all other occasions. The encoding header records the
+            // canonical name of the encoding rather than the configured label.
+            let message = match self.commit_encoding {
+                Some(enc) if enc != encoding_rs::UTF_8 => message.encode_with(Some(enc))?,
+                _ => Cow::Borrowed(message.raw_bytes()),
+            };
+            let commit_id = self.repo.write_object(&gix::objs::Commit {
+                tree: tree_id,
+                parents: parent_ids.into_iter().collect(),
+                author: self.author.clone(),
+                committer: self.committer.clone(),
+                encoding: self.commit_encoding.map(|enc| enc.name().into()),
+                message: message.into_owned().into(),
+                extra_headers: vec![],
+            })?;
+            Ok(commit_id.detach())
+        }
+    }
 }
 
 impl RepositoryExtended for gix::Repository {
@@ -205,7 +283,7 @@ impl RepositoryExtended for gix::Repository {
     ) -> Result<gix::ObjectId> {
         let config = self.config_snapshot();
         let commit_encoding = config.string("i18n.commitencoding");
-        let gpgsign = config.boolean("commit.gpgsign").unwrap_or(false);
+        let gpgsign = GpgSign::from_bool(config.boolean("commit.gpgsign").unwrap_or(false));
         self.commit_with_options(
             author,
             committer,
@@ -228,6 +306,16 @@ impl RepositoryExtended for gix::Repository {
         parent_ids: impl IntoIterator<Item = gix::ObjectId>,
         options: &CommitOptions<'_>,
     ) -> Result<gix::ObjectId> {
+        self.commit_batch(author, committer, options)?
+            .commit(message, tree_id, parent_ids)
+    }
+
+    fn commit_batch<'a>(
+        &'a self,
+        author: &'a gix::actor::Signature,
+        committer: &'a gix::actor::Signature,
+        options: &'a CommitOptions<'_>,
+    ) -> Result<CommitBatch<'a>> {
         let commit_encoding = match &options.commit_encoding {
             Some(s) => {
                 let encoding = encoding_rs::Encoding::for_label(s)
@@ -236,32 +324,13 @@ impl RepositoryExtended for gix::Repository {
             }
             None => None,
         };
-
-        if options.gpgsign
-            || (commit_encoding.is_some() && commit_encoding != Some(encoding_rs::UTF_8))
-        {
-            // Use git for any commit that needs to be signed
-            self.stupid().commit_tree(
-                author,
-                committer,
-                &message.encode_with(commit_encoding)?,
-                tree_id,
-                parent_ids,
-                options.gpgsign,
-            )
-        } else {
-            // Use gitoxide for all other occasions
-            let commit_id = self.write_object(&gix::objs::Commit {
-                tree: tree_id,
-                parents: parent_ids.into_iter().collect(),
-                author: author.clone(),
-                committer: committer.to_owned(),
-                encoding: commit_encoding.map(|enc| enc.name().into()),
-                message: message.raw_bytes().into(),
-                extra_headers: vec![],
-            })?;
-            Ok(commit_id.detach())
-        }
+        Ok(CommitBatch {
+            repo: self,
+            author,
+            committer,
+            commit_encoding,
+            gpgsign: &options.gpgsign,
+        })
     }
 
     fn rev_parse_single_ex(&self, spec: &str) -> Result<gix::Id<'_>> {
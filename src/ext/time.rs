@@ -15,18 +15,41 @@ pub(crate) trait TimeExtended {
     /// | `iso8601`         | `2022-01-06 09:32:07 -0500`      |
     /// | `iso8601-strict`  | `2022-01-06T09:32:07-05:00`      |
     /// | `raw`             | `1641479527 -0500`               |
+    /// | `raw` with `@`    | `@1641479527 -0500`              |
+    /// | `relative`        | `2 weeks ago` or `2.weeks.ago`   |
     /// | `now`             | `now`                            |
     /// | `gitoxide default`| `Thu Jan 6 2022 09:32:07 -0500`  |
+    ///
+    /// This is the single place where user-provided dates are parsed, such that all
+    /// commands accept the same formats and report invalid dates consistently.
     fn parse_time(time_str: &str) -> Result<gix::date::Time> {
         let time_str = time_str.trim();
         let now = std::time::SystemTime::now();
         let zoned_now = jiff::Zoned::try_from(now).unwrap_or_else(|_| jiff::Zoned::now());
 
+        // As with git, a raw timestamp may be marked with a leading '@'.
+        let time_str = time_str
+            .strip_prefix('@')
+            .filter(|rest| rest.starts_with(|c: char| c.is_ascii_digit() || c == '-'))
+            .unwrap_or(time_str);
+
         if time_str == "now" {
             Ok(gix::date::Time::new(
                 zoned_now.timestamp().as_second(),
                 zoned_now.offset().seconds(),
             ))
+        } else if let Some(relative_str) = time_str
+            .strip_suffix("ago")
+            .filter(|rest| rest.ends_with([' ', '.']))
+        {
+            // Git also accepts '.' separators in relative dates, e.g. `2.weeks.ago`.
+            let relative_str = format!("{}ago", relative_str.replace('.', " "));
+            let time = gix::date::parse(&relative_str, Some(now))
+                .map_err(|_| anyhow!("invalid date `{time_str}`"))?;
+            Ok(gix::date::Time::new(
+                time.seconds,
+                zoned_now.offset().seconds(),
+            ))
         } else if let Ok(time) = gix::date::parse(time_str, Some(now)) {
             Ok(time)
         } else if let Ok(time) = gix::date::parse(
@@ -43,9 +66,29 @@ pub(crate) trait TimeExtended {
             Err(anyhow!("invalid date `{time_str}`"))
         }
     }
+
+    /// Format time in git's raw `<seconds> <+/-HHMM>` form.
+    ///
+    /// The time's original UTC offset is preserved, making this form suitable for
+    /// `GIT_AUTHOR_DATE` and `GIT_COMMITTER_DATE`.
+    fn raw_string(&self) -> String;
+
+    /// Format time in RFC 2822 form, e.g. `Thu, 6 Jan 2022 09:32:07 -0500`.
+    ///
+    /// This is the form git uses for `--date=rfc2822` and in email `Date` headers. The
+    /// time's original UTC offset is preserved.
+    fn rfc2822_string(&self) -> String;
 }
 
-impl TimeExtended for gix::date::Time {}
+impl TimeExtended for gix::date::Time {
+    fn raw_string(&self) -> String {
+        self.format(gix::date::time::format::RAW)
+    }
+
+    fn rfc2822_string(&self) -> String {
+        self.format(gix::date::time::format::GIT_RFC2822)
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -101,6 +144,50 @@ mod tests {
         assert_eq!(time.offset, -150 * 60);
     }
 
+    #[test]
+    fn raw_string_preserves_offset() {
+        for time_str in ["123456 +0000", "123456 +0530", "123456 -0230", "-42 -0030"] {
+            assert_eq!(Time::parse_time(time_str).unwrap().raw_string(), time_str);
+        }
+    }
+
+    #[test]
+    fn rfc2822_string_preserves_offset() {
+        for (raw, rfc2822) in [
+            ("1641479527 -0500", "Thu, 6 Jan 2022 09:32:07 -0500"),
+            ("1641479527 +0530", "Thu, 6 Jan 2022 20:02:07 +0530"),
+            ("0 +0000", "Thu, 1 Jan 1970 00:00:00 +0000"),
+        ] {
+            let time = Time::parse_time(raw).unwrap();
+            assert_eq!(time.rfc2822_string(), rfc2822);
+            assert_eq!(Time::parse_time(rfc2822).unwrap(), time);
+        }
+    }
+
+    #[test]
+    fn parse_at_raw() {
+        for (at_raw, raw) in [("@123456 +0600", "123456 +0600"), ("@123456", "123456")] {
+            assert_eq!(
+                Time::parse_time(at_raw).unwrap(),
+                Time::parse_time(raw).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn parse_relative() {
+        let now = Time::parse_time("now").unwrap();
+        for (s, seconds_ago) in [
+            ("2 weeks ago", 2 * 7 * 24 * 60 * 60),
+            ("2.weeks.ago", 2 * 7 * 24 * 60 * 60),
+            ("3 hours ago", 3 * 60 * 60),
+        ] {
+            let time = Time::parse_time(s).unwrap();
+            assert!((now.seconds - seconds_ago - time.seconds).abs() < 60);
+            assert_eq!(time.offset, now.offset);
+        }
+    }
+
     #[test]
     fn test_parse_bad_times() {
         for bad_str in [
@@ -109,6 +196,10 @@ mod tests {
             "123456 -060",
             "123456 +06000",
             "123456 06000",
+            "@",
+            "@abc",
+            "ago",
+            "many.weeks.ago",
         ] {
             assert!(Time::parse_time(bad_str).is_err());
         }
@@ -3,70 +3,153 @@
 //! Support for using git repository hooks.
 
 use std::{
-    borrow::Cow,
-    io::Write,
+    io::{Read, Write},
     path::{Path, PathBuf},
 };
 
 use anyhow::{anyhow, Context, Result};
-use bstr::BString;
+use bstr::{BString, ByteSlice};
 
-use crate::wrap::Message;
+use crate::{stupid::Stupid, wrap::Message};
 
-/// Find path to hook script given a hook name.
+/// A directory in which hook scripts are looked up.
+pub(crate) struct HooksDir {
+    /// Location of the directory.
+    pub(crate) path: PathBuf,
+    /// Config variable that specified the directory, or `None` for the default
+    /// `hooks` directory in the repository.
+    pub(crate) source: Option<&'static str>,
+}
+
+/// Find the directories where hook scripts are located, in search order.
 ///
-/// Returns None if the hook script is not found or is not executable.
-fn get_hook_path(repo: &gix::Repository, hook_name: &str) -> Result<Option<PathBuf>> {
+/// The first directory is determined by `core.hooksPath`, falling back to the
+/// repository's default `hooks` directory. Supplemental directories from any
+/// `stgit.hooksPath` config values follow, in the order they are configured.
+pub(crate) fn get_hooks_dirs(repo: &gix::Repository) -> Result<Vec<HooksDir>> {
     let config = repo.config_snapshot();
-    let hooks_path =
-        if let Some(core_hooks_path) = config.trusted_path("core.hookspath").transpose()? {
-            if core_hooks_path.is_absolute() {
-                core_hooks_path
-            } else if repo.is_bare() {
-                // The hooks path is relative to GIT_DIR in the case of a bare repo
-                Cow::Owned(repo.common_dir().join(core_hooks_path))
-            } else {
-                // The hooks path is relative to the root of the working tree otherwise
-                let work_dir = repo.workdir().expect("non-bare repo must have work dir");
-                Cow::Owned(work_dir.join(core_hooks_path))
-            }
-        } else {
-            // No core.hookspath, use default .git/hooks location
-            Cow::Owned(repo.common_dir().join("hooks"))
-        };
-    let hook_path = hooks_path.join(hook_name);
+    let mut dirs = Vec::new();
+    if let Some(core_hooks_path) = config.trusted_path("core.hookspath").transpose()? {
+        dirs.push(HooksDir {
+            path: resolve_hooks_path(repo, &core_hooks_path)?,
+            source: Some("core.hooksPath"),
+        });
+    } else {
+        // No core.hookspath, use default .git/hooks location
+        dirs.push(HooksDir {
+            path: repo.common_dir().join("hooks"),
+            source: None,
+        });
+    }
 
-    let hook_meta = match std::fs::metadata(&hook_path) {
-        Ok(meta) => meta,
-        Err(_) => return Ok(None), // ignore missing hook
-    };
+    if let Some(stgit_hooks_paths) = config.strings("stgit.hookspath") {
+        let home_dir = gix::path::env::home_dir();
+        for value in stgit_hooks_paths {
+            let path = gix::config::Path::from(value)
+                .interpolate(gix::config::path::interpolate::Context {
+                    home_dir: home_dir.as_deref(),
+                    ..Default::default()
+                })
+                .context("invalid `stgit.hooksPath`")?;
+            dirs.push(HooksDir {
+                path: resolve_hooks_path(repo, &path)?,
+                source: Some("stgit.hooksPath"),
+            });
+        }
+    }
+
+    Ok(dirs)
+}
 
-    if !is_executable(&hook_meta) {
-        return Ok(None);
+/// Resolve a configured hooks directory path relative to the repository.
+fn resolve_hooks_path(repo: &gix::Repository, hooks_path: &Path) -> Result<PathBuf> {
+    if hooks_path.is_absolute() {
+        Ok(hooks_path.to_path_buf())
+    } else if repo.is_bare() {
+        // The hooks path is relative to GIT_DIR in the case of a bare repo
+        Ok(repo.common_dir().join(hooks_path))
+    } else {
+        // The hooks path is relative to the root of the working tree otherwise
+        let work_dir = repo.workdir().ok_or_else(|| {
+            anyhow!(
+                "cannot resolve relative hooks path `{}` without a working tree",
+                hooks_path.display()
+            )
+        })?;
+        Ok(work_dir.join(hooks_path))
     }
+}
+
+/// Find path to hook script given a hook name.
+///
+/// Each of the hooks directories is searched in order and the first executable hook
+/// script found is returned. Returns None if no executable hook script is found.
+pub(crate) fn get_hook_path(repo: &gix::Repository, hook_name: &str) -> Result<Option<PathBuf>> {
+    for hooks_dir in get_hooks_dirs(repo)? {
+        let hook_path = hooks_dir.path.join(hook_name);
+
+        let hook_meta = match std::fs::metadata(&hook_path) {
+            Ok(meta) => meta,
+            Err(_) => continue, // ignore missing hook
+        };
+
+        if is_executable(&hook_meta) {
+            return Ok(Some(gix::path::realpath(hook_path)?));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Outcome of successfully running, or not running, the `pre-commit` hook.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum PreCommitHookOutcome {
+    /// The hook did not run due to `no_verify` or the script not existing, not being
+    /// a file, or not being executable.
+    NotRun,
+
+    /// The hook ran and the status of the index and worktree is unchanged.
+    Unchanged,
 
-    let hook_path = gix::path::realpath(hook_path)?;
+    /// The hook ran and changed the index, and possibly the worktree, e.g. by
+    /// reformatting and staging files.
+    IndexChanged,
 
-    Ok(Some(hook_path))
+    /// The hook ran and changed only the worktree, e.g. by reformatting files without
+    /// staging them.
+    WorktreeChanged,
 }
 
 /// Run the git `pre-commit` hook script.
 ///
 /// The `use_editor` flag determines whether the hook should be allowed to invoke an
-/// interactive editor.
+/// interactive editor. When `no_verify` is true, the hook is treated as absent, as
+/// with `git commit --no-verify`.
 ///
-/// Returns `Ok(true)` if the hook ran and completed successfully, `Err()` if the hook
-/// ran but failed, and `Ok(false)` if the hook did not run due to the script not
-/// existing, not being a file, or not being executable.
-pub(crate) fn run_pre_commit_hook(repo: &gix::Repository, use_editor: bool) -> Result<bool> {
+/// Changes made by the hook are detected by comparing the status of tracked files
+/// from before and after the hook runs. The status from before the hook runs is
+/// captured once and used to determine both whether anything changed and whether the
+/// index changed. Since the status records the index's object ids, any change to the
+/// index is detected; changes to the worktree are detected for files whose worktree
+/// contents matched the index before the hook ran.
+///
+/// Returns `Err()` if the hook ran but failed.
+pub(crate) fn run_pre_commit_hook(
+    repo: &gix::Repository,
+    use_editor: bool,
+    no_verify: bool,
+) -> Result<PreCommitHookOutcome> {
+    if no_verify {
+        return Ok(PreCommitHookOutcome::NotRun);
+    }
     let hook_name = "pre-commit";
     let hook_path = if let Some(hook_path) = get_hook_path(repo, hook_name)? {
         hook_path
     } else {
-        return Ok(false);
+        return Ok(PreCommitHookOutcome::NotRun);
     };
 
-    let work_dir = repo.workdir().expect("not a bare repo");
+    let work_dir = hook_work_dir(repo, hook_name)?;
 
     let mut hook_command = std::process::Command::from(
         gix::command::prepare(hook_path).stdout(std::process::Stdio::inherit()),
@@ -76,17 +159,16 @@ pub(crate) fn run_pre_commit_hook(repo: &gix::Repository, use_editor: bool) -> R
         hook_command.env("GIT_EDITOR", ":");
     }
 
-    let status = hook_command
-        .status()
-        .with_context(|| format!("`{hook_name}` hook"))?;
-
-    if status.success() {
-        Ok(true)
+    let stupid = repo.stupid();
+    let statuses_before = stupid.statuses(None)?;
+    run_hook_command(hook_name, hook_command)?;
+    let statuses_after = stupid.statuses(None)?;
+    if statuses_after == statuses_before {
+        Ok(PreCommitHookOutcome::Unchanged)
+    } else if statuses_after.index_differs(&statuses_before) {
+        Ok(PreCommitHookOutcome::IndexChanged)
     } else {
-        Err(anyhow!(
-            "`{hook_name}` hook returned {}",
-            status.code().unwrap_or(-1)
-        ))
+        Ok(PreCommitHookOutcome::WorktreeChanged)
     }
 }
 
@@ -96,15 +178,24 @@ pub(crate) fn run_pre_commit_hook(repo: &gix::Repository, use_editor: bool) -> R
 /// `commit-msg` script, and deleted after the script exits.
 ///
 /// The `use_editor` flag determines whether the hook should be allowed to invoke an
-/// interactive editor.
+/// interactive editor. When `no_verify` is true, the hook is treated as absent, as
+/// with `git commit --no-verify`.
+///
+/// Returns the unmodified message if `no_verify` is true or if the hook script does
+/// not exist, is not a file, or is not executable.
 ///
-/// Returns successfully if the hook script does not exist, is not a file, or is not
-/// executable.
+/// Hooks, or editors invoked by hooks, may rewrite the message file with CRLF line
+/// endings. When `core.autocrlf` or `core.eol` indicate CRLF line endings are in use,
+/// CRLF line endings in the hook's message are normalized to LF.
 pub(crate) fn run_commit_msg_hook<'repo>(
     repo: &gix::Repository,
     message: Message<'repo>,
     use_editor: bool,
+    no_verify: bool,
 ) -> Result<Message<'repo>> {
+    if no_verify {
+        return Ok(message);
+    }
     let hook_name = "commit-msg";
     let hook_path = if let Some(hook_path) = get_hook_path(repo, hook_name)? {
         hook_path
@@ -112,7 +203,7 @@ pub(crate) fn run_commit_msg_hook<'repo>(
         return Ok(message);
     };
 
-    let work_dir = repo.workdir().expect("not a bare repo");
+    let work_dir = hook_work_dir(repo, hook_name)?;
     let temp_msg = TemporaryMessage::new(work_dir, &message)?;
 
     let index_path = repo.index_path();
@@ -130,28 +221,148 @@ pub(crate) fn run_commit_msg_hook<'repo>(
 
     hook_command.arg(temp_msg.filename());
 
-    let status = hook_command
-        .status()
+    run_hook_command(hook_name, hook_command)?;
+
+    let message_bytes = temp_msg.read()?;
+    let encoding = message.encoding()?;
+    let message = encoding
+        .decode_without_bom_handling_and_without_replacement(&message_bytes)
+        .ok_or_else(|| {
+            anyhow!("message could not be decoded with `{}`", encoding.name())
+                .context("`{hook_name}` hook")
+        })?;
+    if use_crlf_normalization(repo) && message.contains("\r\n") {
+        Ok(Message::from(message.replace("\r\n", "\n")))
+    } else {
+        Ok(Message::from(message.to_string()))
+    }
+}
+
+/// Determine whether CRLF line endings should be normalized to LF.
+///
+/// This is the case when `core.autocrlf` is `true` or `input`, or when `core.eol`
+/// is `crlf`.
+fn use_crlf_normalization(repo: &gix::Repository) -> bool {
+    let config = repo.config_snapshot();
+    let autocrlf = config.string("core.autocrlf").is_some_and(|value| {
+        value.eq_ignore_ascii_case(b"input")
+            || gix::config::Boolean::try_from(value.as_ref()).is_ok_and(|b| b.0)
+    });
+    autocrlf
+        || config
+            .string("core.eol")
+            .is_some_and(|value| value.eq_ignore_ascii_case(b"crlf"))
+}
+
+/// Get the work tree directory in which a hook is run.
+///
+/// Hooks are only looked up before calling this, such that a bare repository without
+/// the hook is unaffected, but a hook that is present cannot be run without a working
+/// tree.
+fn hook_work_dir<'repo>(repo: &'repo gix::Repository, hook_name: &str) -> Result<&'repo Path> {
+    repo.workdir().ok_or_else(|| {
+        anyhow!("hooks require a working tree").context(format!("`{hook_name}` hook"))
+    })
+}
+
+/// Maximum number of lines of a failed hook's stderr to include in the error.
+const HOOK_STDERR_TAIL_LINES: usize = 20;
+
+/// A hook that ran, but exited unsuccessfully.
+///
+/// Serializes as the `hook` record of `--json` error diagnostics.
+#[derive(Debug, serde::Serialize)]
+pub(crate) struct HookError {
+    /// Name of the failed hook, e.g. "pre-commit".
+    #[serde(rename = "name")]
+    hook_name: String,
+    /// Exit code of the hook, or -1 if the hook was terminated by a signal.
+    #[serde(rename = "exit_code")]
+    code: i32,
+    /// Up to [`HOOK_STDERR_TAIL_LINES`] trailing lines of the hook's stderr.
+    stderr: String,
+}
+
+impl std::fmt::Display for HookError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "`{}` hook returned {}", self.hook_name, self.code)?;
+        if !self.stderr.is_empty() {
+            write!(f, ": {}", self.stderr)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for HookError {}
+
+/// Run a hook command to completion.
+///
+/// The hook's stdin and stdout are inherited. The hook's stderr is relayed to StGit's
+/// stderr as it is produced while its tail is retained such that, if the hook fails,
+/// the tail of its stderr is included in the returned [`HookError`].
+fn run_hook_command(hook_name: &str, mut hook_command: std::process::Command) -> Result<()> {
+    let mut child = hook_command
+        .stdin(std::process::Stdio::inherit())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .with_context(|| format!("`{hook_name}` hook"))?;
+
+    let mut child_stderr = child.stderr.take().expect("hook stderr is piped");
+    let mut stderr = std::io::stderr();
+    let mut tail = Vec::new();
+    let mut buf = [0u8; 8192];
+    loop {
+        let len = match child_stderr.read(&mut buf) {
+            Ok(0) => break,
+            Ok(len) => len,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e).with_context(|| format!("`{hook_name}` hook stderr")),
+        };
+        stderr.write_all(&buf[..len])?;
+        stderr.flush()?;
+        tail.extend_from_slice(&buf[..len]);
+        truncate_to_tail_lines(&mut tail, HOOK_STDERR_TAIL_LINES);
+    }
+
+    let status = child
+        .wait()
         .with_context(|| format!("`{hook_name}` hook"))?;
 
     if status.success() {
-        let message_bytes = temp_msg.read()?;
-        let encoding = message.encoding()?;
-        let message = encoding
-            .decode_without_bom_handling_and_without_replacement(&message_bytes)
-            .ok_or_else(|| {
-                anyhow!("message could not be decoded with `{}`", encoding.name())
-                    .context("`{hook_name}` hook")
-            })?;
-        Ok(Message::from(message.to_string()))
-    } else {
-        Err(anyhow!(
-            "`{hook_name}` hook returned {}",
-            status.code().unwrap_or(-1)
-        ))
+        return Ok(());
     }
+
+    let tail = tail.to_str_lossy();
+    let lines: Vec<&str> = tail.trim_end().lines().collect();
+    Err(HookError {
+        hook_name: hook_name.to_string(),
+        code: status.code().unwrap_or(-1),
+        stderr: lines[lines.len().saturating_sub(HOOK_STDERR_TAIL_LINES)..].join("\n"),
+    }
+    .into())
 }
 
+/// Discard all but the last `max_lines` lines of `buf`, disregarding trailing
+/// whitespace.
+///
+/// A partial last line counts as a line.
+fn truncate_to_tail_lines(buf: &mut Vec<u8>, max_lines: usize) {
+    let content_len = buf.trim_end().len();
+    if let Some(pos) = buf[..content_len]
+        .rfind_iter(b"\n")
+        .nth(max_lines.saturating_sub(1))
+    {
+        buf.drain(..=pos);
+    }
+}
+
+/// Prefix of temporary commit message file names.
+const TEMP_MESSAGE_PREFIX: &str = ".stgit-msg-temp-";
+
+/// Age beyond which a temporary message file is assumed to be left over by a prior
+/// StGit process that crashed or was killed.
+const STALE_TEMP_MESSAGE_AGE: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
 /// Temporary commit message file for commit-msg hook.
 ///
 /// The temporary file is created relative to the work dir using the StGit process id to
@@ -165,7 +376,7 @@ impl<'repo> TemporaryMessage<'repo> {
     /// Create new temporary file containing commit message.
     fn new(work_dir: &'repo Path, message: &Message<'repo>) -> Result<Self> {
         let pid = std::process::id();
-        let filename = PathBuf::from(format!(".stgit-msg-temp-{pid}"));
+        let filename = PathBuf::from(format!("{TEMP_MESSAGE_PREFIX}{pid}"));
         let msg_path = work_dir.join(&filename);
         let mut msg_file = std::fs::OpenOptions::new()
             .create_new(true)
@@ -193,10 +404,56 @@ impl Drop for TemporaryMessage<'_> {
         let msg_path = self.work_dir.join(&self.filename);
         if msg_path.is_file() {
             if let Err(e) = std::fs::remove_file(&msg_path) {
-                panic!("failed to remove temp message {msg_path:?}: {e}");
+                // Panicking in drop could abort the process, e.g. when the file is
+                // still locked by a hook process on Windows.
+                eprintln!("warning: {}", temp_message_removal_failure(&msg_path, &e));
+            }
+        }
+    }
+}
+
+/// Remove stale temporary message files left behind by prior StGit processes.
+///
+/// Only files not modified within [`STALE_TEMP_MESSAGE_AGE`] are removed such that
+/// the temporary files of concurrently running StGit processes are left alone.
+/// Cleanup is best-effort; a warning message is returned for each stale file that
+/// could not be removed.
+pub(crate) fn remove_stale_temp_messages(work_dir: &Path) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let Ok(entries) = std::fs::read_dir(work_dir) else {
+        return warnings;
+    };
+    let now = std::time::SystemTime::now();
+    for entry in entries.filter_map(Result::ok) {
+        if !entry
+            .file_name()
+            .to_str()
+            .is_some_and(|name| name.starts_with(TEMP_MESSAGE_PREFIX))
+        {
+            continue;
+        }
+        let is_stale = entry
+            .metadata()
+            .and_then(|meta| meta.modified())
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .is_some_and(|age| age > STALE_TEMP_MESSAGE_AGE);
+        if is_stale {
+            let msg_path = entry.path();
+            if let Err(e) = std::fs::remove_file(&msg_path) {
+                warnings.push(temp_message_removal_failure(&msg_path, &e));
             }
         }
     }
+    warnings
+}
+
+/// Describe the failure to remove a temporary message file.
+fn temp_message_removal_failure(msg_path: &Path, e: &std::io::Error) -> String {
+    format!(
+        "failed to remove temporary message file `{}`: {e}",
+        msg_path.display()
+    )
 }
 
 #[cfg(unix)]
@@ -209,3 +466,23 @@ fn is_executable(meta: &std::fs::Metadata) -> bool {
 fn is_executable(_meta: &std::fs::Metadata) -> bool {
     true
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncate_stderr_tail() {
+        let mut buf = b"one\ntwo\nthree\nfour\n\n".to_vec();
+        truncate_to_tail_lines(&mut buf, 2);
+        assert_eq!(buf, b"three\nfour\n\n");
+
+        let mut buf = b"one\ntwo\nthr".to_vec();
+        truncate_to_tail_lines(&mut buf, 2);
+        assert_eq!(buf, b"two\nthr");
+
+        let mut buf = b"one\ntwo\n".to_vec();
+        truncate_to_tail_lines(&mut buf, 2);
+        assert_eq!(buf, b"one\ntwo\n");
+    }
+}
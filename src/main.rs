@@ -12,8 +12,10 @@ mod argset;
 mod branchloc;
 mod cmd;
 mod color;
+mod configparams;
 mod ext;
 mod hook;
+mod pager;
 mod patch;
 mod signal;
 mod stack;
@@ -90,6 +92,32 @@ fn get_base_command(color_choice: Option<termcolor::ColorChoice>) -> clap::Comma
                 .value_name("path")
                 .value_hint(clap::ValueHint::AnyPath),
         )
+        .arg(
+            clap::Arg::new("config")
+                .short('c')
+                .help("Pass a configuration parameter to git")
+                .long_help(
+                    "Pass a configuration parameter which overrides values from \
+                     configuration files for the duration of this command, as with \
+                     `git -c`. The parameter is also passed on to any git commands run \
+                     by stg. The <name> is expected in the same format as listed by \
+                     `git config`. Omitting '=<value>' sets <name> to boolean true.",
+                )
+                .action(clap::ArgAction::Append)
+                .value_name("name>=<value"),
+        )
+        .arg(
+            clap::Arg::new("json")
+                .long("json")
+                .help("Report errors as JSON diagnostics")
+                .long_help(
+                    "Report a failed command's error on stderr as a single-line JSON \
+                     record instead of as a human-readable message. When a git hook \
+                     caused the failure, the record includes the hook's name, exit \
+                     code, and the tail of its stderr.",
+                )
+                .action(clap::ArgAction::SetTrue),
+        )
         .arg(color::get_color_arg().global(true).display_order(998));
 
     // Ensure "stg" and not "stg.exe" shows up in usage on Windows.
@@ -153,7 +181,7 @@ fn main() -> ! {
     let color_choice = color::parse_color_choice(&argv);
 
     if let Err(e) = self::signal::setup() {
-        exit_with_result(Err(e), color_choice)
+        exit_with_result(Err(e), color_choice, ErrorFormat::Human)
     }
 
     // Avoid the expense of constructing a full-blown clap::Command with all the dozens of
@@ -162,6 +190,12 @@ fn main() -> ! {
     // First, using a minimal top-level Command instance, let clap find anything that looks
     // like a subcommand name (i.e. by using AppSettings::AllowExternalSubcommands).
     if let Ok(matches) = get_bootstrap_command(color_choice).try_get_matches_from(&argv) {
+        let error_format = if matches.get_flag("json") {
+            ErrorFormat::Json
+        } else {
+            ErrorFormat::Human
+        };
+
         // N.B. changing directories here, early, affects which aliases will ultimately
         // be found.
         if matches.get_flag("version") {
@@ -169,11 +203,20 @@ fn main() -> ! {
                 &self::cmd::version::STGIT_COMMAND,
                 vec![argv[0].clone(), OsString::from("version")],
                 color_choice,
+                error_format,
             )
         } else if let Err(e) = change_directories(&matches) {
-            exit_with_result(Err(e), color_choice)
+            exit_with_result(Err(e), color_choice, error_format)
+        } else if let Err(e) = configparams::push_config_overrides(
+            matches
+                .get_many::<String>("config")
+                .into_iter()
+                .flatten()
+                .map(String::as_str),
+        ) {
+            exit_with_result(Err(e), color_choice, error_format)
         } else if matches.get_flag("help-option") {
-            full_app_help(argv, None, color_choice)
+            full_app_help(argv, None, color_choice, error_format)
         } else if let Some((sub_name, sub_matches)) = matches.subcommand() {
             // If the name matches any known subcommands, then only the Command for that
             // particular command is constructed and the costs of searching for aliases
@@ -182,7 +225,7 @@ fn main() -> ! {
                 .iter()
                 .find(|command| command.name == sub_name)
             {
-                execute_command(command, argv, color_choice)
+                execute_command(command, argv, color_choice, error_format)
             } else {
                 // If the subcommand name does not match a builtin subcommand, the
                 // aliases are located, which involves finding the Git repo and parsing
@@ -190,7 +233,7 @@ fn main() -> ! {
                 // alias, it is executed and the cost of constructing all subcommands'
                 // Command instances is still avoided.
                 match get_aliases() {
-                    Err(e) => exit_with_result(Err(e), color_choice),
+                    Err(e) => exit_with_result(Err(e), color_choice, error_format),
                     Ok((aliases, maybe_repo)) => {
                         if let Some(alias) = aliases.get(sub_name) {
                             let user_args: Vec<OsString> = sub_matches
@@ -202,6 +245,7 @@ fn main() -> ! {
                                     alias,
                                     user_args,
                                     color_choice,
+                                    error_format,
                                     maybe_repo.as_ref(),
                                 ),
                                 alias::AliasKind::StGit => execute_stgit_alias(
@@ -209,6 +253,7 @@ fn main() -> ! {
                                     &argv[0],
                                     user_args,
                                     color_choice,
+                                    error_format,
                                     &aliases,
                                 ),
                             }
@@ -219,26 +264,40 @@ fn main() -> ! {
                             // each subcommand and alias. The command line is then
                             // re-processed by this full-blown Command instance which is
                             // expected to terminate with an appropriate help message.
-                            full_app_help(argv, Some(aliases), color_choice)
+                            full_app_help(argv, Some(aliases), color_choice, error_format)
                         }
                     }
                 }
             }
         } else {
-            full_app_help(argv, None, color_choice)
+            full_app_help(argv, None, color_choice, error_format)
         }
     } else {
         // -C options are not processed in this branch. This is okay because clap's
         // error message will not include aliases (which depend on -C).
-        full_app_help(argv, None, color_choice)
+        full_app_help(argv, None, color_choice, ErrorFormat::Human)
     }
 }
 
+/// Format of the error message reported by [`exit_with_result()`].
+#[derive(Clone, Copy)]
+enum ErrorFormat {
+    /// Human-readable, possibly colored, error message.
+    Human,
+
+    /// Single-line JSON record, as requested with `--json`.
+    Json,
+}
+
 /// Exit the program based on the provided [`Result`].
 ///
 /// Error results from conflicts trigger merge conflicts to be printed and an exit code
 /// of [`CONFLICT_ERROR`].
-fn exit_with_result(result: Result<()>, color_choice: Option<termcolor::ColorChoice>) -> ! {
+fn exit_with_result(
+    result: Result<()>,
+    color_choice: Option<termcolor::ColorChoice>,
+    error_format: ErrorFormat,
+) -> ! {
     let code = match result {
         Ok(()) => 0,
         Err(e) => {
@@ -253,7 +312,10 @@ fn exit_with_result(result: Result<()>, color_choice: Option<termcolor::ColorCho
                 })
             }
 
-            print_error_message(color_choice, &e);
+            match error_format {
+                ErrorFormat::Human => print_error_message(color_choice, &e),
+                ErrorFormat::Json => print_error_json(&e),
+            }
 
             if let Some(e) = e.downcast_ref::<stack::TransactionError>() {
                 match e {
@@ -263,7 +325,7 @@ fn exit_with_result(result: Result<()>, color_choice: Option<termcolor::ColorCho
                         }
                         CONFLICT_ERROR
                     }
-                    stack::TransactionError::CheckoutConflicts(_) => CONFLICT_ERROR,
+                    stack::TransactionError::CheckoutConflicts { .. } => CONFLICT_ERROR,
                 }
             } else if let Some(e) = e.downcast_ref::<cmd::Error>() {
                 match e {
@@ -302,13 +364,14 @@ fn full_app_help(
     argv: Vec<OsString>,
     aliases: Option<alias::Aliases>,
     color_choice: Option<termcolor::ColorChoice>,
+    error_format: ErrorFormat,
 ) -> ! {
     let aliases = if let Some(aliases) = aliases {
         aliases
     } else {
         match get_aliases() {
             Ok((aliases, _)) => aliases,
-            Err(e) => exit_with_result(Err(e), color_choice),
+            Err(e) => exit_with_result(Err(e), color_choice, error_format),
         }
     };
 
@@ -393,6 +456,7 @@ fn execute_command(
     command: &cmd::StGitCommand,
     argv: Vec<OsString>,
     color_choice: Option<termcolor::ColorChoice>,
+    error_format: ErrorFormat,
 ) -> ! {
     match get_base_command(color_choice)
         .subcommand((command.make)())
@@ -402,7 +466,8 @@ fn execute_command(
             let (_sub_name, sub_matches) = top_matches
                 .subcommand()
                 .expect("this subcommand is already known to be in argv");
-            exit_with_result((command.run)(sub_matches), color_choice)
+            remove_stale_temp_messages(sub_matches);
+            exit_with_result((command.run)(sub_matches), color_choice, error_format)
         }
 
         Err(err) => {
@@ -412,6 +477,20 @@ fn execute_command(
     }
 }
 
+/// Remove temporary hook message files left behind by prior StGit processes.
+///
+/// This is a best-effort cleanup; failing to open the repository is not an error here
+/// since the subcommand reports any such failure itself.
+fn remove_stale_temp_messages(matches: &ArgMatches) {
+    if let Ok(repo) = gix::Repository::open() {
+        if let Some(work_dir) = repo.workdir() {
+            for warning in hook::remove_stale_temp_messages(work_dir) {
+                print_warning_message(matches, &warning);
+            }
+        }
+    }
+}
+
 /// Execute shell alias subprocess.
 ///
 /// If the child process fails, the parent process will be terminated, returning the
@@ -420,6 +499,7 @@ fn execute_shell_alias(
     alias: &alias::Alias,
     user_args: Vec<OsString>,
     color_choice: Option<termcolor::ColorChoice>,
+    error_format: ErrorFormat,
     repo: Option<&gix::Repository>,
 ) -> ! {
     if let Some(first_arg) = user_args.first() {
@@ -468,7 +548,7 @@ fn execute_shell_alias(
         )
     }) {
         Ok(status) => std::process::exit(status.code().unwrap_or(-1)),
-        Err(e) => exit_with_result(Err(e), color_choice),
+        Err(e) => exit_with_result(Err(e), color_choice, error_format),
     }
 }
 
@@ -480,6 +560,7 @@ fn execute_stgit_alias(
     exec_path: &OsString,
     user_args: Vec<OsString>,
     color_choice: Option<termcolor::ColorChoice>,
+    error_format: ErrorFormat,
     aliases: &alias::Aliases,
 ) -> ! {
     let result = match alias.split() {
@@ -506,7 +587,7 @@ fn execute_stgit_alias(
                 .iter()
                 .find(|command| command.name == resolved_cmd_name)
             {
-                execute_command(command, argv, color_choice)
+                execute_command(command, argv, color_choice, error_format)
             } else if aliases.contains_key(resolved_cmd_name) {
                 Err(anyhow!("recursive alias `{}`", alias.name))
             } else {
@@ -519,7 +600,7 @@ fn execute_stgit_alias(
         Err(reason) => Err(anyhow!("bad alias for `{}`: {reason}", alias.name)),
     };
 
-    exit_with_result(result, color_choice)
+    exit_with_result(result, color_choice, error_format)
 }
 
 /// Get aliases mapping.
@@ -596,7 +677,12 @@ fn print_message(
 }
 
 /// Print user-facing informational message to stderr.
+///
+/// The message is suppressed if the command has a `--quiet` flag that is set.
 pub(crate) fn print_info_message(matches: &ArgMatches, msg: &str) {
+    if matches!(matches.try_get_one::<bool>("quiet"), Ok(Some(true))) {
+        return;
+    }
     let mut stderr = color::get_color_stderr(matches);
     print_message("info", termcolor::Color::Blue, &mut stderr, msg);
 }
@@ -622,6 +708,19 @@ fn print_error_message(color_choice: Option<termcolor::ColorChoice>, err: &anyho
     print_message("error", termcolor::Color::Red, &mut stderr, &err_string);
 }
 
+/// Print error as a single-line JSON record to stderr.
+///
+/// The record's `error` is the same message that would otherwise be printed. If the
+/// error was caused by a failed hook, the record's `hook` reports the hook's name,
+/// exit code, and the tail of its stderr.
+fn print_error_json(err: &anyhow::Error) {
+    let mut record = serde_json::json!({ "error": format!("{err:#}") });
+    if let Some(hook_err) = err.downcast_ref::<hook::HookError>() {
+        record["hook"] = serde_json::to_value(hook_err).expect("hook error is serializable");
+    }
+    eprintln!("{record}");
+}
+
 /// Print file names with merge conflicts to stdout.
 // TODO: this should print to stderr instead.
 fn print_merge_conflicts() {
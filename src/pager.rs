@@ -0,0 +1,122 @@
+// SPDX-License-Identifier: GPL-2.0-only
+
+//! Page command output through the user's pager.
+//!
+//! Output is paged in the same manner as git: the pager is only used when stdout is a
+//! terminal and is determined from, in order, `GIT_PAGER`, `core.pager`, `PAGER`, and
+//! finally `less`.
+
+use std::{
+    io::Write,
+    process::{Child, ChildStdin, Stdio},
+};
+
+use bstr::ByteSlice;
+use is_terminal::IsTerminal;
+
+/// Destination for command output which is either a pager or stdout.
+///
+/// Output written to a [`Pager`] goes to the stdin of the spawned pager process, or to
+/// stdout if no pager is in use. When dropped, the pager's stdin is closed and the
+/// pager is waited on such that StGit does not exit while the user is still paging.
+pub(crate) struct Pager {
+    child: Option<Child>,
+    stdin: Option<ChildStdin>,
+}
+
+impl Pager {
+    /// Start the pager configured for the repository.
+    ///
+    /// Falls back to stdout when stdout is not a terminal, when StGit is itself
+    /// running under a pager spawned by git, when the pager is configured to be empty
+    /// or `cat`, or when the pager fails to spawn.
+    pub(crate) fn start(repo: &gix::Repository) -> Self {
+        if !std::io::stdout().is_terminal() || std::env::var_os("GIT_PAGER_IN_USE").is_some() {
+            return Self::stdout();
+        }
+        let Some(pager) = get_pager(repo) else {
+            return Self::stdout();
+        };
+
+        let mut command =
+            std::process::Command::from(gix::command::prepare(pager.as_str()).with_shell());
+        // As with git, `less` and `lv` are configured to pass through color and to
+        // exit when the output fits on one screen, unless the user says otherwise.
+        if std::env::var_os("LESS").is_none() {
+            command.env("LESS", "FRX");
+        }
+        if std::env::var_os("LV").is_none() {
+            command.env("LV", "-c");
+        }
+
+        match command.stdin(Stdio::piped()).spawn() {
+            Ok(mut child) => {
+                let stdin = child.stdin.take();
+                Self {
+                    child: Some(child),
+                    stdin,
+                }
+            }
+            Err(_) => Self::stdout(),
+        }
+    }
+
+    /// Output directly to stdout without paging.
+    pub(crate) fn stdout() -> Self {
+        Self {
+            child: None,
+            stdin: None,
+        }
+    }
+
+    /// Determine whether output is going to a pager process.
+    pub(crate) fn is_active(&self) -> bool {
+        self.stdin.is_some()
+    }
+}
+
+impl Write for Pager {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if let Some(stdin) = self.stdin.as_mut() {
+            stdin.write(buf)
+        } else {
+            std::io::stdout().write(buf)
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        if let Some(stdin) = self.stdin.as_mut() {
+            stdin.flush()
+        } else {
+            std::io::stdout().flush()
+        }
+    }
+}
+
+impl Drop for Pager {
+    fn drop(&mut self) {
+        // Closing the pager's stdin signals the end of output.
+        drop(self.stdin.take());
+        if let Some(mut child) = self.child.take() {
+            let _ = child.wait();
+        }
+    }
+}
+
+/// Determine the pager command, if any.
+///
+/// Returns `None` if the pager is configured to be empty or `cat`, which git also
+/// treats as disabling the pager.
+fn get_pager(repo: &gix::Repository) -> Option<String> {
+    let pager = std::env::var("GIT_PAGER")
+        .ok()
+        .or_else(|| {
+            repo.config_snapshot()
+                .string("core.pager")
+                .map(|pager| pager.to_str_lossy().into_owned())
+        })
+        .or_else(|| std::env::var("PAGER").ok())
+        .unwrap_or_else(|| "less".to_string());
+    let pager = pager.trim();
+    (!pager.is_empty() && pager != "cat").then(|| pager.to_string())
+}
@@ -97,7 +97,11 @@ pub(crate) fn add_args(
         .arg(
             Arg::new("no-verify")
                 .long("no-verify")
-                .help("Disable commit-msg hook")
+                .help("Bypass the pre-commit and commit-msg hooks")
+                .long_help(
+                    "Bypass the pre-commit and commit-msg hooks. Only `stg refresh` \
+                     runs the pre-commit hook.",
+                )
                 .action(clap::ArgAction::SetTrue),
         )
         .arg(
@@ -216,7 +220,10 @@ pub(crate) fn add_args(
                 .long_help(
                     "Set the date the patch was authored.\n\
                      \n\
-                     Use \"now\" to use the current time and date.",
+                     The date may be given in several of the formats accepted by git, \
+                     including RFC 2822, ISO 8601, a raw \"@<seconds> <tz>\" \
+                     timestamp, or a relative date such as \"2.weeks.ago\". Use \
+                     \"now\" to use the current time and date.",
                 )
                 .value_name("date")
                 .num_args(1)
@@ -123,6 +123,27 @@ pub(crate) fn call_editor<P: AsRef<Path>>(
     Ok(buf)
 }
 
+/// Environment variables used to determine the user's editor of choice.
+///
+/// This is suitable for documenting commands that interactively edit patches.
+pub(crate) const EDITOR_ENVVARS: &[(&str, &str)] = &[
+    (
+        "GIT_EDITOR",
+        "Editor to use for interactive edits. Takes precedence over the \
+         `stgit.editor` and `core.editor` configuration variables.",
+    ),
+    (
+        "VISUAL",
+        "Editor to use for interactive edits when neither GIT_EDITOR, \
+         `stgit.editor`, nor `core.editor` is set.",
+    ),
+    (
+        "EDITOR",
+        "Editor to use for interactive edits when VISUAL is also not set. If none \
+         of the above are set, vi is used.",
+    ),
+];
+
 /// Determine user's editor of choice based on config and environment.
 fn get_editor(config: &gix::config::Snapshot) -> Result<OsString> {
     let editor = if let Some(editor) = std::env::var_os("GIT_EDITOR") {
@@ -23,16 +23,20 @@ use anyhow::{anyhow, Result};
 use bstr::{BString, ByteSlice};
 use clap::ArgMatches;
 
-pub(crate) use self::{args::add_args, interactive::call_editor, parse::parse_name_email};
+pub(crate) use self::{
+    args::add_args,
+    interactive::{call_editor, EDITOR_ENVVARS},
+    parse::parse_name_email,
+};
 use self::{
     description::{DiffBuffer, EditablePatchDescription, EditedPatchDescription},
     interactive::edit_interactive,
 };
 use super::PatchName;
 use crate::{
-    ext::{CommitExtended, RepositoryExtended, SignatureExtended},
+    ext::{CommitExtended, CommitOptions, RepositoryExtended, SignatureExtended},
     stack::StackStateAccess,
-    stupid::Stupid,
+    stupid::{GpgSign, Stupid, TrailerOptions},
     wrap::Message,
 };
 
@@ -80,6 +84,9 @@ pub(crate) struct EditBuilder<'a, 'repo> {
     allow_diff_edit: bool,
     allow_implicit_edit: bool,
     allow_template_save: bool,
+    trailer_options: TrailerOptions,
+    extra_trailers: Vec<(String, String)>,
+    gpgsign: Option<GpgSign>,
     overlay: Overlay,
 }
 
@@ -126,6 +133,23 @@ impl<'a, 'repo> EditBuilder<'a, 'repo> {
         self
     }
 
+    /// Set the options used when adding trailers (e.g. `--signoff`) to the message.
+    ///
+    /// By default, git's configuration and defaults determine how trailers are added.
+    pub(crate) fn trailer_options(mut self, options: TrailerOptions) -> Self {
+        self.trailer_options = options;
+        self
+    }
+
+    /// Set whether, and with which key, the new patch commit should be signed with GPG.
+    ///
+    /// By default, i.e. with `None`, the `commit.gpgSign` configuration determines
+    /// whether the commit is signed.
+    pub(crate) fn gpgsign(mut self, gpgsign: Option<GpgSign>) -> Self {
+        self.gpgsign = gpgsign;
+        self
+    }
+
     /// Set the original patch name, if applicable.
     ///
     /// The original patchname will be presented to the user in the patch edit template
@@ -175,6 +199,16 @@ impl<'a, 'repo> EditBuilder<'a, 'repo> {
         self
     }
 
+    /// Set additional trailers to be added to the patch message.
+    ///
+    /// Unlike trailers written into the default message, these trailers are added
+    /// regardless of whether the message comes from the default message or from the
+    /// `--message` or `--file` options.
+    pub(crate) fn extra_trailers(mut self, trailers: Vec<(String, String)>) -> Self {
+        self.extra_trailers = trailers;
+        self
+    }
+
     /// Set the default message for the patch.
     ///
     /// This is useful for `stg import` where the imported patch may have a message from
@@ -228,6 +262,9 @@ impl<'a, 'repo> EditBuilder<'a, 'repo> {
             allow_diff_edit,
             allow_implicit_edit,
             allow_template_save,
+            trailer_options,
+            extra_trailers,
+            gpgsign,
             overlay:
                 Overlay {
                     author: overlay_author,
@@ -350,7 +387,15 @@ impl<'a, 'repo> EditBuilder<'a, 'repo> {
             // N.B. add_trailers needs to operate on utf-8 data. The user providing
             // trailer-altering options (e.g. --review) will force the message to be
             // decoded. In such cases the returned message will wrap a utf-8 String.
-            trailers::add_trailers(repo, message, matches, default_committer, autosign)?
+            trailers::add_trailers(
+                repo,
+                message,
+                matches,
+                default_committer,
+                autosign,
+                &extra_trailers,
+                &trailer_options,
+            )?
         };
 
         let tree_id = overlay_tree_id.unwrap_or_else(|| {
@@ -410,8 +455,7 @@ impl<'a, 'repo> EditBuilder<'a, 'repo> {
             })
         };
 
-        let need_commit_msg_hook =
-            !matches.get_flag("no-verify") && (need_interactive_edit || is_message_modified());
+        let need_commit_msg_hook = need_interactive_edit || is_message_modified();
 
         let instruction = Some(interactive::EDIT_INSTRUCTION);
         let diff_instruction = Some(if allow_diff_edit {
@@ -529,7 +573,7 @@ impl<'a, 'repo> EditBuilder<'a, 'repo> {
 
         let message = if need_commit_msg_hook {
             // TODO: Want to save patch description here too
-            crate::hook::run_commit_msg_hook(repo, message, false)?
+            crate::hook::run_commit_msg_hook(repo, message, false, matches.get_flag("no-verify"))?
         } else {
             message
         };
@@ -565,6 +609,18 @@ impl<'a, 'repo> EditBuilder<'a, 'repo> {
                 && patch_commit_ref.parents().next() == Some(parent_id)
             }) {
             None
+        } else if let Some(gpgsign) = gpgsign {
+            Some(repo.commit_with_options(
+                &author,
+                &committer,
+                &message,
+                tree_id,
+                [parent_id],
+                &CommitOptions {
+                    commit_encoding: config.string("i18n.commitencoding"),
+                    gpgsign,
+                },
+            )?)
         } else {
             Some(repo.commit_ex(&author, &committer, &message, tree_id, [parent_id])?)
         };
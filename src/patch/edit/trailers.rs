@@ -6,21 +6,32 @@ use anyhow::{anyhow, Result};
 use bstr::ByteSlice;
 use clap::ArgMatches;
 
-use crate::{stupid::Stupid, wrap::Message};
+use crate::{
+    stupid::{Stupid, TrailerOptions},
+    wrap::Message,
+};
 
 /// Add trailers to commit message based on user-provided command line options.
 ///
 /// The `matches` provided to this function must be from a [`clap::Command`] that was
 /// setup with [`super::add_args`].
+///
+/// The `extra_trailers` are added ahead of any trailers from the command line options
+/// or `autosign`.
 pub(crate) fn add_trailers<'a, 'b>(
     repo: &gix::Repository,
     message: Message<'a>,
     matches: &ArgMatches,
     signature: impl Into<gix::actor::SignatureRef<'b>>,
     autosign: Option<&str>,
+    extra_trailers: &[(String, String)],
+    options: &TrailerOptions,
 ) -> Result<Message<'a>> {
     let signature = signature.into();
-    let mut trailers: Vec<(usize, &str, &str)> = vec![];
+    let mut trailers: Vec<(usize, &str, &str)> = extra_trailers
+        .iter()
+        .map(|(trailer, value)| (0, trailer.as_str(), value.as_str()))
+        .collect();
 
     for (opt_name, old_by_opt, trailer) in &[
         ("signoff", "sign-by", "Signed-off-by"),
@@ -68,6 +79,7 @@ pub(crate) fn add_trailers<'a, 'b>(
                     (*trailer, *value)
                 }
             }),
+            options,
         )?;
         let message = String::from_utf8(message_bytes)
             .map_err(|_| anyhow!("could not decode message after adding trailers"))?;
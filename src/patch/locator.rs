@@ -48,6 +48,9 @@ pub(crate) enum Error {
         patchnames: String,
     },
 
+    #[error("ambiguous patch name prefix `{prefix}` matches patches {patchnames}")]
+    AmbiguousPrefix { prefix: String, patchnames: String },
+
     #[error("finding ancestor: {0}")]
     Ancestors(String),
 }
@@ -236,6 +239,46 @@ impl PatchLocator {
         Ok(patches[index as usize].clone())
     }
 
+    /// Expand a patch name which is an unambiguous prefix of a known patch's name.
+    ///
+    /// Locators that resolve with [`PatchLocator::resolve_name()`] take precedence and
+    /// are returned unchanged, as are locators that do not name a patch or whose name
+    /// is not a prefix of any known patch's name. It is an error if the name is a
+    /// prefix of more than one known patch's name.
+    pub(crate) fn expand_name_prefix<'repo>(
+        &self,
+        stack: &impl StackStateAccess<'repo>,
+    ) -> Result<Self, Error> {
+        if let PatchId::Name(prefix) = &self.id {
+            if matches!(
+                self.resolve_name(stack),
+                Err(Error::PatchNotKnown { .. } | Error::PatchSimilar { .. })
+            ) {
+                let prefix: &str = prefix.as_ref();
+                let matching_names: Vec<&PatchName> = stack
+                    .all_patches()
+                    .filter(|&pn| AsRef::<str>::as_ref(pn).starts_with(prefix))
+                    .collect();
+                match matching_names.len() {
+                    0 => {}
+                    1 => {
+                        return Ok(Self {
+                            id: PatchId::Name(matching_names[0].clone()),
+                            offsets: self.offsets.clone(),
+                        })
+                    }
+                    _ => {
+                        return Err(Error::AmbiguousPrefix {
+                            prefix: prefix.to_string(),
+                            patchnames: patchnames_string(&matching_names).unwrap(),
+                        })
+                    }
+                }
+            }
+        }
+        Ok(self.clone())
+    }
+
     /// Resolve patchname and commit object based on patch location and offsets.
     ///
     /// Unlike [`PatchLocator::resolve_name()`], the offsets are allowed to land on a
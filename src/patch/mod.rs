@@ -154,6 +154,9 @@ pub(crate) enum RangeConstraint {
     /// stop at the last applied patch.
     AllWithAppliedBoundary,
     /// All visible (non-hidden) patches are allowed in the range.
+    ///
+    /// I.e. both applied and unapplied patches are allowed, but any hidden patch named
+    /// by the range is rejected with an error naming that patch.
     Visible,
     /// All visible (non-hidden) patches are allowed in the range, but open-ended ranges
     /// stop at the last applied patch.
@@ -221,6 +221,41 @@ pub(crate) fn resolve_names<'a, 'repo>(
     Ok(patches)
 }
 
+/// Expand unambiguous patch name prefixes in user-provided patch ranges.
+///
+/// Each patch locator in `ranges` that does not otherwise resolve, but whose name is an
+/// unambiguous prefix of a known patch's name, is replaced with a locator using the
+/// full patch name. See [`PatchLocator::expand_name_prefix()`].
+///
+/// The expanded ranges may subsequently be resolved with [`resolve_names()`].
+///
+/// [`PatchLocator::expand_name_prefix()`]: super::PatchLocator::expand_name_prefix
+pub(crate) fn expand_name_prefixes<'a, 'repo>(
+    stack: &impl StackStateAccess<'repo>,
+    ranges: impl IntoIterator<Item = &'a PatchRange>,
+) -> Result<Vec<PatchRange>, Error> {
+    ranges
+        .into_iter()
+        .map(|range| match range {
+            PatchRange::Single(patch_loc) => {
+                Ok(PatchRange::Single(patch_loc.expand_name_prefix(stack)?))
+            }
+            PatchRange::Range(PatchRangeBounds { begin, end }) => {
+                Ok(PatchRange::Range(PatchRangeBounds {
+                    begin: begin
+                        .as_ref()
+                        .map(|loc| loc.expand_name_prefix(stack))
+                        .transpose()?,
+                    end: end
+                        .as_ref()
+                        .map(|loc| loc.expand_name_prefix(stack))
+                        .transpose()?,
+                }))
+            }
+        })
+        .collect()
+}
+
 /// Resolve user-provided patch ranges into contiguous patch names.
 ///
 /// It is an error if any of the ranges provided in `ranges` are discontiguous.
@@ -189,3 +189,93 @@ fn should_resolve_ambiguity() {
     ));
     assert_eq!(name("patch"), resolve("beef3"));
 }
+
+#[test]
+fn should_resolve_visible_ranges() {
+    let stack = DummyStack::from_series(&[
+        ('+', "a", None),
+        ('>', "b", None),
+        ('-', "c", None),
+        ('-', "d", None),
+        ('!', "h", None),
+    ]);
+
+    let resolve = |specs: &[&str]| {
+        let ranges: Vec<PatchRange> = specs
+            .iter()
+            .map(|s| PatchRange::from_str(s).expect("valid patch range"))
+            .collect();
+        patchrange::resolve_names(&stack, ranges.iter(), RangeConstraint::Visible)
+    };
+
+    assert_eq!(
+        resolve(&["a..c"]).unwrap(),
+        [name("a"), name("b"), name("c")]
+    );
+    assert_eq!(
+        resolve(&["b.."]).unwrap(),
+        [name("b"), name("c"), name("d")]
+    );
+    assert_eq!(resolve(&["d", "a"]).unwrap(), [name("d"), name("a")]);
+
+    for specs in [&["h"][..], &["a", "h"], &["c..h"]] {
+        let err = resolve(specs).unwrap_err();
+        assert_eq!(err.to_string(), "hidden patch `h` is not allowed");
+    }
+}
+
+#[test]
+fn should_expand_name_prefixes() {
+    let stack = DummyStack::from_series(&[
+        ('+', "feature-x", None),
+        ('+', "bugfix-y", None),
+        ('>', "bugfix-z", None),
+        ('-', "fix", None),
+        ('-', "fixup", None),
+    ]);
+
+    let expand = |s| {
+        PatchLocator::from_str(s)
+            .expect("valid patch locator")
+            .expand_name_prefix(&stack)
+    };
+
+    assert_eq!(
+        name("feature-x"),
+        expand("fea").unwrap().resolve_name(&stack).unwrap()
+    );
+    assert_eq!(
+        name("bugfix-z"),
+        expand("bugfix-z").unwrap().resolve_name(&stack).unwrap()
+    );
+    assert_eq!(
+        name("fix"),
+        expand("fix").unwrap().resolve_name(&stack).unwrap()
+    );
+    assert_eq!(
+        name("fixup"),
+        expand("fixu").unwrap().resolve_name(&stack).unwrap()
+    );
+    assert_eq!(
+        name("fix"),
+        expand("fixu~").unwrap().resolve_name(&stack).unwrap()
+    );
+    assert_eq!(
+        expand("nothing").unwrap(),
+        PatchLocator::from_str("nothing").unwrap()
+    );
+    assert!(matches!(
+        expand("bug"),
+        Err(super::super::locator::Error::AmbiguousPrefix { .. })
+    ));
+
+    let ranges = [
+        PatchRange::from_str("fea..bugfix-y").unwrap(),
+        PatchRange::from_str("fixu").unwrap(),
+    ];
+    let ranges = super::super::range::expand_name_prefixes(&stack, &ranges).unwrap();
+    assert_eq!(
+        vec![name("feature-x"), name("bugfix-y"), name("fixup")],
+        super::super::range::resolve_names(&stack, &ranges, RangeConstraint::All).unwrap()
+    );
+}
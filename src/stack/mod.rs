@@ -4,6 +4,7 @@
 mod access;
 mod iter;
 mod serde;
+mod spill;
 #[allow(clippy::module_inception)]
 mod stack;
 mod state;
@@ -11,6 +12,7 @@ mod transaction;
 mod upgrade;
 
 pub(crate) use access::{StackAccess, StackStateAccess};
+pub(crate) use spill::compute_spilled_tree;
 pub(crate) use stack::{state_refname_from_branch_name, InitializationPolicy, Stack};
 pub(crate) use state::{PatchState, StackState};
-pub(crate) use transaction::{Error as TransactionError, StackTransaction};
+pub(crate) use transaction::{Error as TransactionError, PushMergeOptions, StackTransaction};
@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: GPL-2.0-only
+
+//! Compute the trees of patches with some or all of their changes spilled.
+
+use std::ffi::{OsStr, OsString};
+
+use anyhow::Result;
+
+use crate::stupid::Stupid;
+
+/// Compute the tree of a patch with the changes matching `pathspecs` spilled.
+///
+/// The changes from `parent_tree_id` to `patch_tree_id` that match `pathspecs` are
+/// reverted in the returned tree. Without pathspecs, all of the patch's changes are
+/// spilled and thus `parent_tree_id` is returned.
+///
+/// Also returned are any pathspecs that did not match any of the patch's changes.
+pub(crate) fn compute_spilled_tree<SpecIter, SpecArg>(
+    repo: &gix::Repository,
+    patch_tree_id: gix::ObjectId,
+    parent_tree_id: gix::ObjectId,
+    pathspecs: Option<SpecIter>,
+) -> Result<(gix::ObjectId, Vec<OsString>)>
+where
+    SpecIter: IntoIterator<Item = SpecArg>,
+    SpecArg: AsRef<OsStr>,
+{
+    if let Some(pathspecs) = pathspecs {
+        repo.stupid().with_temp_index(|stupid_temp| {
+            stupid_temp.read_tree(patch_tree_id)?;
+            let (_, unmatched) = stupid_temp.apply_pathlimited_treediff_to_index(
+                patch_tree_id,
+                parent_tree_id,
+                true,
+                pathspecs,
+            )?;
+            Ok((stupid_temp.write_tree()?, unmatched))
+        })
+    } else {
+        Ok((parent_tree_id, Vec::new()))
+    }
+}
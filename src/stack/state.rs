@@ -14,6 +14,7 @@ use super::{access::StackStateAccess, iter::AllPatches, serde::RawStackState};
 use crate::{
     ext::{CommitExtended, CommitOptions, RepositoryExtended},
     patch::PatchName,
+    stupid::GpgSign,
     wrap::Message,
 };
 
@@ -227,17 +228,13 @@ impl<'repo> StackState<'repo> {
 
         let commit_opts = CommitOptions {
             commit_encoding: None,
-            gpgsign: config.boolean("stgit.gpgsign").unwrap_or(false),
+            gpgsign: GpgSign::from_bool(config.boolean("stgit.gpgsign").unwrap_or(false)),
         };
 
-        let simplified_parent_id = repo.commit_with_options(
-            &author,
-            &committer,
-            &message,
-            state_tree_id,
-            simplified_parents,
-            &commit_opts,
-        )?;
+        let commit_batch = repo.commit_batch(&author, &committer, &commit_opts)?;
+
+        let simplified_parent_id =
+            commit_batch.commit(&message, state_tree_id, simplified_parents)?;
 
         let mut parent_set = indexmap::IndexSet::new();
         parent_set.insert(self.head.id);
@@ -262,27 +259,17 @@ impl<'repo> StackState<'repo> {
         while parent_oids.len() > MAX_PARENTS {
             let parent_group_oids =
                 parent_oids.drain(parent_oids.len() - MAX_PARENTS..parent_oids.len());
-            let group_oid = repo.commit_with_options(
-                &author,
-                &committer,
+            let group_oid = commit_batch.commit(
                 &Message::from("parent grouping"),
                 state_tree_id,
                 parent_group_oids,
-                &commit_opts,
             )?;
             parent_oids.push(group_oid);
         }
 
         parent_oids.insert(0, simplified_parent_id);
 
-        let commit_oid = repo.commit_with_options(
-            &author,
-            &committer,
-            &message,
-            state_tree_id,
-            parent_oids,
-            &commit_opts,
-        )?;
+        let commit_oid = commit_batch.commit(&message, state_tree_id, parent_oids)?;
 
         if let Some(refname) = update_ref {
             repo.reference(
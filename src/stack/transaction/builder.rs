@@ -9,12 +9,16 @@ use super::{
     ui::TransactionUserInterface,
     ExecuteContext, StackTransaction,
 };
-use crate::stack::{Stack, StackAccess, StackStateAccess};
+use crate::{
+    stack::{Stack, StackAccess, StackStateAccess},
+    stupid::ConflictStyle,
+};
 
 /// Builder used to setup a stack transaction.
 pub(crate) struct TransactionBuilder<'repo> {
     stack: Stack<'repo>,
     output: Option<termcolor::StandardStream>,
+    quiet: bool,
     options: TransactionOptions,
 }
 
@@ -26,6 +30,7 @@ impl<'repo> TransactionBuilder<'repo> {
         Self {
             stack,
             output: None,
+            quiet: false,
             options: TransactionOptions::default(),
         }
     }
@@ -100,6 +105,14 @@ impl<'repo> TransactionBuilder<'repo> {
         self
     }
 
+    /// Reduce the transaction's output such that only conflicts and rollbacks are
+    /// reported. By default, all stack changes are reported to the output stream.
+    #[must_use]
+    pub(crate) fn quiet(mut self, quiet: bool) -> Self {
+        self.quiet = quiet;
+        self
+    }
+
     /// Determines whether the branch and stack metadata refs should be updated when the
     /// transaction executes successfully. This is the default. Disabling this is only
     /// useful in very special circumstances (e.g. for `stg uncommit`).
@@ -117,6 +130,14 @@ impl<'repo> TransactionBuilder<'repo> {
         self
     }
 
+    /// Set the style of conflict markers written when a pushed patch has merge
+    /// conflicts. By default, the `merge.conflictStyle` config is honored.
+    #[must_use]
+    pub(crate) fn conflict_style(mut self, conflict_style: Option<ConflictStyle>) -> Self {
+        self.options.conflict_style = conflict_style;
+        self
+    }
+
     /// Perform stack transaction operations.
     ///
     /// The closure provided to this method may call various methods on the provided
@@ -136,11 +157,14 @@ impl<'repo> TransactionBuilder<'repo> {
         let Self {
             stack,
             output,
+            quiet,
             options,
         } = self;
 
-        let ui =
-            TransactionUserInterface::new(output.expect("with_output_stream() must be called"));
+        let ui = TransactionUserInterface::new(
+            output.expect("with_output_stream() must be called"),
+            quiet,
+        );
 
         let current_tree_id = stack
             .get_branch_head()
@@ -30,12 +30,12 @@ mod builder;
 mod options;
 mod ui;
 
-use std::{collections::BTreeMap, rc::Rc};
+use std::{collections::BTreeMap, path::PathBuf, rc::Rc};
 
 use anyhow::{anyhow, Result};
 use indexmap::IndexSet;
 
-pub(crate) use self::builder::TransactionBuilder;
+pub(crate) use self::{builder::TransactionBuilder, options::PushMergeOptions};
 use self::{
     options::{ConflictMode, TransactionOptions},
     ui::TransactionUserInterface,
@@ -45,19 +45,44 @@ use crate::{
     ext::{CommitExtended, RepositoryExtended},
     patch::PatchName,
     stack::{PatchState, Stack, StackStateAccess},
-    stupid::{Stupid, StupidContext},
+    stupid::{CheckoutConflicts, Stupid, StupidContext},
     wrap::Branch,
 };
 
 #[derive(thiserror::Error, Debug)]
 pub(crate) enum Error {
-    #[error("{0}")]
-    CheckoutConflicts(String),
+    /// Checking-out the transaction's final tree failed.
+    ///
+    /// The paths that would have been overwritten are reported when git's error output
+    /// could be interpreted, followed by the raw error message from git.
+    #[error("{}", checkout_conflicts_message(.msg, .paths))]
+    CheckoutConflicts { msg: String, paths: Vec<PathBuf> },
 
     #[error("{msg}")]
     TransactionHalt { msg: String, conflicts: bool },
 }
 
+fn checkout_conflicts_message(msg: &str, paths: &[PathBuf]) -> String {
+    if paths.is_empty() {
+        msg.to_string()
+    } else {
+        format!("{}:\n{msg}", overwrite_message("checkout", paths))
+    }
+}
+
+/// Describe the local changes to `paths` that would be overwritten by `action`.
+fn overwrite_message(action: &str, paths: &[PathBuf]) -> String {
+    let paths: Vec<_> = paths
+        .iter()
+        .map(|path| format!("`{}`", path.display()))
+        .collect();
+    format!(
+        "{action} would overwrite local changes to {} \
+         (refresh, stash, or discard them first)",
+        paths.join(", ")
+    )
+}
+
 /// Stack transaction state.
 pub(crate) struct StackTransaction<'repo> {
     stack: Stack<'repo>,
@@ -344,7 +369,13 @@ fn checkout(
         stupid.update_index_refresh()?;
         stupid
             .read_tree_checkout(current_tree_id, tree_id)
-            .map_err(|e| Error::CheckoutConflicts(format!("{e:#}")))?;
+            .map_err(|e| Error::CheckoutConflicts {
+                paths: e
+                    .downcast_ref::<CheckoutConflicts>()
+                    .map(|conflicts| conflicts.paths.clone())
+                    .unwrap_or_default(),
+                msg: format!("{e:#}"),
+            })?;
     }
 
     Ok(())
@@ -966,6 +997,43 @@ impl<'repo> StackTransaction<'repo> {
     /// tree. Patches that are determined to have already been merged will still be
     /// pushed successfully, but their diff will be empty.
     pub(crate) fn push_patches<P>(&mut self, patchnames: &[P], check_merged: bool) -> Result<()>
+    where
+        P: AsRef<PatchName>,
+    {
+        self.push_patches_impl(
+            patchnames,
+            check_merged,
+            false,
+            &PushMergeOptions::default(),
+        )
+    }
+
+    /// Push unapplied patches to become applied, using the given merge options and
+    /// reporting progress for each patch.
+    ///
+    /// Behaves as [`StackTransaction::push_patches()`], but any merge needed to push
+    /// one of these patches uses `merge_options`; other pushes in the transaction are
+    /// unaffected. When more than one patch is to be pushed, a progress line is output
+    /// before each patch is pushed. This is useful for potentially long-running pushes
+    /// of many patches.
+    pub(crate) fn push_patches_with_merge_options<P>(
+        &mut self,
+        patchnames: &[P],
+        merge_options: &PushMergeOptions,
+    ) -> Result<()>
+    where
+        P: AsRef<PatchName>,
+    {
+        self.push_patches_impl(patchnames, false, true, merge_options)
+    }
+
+    fn push_patches_impl<P>(
+        &mut self,
+        patchnames: &[P],
+        check_merged: bool,
+        show_progress: bool,
+        merge_options: &PushMergeOptions,
+    ) -> Result<()>
     where
         P: AsRef<PatchName>,
     {
@@ -985,12 +1053,17 @@ impl<'repo> StackTransaction<'repo> {
                 let already_merged = merged
                     .as_ref()
                     .is_some_and(|merged| merged.contains(&patchname));
+                if show_progress && patchnames.len() > 1 {
+                    self.ui
+                        .print_push_progress(patchname, i + 1, patchnames.len())?;
+                }
                 self.push_patch(
                     patchname,
                     already_merged,
                     is_last,
                     stupid_temp,
                     &mut temp_index_tree_id,
+                    merge_options,
                 )?;
             }
 
@@ -1005,6 +1078,7 @@ impl<'repo> StackTransaction<'repo> {
         is_last: bool,
         stupid_temp: &StupidContext,
         temp_index_tree_id: &mut Option<gix::ObjectId>,
+        merge_options: &PushMergeOptions,
     ) -> Result<()> {
         let repo = self.stack.repo;
         let config = repo.config_snapshot();
@@ -1069,6 +1143,20 @@ impl<'repo> StackTransaction<'repo> {
                 }
                 .into());
             } else {
+                // Detect local changes that would be overwritten before touching the
+                // index and worktree.
+                let would_overwrite =
+                    stupid.read_tree_checkout_dry_run(self.current_tree_id, ours)?;
+                if !would_overwrite.is_empty() {
+                    return Err(Error::TransactionHalt {
+                        msg: overwrite_message(
+                            &format!("pushing patch `{patchname}`"),
+                            &would_overwrite,
+                        ),
+                        conflicts: false,
+                    }
+                    .into());
+                }
                 if stupid
                     .read_tree_checkout(self.current_tree_id, ours)
                     .is_err()
@@ -1082,7 +1170,14 @@ impl<'repo> StackTransaction<'repo> {
                 self.current_tree_id = ours;
 
                 let use_mergetool = config.boolean("stgit.autoimerge").unwrap_or(false);
-                match stupid.merge_recursive_or_mergetool(base, ours, theirs, use_mergetool) {
+                match stupid.merge_recursive_or_mergetool(
+                    base,
+                    ours,
+                    theirs,
+                    &merge_options.strategy_options,
+                    merge_options.conflict_style.or(self.options.conflict_style),
+                    use_mergetool,
+                ) {
                     Ok(true) => {
                         // Success, no conflicts
                         let tree_id = stupid.write_tree().map_err(|_| Error::TransactionHalt {
@@ -1,5 +1,7 @@
 // SPDX-License-Identifier: GPL-2.0-only
 
+use crate::stupid::ConflictStyle;
+
 /// Options for fine-tuning stack transaction behaviors.
 pub(super) struct TransactionOptions {
     pub(super) conflict_mode: ConflictMode,
@@ -9,6 +11,7 @@ pub(super) struct TransactionOptions {
     pub(super) set_head: bool,
     pub(super) allow_bad_head: bool,
     pub(super) committer_date_is_author_date: bool,
+    pub(super) conflict_style: Option<ConflictStyle>,
 }
 
 impl Default for TransactionOptions {
@@ -21,16 +24,30 @@ impl Default for TransactionOptions {
             set_head: true,
             allow_bad_head: false,
             committer_date_is_author_date: false,
+            conflict_style: None,
         }
     }
 }
 
+/// Options for merging patches that do not apply cleanly when pushed with
+/// [`super::StackTransaction::push_patches_with_merge_options()`].
+#[derive(Default)]
+pub(crate) struct PushMergeOptions {
+    /// Merge strategy options (e.g. `ours` or `theirs`) passed to `git merge-recursive`.
+    pub(crate) strategy_options: Vec<String>,
+
+    /// Style of conflict markers, overriding the transaction's conflict style.
+    pub(crate) conflict_style: Option<ConflictStyle>,
+}
+
 /// Policies for whether a transaction may execute when conflicts emerge from the
 /// transactions operations.
+#[derive(Default)]
 pub(crate) enum ConflictMode {
     /// Transaction execution will fail if there are conflicts recorded in the index.
     ///
     /// This is the default.
+    #[default]
     Disallow,
 
     /// Transaction execution will succeed even if there are outstanding conflicts.
@@ -40,9 +57,3 @@ pub(crate) enum ConflictMode {
     /// is unchanged by the transaction.
     AllowIfSameTop,
 }
-
-impl Default for ConflictMode {
-    fn default() -> Self {
-        Self::Disallow
-    }
-}
@@ -9,15 +9,19 @@ use super::PushStatus;
 use crate::patch::PatchName;
 
 /// User output for stack transactions.
+///
+/// In quiet mode, only conflicting pushes and rollbacks are reported.
 pub(super) struct TransactionUserInterface {
     output: RefCell<termcolor::StandardStream>,
+    quiet: bool,
     printed_top: bool,
 }
 
 impl TransactionUserInterface {
-    pub(super) fn new(output: termcolor::StandardStream) -> TransactionUserInterface {
+    pub(super) fn new(output: termcolor::StandardStream, quiet: bool) -> TransactionUserInterface {
         TransactionUserInterface {
             output: RefCell::new(output),
+            quiet,
             printed_top: false,
         }
     }
@@ -27,6 +31,9 @@ impl TransactionUserInterface {
     }
 
     pub(super) fn print_merged(&self, merged_patches: &[&PatchName]) -> Result<()> {
+        if self.quiet {
+            return Ok(());
+        }
         let mut output = self.output.borrow_mut();
         write!(output, "Found ")?;
         let mut color_spec = termcolor::ColorSpec::new();
@@ -43,6 +50,9 @@ impl TransactionUserInterface {
         old_patchname: &PatchName,
         new_patchname: &PatchName,
     ) -> Result<()> {
+        if self.quiet {
+            return Ok(());
+        }
         let mut output = self.output.borrow_mut();
         let mut color_spec = termcolor::ColorSpec::new();
         output.set_color(color_spec.set_dimmed(true))?;
@@ -56,6 +66,9 @@ impl TransactionUserInterface {
     }
 
     pub(super) fn print_committed(&self, committed: &[PatchName]) -> Result<()> {
+        if self.quiet {
+            return Ok(());
+        }
         let mut output = self.output.borrow_mut();
         let mut color_spec = termcolor::ColorSpec::new();
         output.set_color(color_spec.set_fg(Some(termcolor::Color::Yellow)))?;
@@ -76,6 +89,9 @@ impl TransactionUserInterface {
     }
 
     pub(super) fn print_uncommitted(&mut self, uncommitted: &[PatchName]) -> Result<()> {
+        if self.quiet {
+            return Ok(());
+        }
         if !uncommitted.is_empty() {
             let mut output = self.output.borrow_mut();
             let mut color_spec = termcolor::ColorSpec::new();
@@ -94,6 +110,9 @@ impl TransactionUserInterface {
     }
 
     pub(super) fn print_deleted(&self, deleted: &[PatchName]) -> Result<()> {
+        if self.quiet {
+            return Ok(());
+        }
         if !deleted.is_empty() {
             let mut output = self.output.borrow_mut();
             let mut color_spec = termcolor::ColorSpec::new();
@@ -116,6 +135,9 @@ impl TransactionUserInterface {
     }
 
     pub(super) fn print_hidden(&self, hidden: &[PatchName]) -> Result<()> {
+        if self.quiet {
+            return Ok(());
+        }
         let mut output = self.output.borrow_mut();
         let mut color_spec = termcolor::ColorSpec::new();
         for patchname in hidden {
@@ -131,6 +153,9 @@ impl TransactionUserInterface {
     }
 
     pub(super) fn print_unhidden(&self, unhidden: &[PatchName]) -> Result<()> {
+        if self.quiet {
+            return Ok(());
+        }
         let mut output = self.output.borrow_mut();
         let mut color_spec = termcolor::ColorSpec::new();
         for patchname in unhidden {
@@ -146,6 +171,9 @@ impl TransactionUserInterface {
     }
 
     pub(super) fn print_popped(&self, popped: &[PatchName]) -> Result<()> {
+        if self.quiet {
+            return Ok(());
+        }
         if !popped.is_empty() {
             let mut output = self.output.borrow_mut();
             let mut color_spec = termcolor::ColorSpec::new();
@@ -173,6 +201,9 @@ impl TransactionUserInterface {
         status: PushStatus,
         is_last: bool,
     ) -> Result<()> {
+        if self.quiet && !matches!(status, PushStatus::Conflict) {
+            return Ok(());
+        }
         let mut output = self.output.borrow_mut();
         let sigil = if is_last { '>' } else { '+' };
         let mut color_spec = termcolor::ColorSpec::new();
@@ -214,7 +245,31 @@ impl TransactionUserInterface {
         Ok(())
     }
 
+    pub(super) fn print_push_progress(
+        &self,
+        patchname: &PatchName,
+        index: usize,
+        total: usize,
+    ) -> Result<()> {
+        if self.quiet {
+            return Ok(());
+        }
+        let mut output = self.output.borrow_mut();
+        let mut color_spec = termcolor::ColorSpec::new();
+        write!(output, "pushing ")?;
+        output.set_color(color_spec.set_intense(true))?;
+        write!(output, "{patchname}")?;
+        color_spec.clear();
+        output.set_color(color_spec.set_dimmed(true))?;
+        writeln!(output, " [{index}/{total}]")?;
+        output.reset()?;
+        Ok(())
+    }
+
     pub(super) fn print_top(&self, patchname: &PatchName) -> Result<()> {
+        if self.quiet {
+            return Ok(());
+        }
         let mut output = self.output.borrow_mut();
         let mut color_spec = termcolor::ColorSpec::new();
         output.set_color(color_spec.set_fg(Some(termcolor::Color::Blue)))?;
@@ -244,6 +299,9 @@ impl TransactionUserInterface {
     }
 
     pub(super) fn print_updated(&self, patchname: &PatchName, applied: &[PatchName]) -> Result<()> {
+        if self.quiet {
+            return Ok(());
+        }
         let mut output = self.output.borrow_mut();
         let (is_applied, is_top) = if let Some(pos) = applied.iter().position(|pn| pn == patchname)
         {
@@ -3,8 +3,9 @@
 //! Traits to extend [`std::process::Command`] for running `git`.
 
 use std::{
-    io::Write,
+    io::{Read, Write},
     process::{Child, Command, ExitStatus, Output, Stdio},
+    time::Duration,
 };
 
 use anyhow::{anyhow, Context, Result};
@@ -12,6 +13,12 @@ use bstr::ByteSlice;
 
 const GIT_EXEC_FAIL: &str = "could not execute `git`";
 
+/// Number of times a command is retried when the index is locked by another process.
+const INDEX_LOCK_RETRIES: u32 = 5;
+
+/// Delay before the first retry. The delay doubles for each subsequent retry.
+const INDEX_LOCK_INITIAL_BACKOFF: Duration = Duration::from_millis(50);
+
 pub(super) trait StupidCommand {
     /// Spawn command with git error context.
     ///
@@ -103,6 +110,72 @@ pub(super) fn git_command_error(command: &str, stderr: &[u8]) -> anyhow::Error {
     anyhow!(err_str.to_string()).context(format!("`git {command}`"))
 }
 
+/// Run a git command that modifies an index, retrying while the index is locked.
+///
+/// Another git process holding the index's `.lock` file causes the command to fail
+/// immediately. Such failures are retried a bounded number of times, with exponential
+/// backoff, before the last output is returned to the caller for error handling.
+///
+/// Lock contention is recognized from git's stderr, so the command must be run with
+/// `LC_ALL=C` such that git's messages are not localized.
+pub(super) fn retry_if_index_locked(mut run: impl FnMut() -> Result<Output>) -> Result<Output> {
+    let mut backoff = INDEX_LOCK_INITIAL_BACKOFF;
+    for _ in 0..INDEX_LOCK_RETRIES {
+        let output = run()?;
+        if output.status.success() || !is_index_lock_contention(&output.stderr) {
+            return Ok(output);
+        }
+        std::thread::sleep(backoff);
+        backoff *= 2;
+    }
+    run()
+}
+
+/// Determine whether git's stderr indicates that it could not take a lock file.
+pub(super) fn is_index_lock_contention(stderr: &[u8]) -> bool {
+    stderr.lines().any(|line| {
+        line.contains_str("Unable to create '") && line.contains_str(".lock': File exists")
+    })
+}
+
+/// Run two commands with the stdout of `upstream` piped into the stdin of `downstream`.
+///
+/// The upstream command's stderr is drained on a separate thread while the downstream
+/// command runs. Otherwise, an upstream command producing enough stderr to fill the
+/// pipe buffer would block, leaving the downstream command waiting indefinitely for
+/// the end of its input.
+///
+/// The returned upstream [`Output`] has an empty stdout.
+pub(super) fn pipe_git(
+    upstream: &mut Command,
+    downstream: &mut Command,
+) -> Result<(Output, Output)> {
+    let mut upstream_child = upstream
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .spawn_git()?;
+    let upstream_stdout = upstream_child.stdout.take().unwrap();
+    let mut upstream_stderr = upstream_child.stderr.take().unwrap();
+    std::thread::scope(|scope| {
+        let stderr_handle = scope.spawn(move || -> std::io::Result<Vec<u8>> {
+            let mut stderr = Vec::new();
+            upstream_stderr.read_to_end(&mut stderr)?;
+            Ok(stderr)
+        });
+        let downstream_output = downstream.stdin(upstream_stdout).output_git();
+        let upstream_status = upstream_child.wait();
+        let upstream_stderr = stderr_handle
+            .join()
+            .map_err(|_| anyhow!("panic while reading stderr"))??;
+        let upstream_output = Output {
+            status: upstream_status?,
+            stdout: Vec::new(),
+            stderr: upstream_stderr,
+        };
+        Ok((upstream_output, downstream_output?))
+    })
+}
+
 pub(super) trait StupidExitStatus {
     /// Test whether command exit was due to a particular signal.
     fn is_signal(&self, signum: i32) -> bool;
@@ -120,3 +193,85 @@ impl StupidExitStatus for ExitStatus {
         false
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_lock_contention() {
+        assert!(is_index_lock_contention(
+            b"fatal: Unable to create '/repo/.git/index.lock': File exists.\n\n\
+              Another git process seems to be running in this repository.\n"
+        ));
+        assert!(!is_index_lock_contention(
+            b"error: patch failed: foo.txt:1\nerror: foo.txt: patch does not apply\n"
+        ));
+        assert!(!is_index_lock_contention(b""));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn retry_until_index_unlocked() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let mut attempts = 0;
+        let output = retry_if_index_locked(|| {
+            attempts += 1;
+            Ok(if attempts < 3 {
+                Output {
+                    status: ExitStatus::from_raw(128 << 8),
+                    stdout: Vec::new(),
+                    stderr: b"fatal: Unable to create '/repo/.git/index.lock': File exists.\n"
+                        .to_vec(),
+                }
+            } else {
+                Output {
+                    status: ExitStatus::from_raw(0),
+                    stdout: Vec::new(),
+                    stderr: Vec::new(),
+                }
+            })
+        })
+        .unwrap();
+        assert!(output.status.success());
+        assert_eq!(attempts, 3);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn no_retry_for_other_failures() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let mut attempts = 0;
+        let output = retry_if_index_locked(|| {
+            attempts += 1;
+            Ok(Output {
+                status: ExitStatus::from_raw(1 << 8),
+                stdout: Vec::new(),
+                stderr: b"error: patch does not apply\n".to_vec(),
+            })
+        })
+        .unwrap();
+        assert!(!output.status.success());
+        assert_eq!(attempts, 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn pipe_with_large_stderr() {
+        // Both commands write more to stderr than fits in a pipe buffer before the
+        // upstream command writes its stdout.
+        let noisy = "head -c 262144 /dev/zero | tr '\\0' x >&2";
+        let (upstream_output, downstream_output) = pipe_git(
+            Command::new("sh").args(["-c", &format!("{noisy}; echo hello")]),
+            Command::new("sh").args(["-c", &format!("{noisy}; cat")]),
+        )
+        .unwrap();
+        assert!(upstream_output.status.success());
+        assert_eq!(upstream_output.stderr.len(), 262144);
+        assert!(downstream_output.status.success());
+        assert_eq!(downstream_output.stderr.len(), 262144);
+        assert_eq!(downstream_output.stdout, b"hello\n");
+    }
+}
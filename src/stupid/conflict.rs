@@ -0,0 +1,260 @@
+// SPDX-License-Identifier: GPL-2.0-only
+
+//! Support for parsing merge conflict reports from `git`.
+
+use std::{path::PathBuf, str::FromStr};
+
+use anyhow::{anyhow, Result};
+use bstr::ByteSlice;
+
+/// Style of conflict markers written to files with merge conflicts.
+///
+/// Corresponds to the values of git's `merge.conflictStyle` config variable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ConflictStyle {
+    /// Conflict markers with "ours" and "theirs" sections.
+    Merge,
+
+    /// Conflict markers additionally including the base, i.e. ancestor, section.
+    Diff3,
+
+    /// Like [`ConflictStyle::Diff3`], but with lines common to both sides moved out
+    /// of the conflict region. Requires git 2.35 or newer.
+    Zdiff3,
+}
+
+impl ConflictStyle {
+    /// Get the `merge.conflictStyle` config value for this style.
+    pub(crate) fn config_value(self) -> &'static str {
+        match self {
+            ConflictStyle::Merge => "merge",
+            ConflictStyle::Diff3 => "diff3",
+            ConflictStyle::Zdiff3 => "zdiff3",
+        }
+    }
+}
+
+impl FromStr for ConflictStyle {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "merge" => Ok(ConflictStyle::Merge),
+            "diff3" => Ok(ConflictStyle::Diff3),
+            "zdiff3" => Ok(ConflictStyle::Zdiff3),
+            _ => Err(anyhow!("invalid conflict style `{s}`")),
+        }
+    }
+}
+
+/// Extract the paths involved in conflicts from `git merge-recursive` output.
+///
+/// Only lines of the form `CONFLICT (<kind>): <description>` are considered. The
+/// path(s) are extracted from the description based on the various conflict kinds
+/// reported by git, e.g. content, add/add, modify/delete, rename/delete, and
+/// rename/rename conflicts. Lines that cannot be interpreted are ignored.
+///
+/// Each path appears at most once in the returned list, in order of first appearance.
+pub(crate) fn parse_conflict_paths(output: &[u8]) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = Vec::new();
+    for line in output.lines() {
+        let Some(rest) = line.strip_prefix(b"CONFLICT (") else {
+            continue;
+        };
+        let Some((kind, description)) = rest.split_once_str("): ") else {
+            continue;
+        };
+        for path in conflict_description_paths(kind, description) {
+            let path = path.to_path_lossy().into_owned();
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+    }
+    paths
+}
+
+fn conflict_description_paths<'a>(kind: &[u8], description: &'a [u8]) -> Vec<&'a [u8]> {
+    if let Some((_, path)) = description.split_once_str("Merge conflict in ") {
+        vec![path]
+    } else if kind == b"modify/delete" || kind == b"delete/modify" {
+        // <path> deleted in <branch> and modified in <branch>. ...
+        description
+            .split_once_str(" deleted in ")
+            .map(|(path, _)| vec![path])
+            .unwrap_or_default()
+    } else if kind.starts_with(b"rename/") {
+        // <old> renamed to <new> in <branch>, but deleted in <branch>.
+        // <old> renamed to <new1> in <branch> and to <new2> in <branch>.
+        let mut paths = Vec::new();
+        let mut remainder = description;
+        while let Some(pos) = remainder.find(" to ") {
+            remainder = &remainder[pos + 4..];
+            if let Some((path, rest)) = remainder.split_once_str(" in ") {
+                paths.push(path);
+                remainder = rest;
+            } else {
+                break;
+            }
+        }
+        paths
+    } else if kind == b"file/directory" || kind == b"directory/file" {
+        // directory in the way of <path> from <branch>; moving it to ...
+        description
+            .split_once_str(" in the way of ")
+            .and_then(|(_, rest)| rest.rsplit_once_str(" from "))
+            .map(|(path, _)| vec![path])
+            .unwrap_or_default()
+    } else {
+        Vec::new()
+    }
+}
+
+/// A `git read-tree -m -u` checkout that failed because it would overwrite paths.
+///
+/// The raw stderr from git is retained, and used for display, as a fallback for when
+/// the paths alone do not adequately describe the failure.
+#[derive(Debug)]
+pub(crate) struct CheckoutConflicts {
+    /// Paths that would be overwritten by the checkout.
+    pub(crate) paths: Vec<PathBuf>,
+    /// Raw stderr output from git.
+    pub(crate) stderr: String,
+}
+
+impl std::fmt::Display for CheckoutConflicts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.stderr)
+    }
+}
+
+impl std::error::Error for CheckoutConflicts {}
+
+/// Extract the paths that would be overwritten from `git read-tree -m -u` errors.
+///
+/// Both single-path forms, e.g. `Entry '<path>' not uptodate. Cannot merge.` and
+/// `Untracked working tree file '<path>' would be overwritten by merge.`, as well as
+/// the multi-path form where each path is listed on its own tab-indented line
+/// following a `...would be overwritten by merge:` header are recognized.
+///
+/// Each path appears at most once in the returned list, in order of first appearance.
+pub(crate) fn parse_checkout_conflict_paths(stderr: &[u8]) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = Vec::new();
+    let mut in_path_list = false;
+    for line in stderr.lines() {
+        let path = if in_path_list {
+            if let Some(path) = line.strip_prefix(b"\t") {
+                Some(path)
+            } else {
+                in_path_list = false;
+                None
+            }
+        } else {
+            let message = line.strip_prefix(b"error: ").unwrap_or(line);
+            if message.ends_with(b"would be overwritten by merge:") {
+                in_path_list = true;
+                None
+            } else if message.starts_with(b"Entry '")
+                || message.starts_with(b"Untracked working tree file '")
+            {
+                message
+                    .split_once_str("'")
+                    .and_then(|(_, rest)| rest.rsplit_once_str("' "))
+                    .map(|(path, _)| path)
+            } else {
+                None
+            }
+        };
+        if let Some(path) = path {
+            let path = path.to_path_lossy().into_owned();
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+    }
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    #[test]
+    fn content_conflicts() {
+        let output = b"Auto-merging a.txt\n\
+                       CONFLICT (content): Merge conflict in a.txt\n\
+                       Auto-merging dir/b c.txt\n\
+                       CONFLICT (add/add): Merge conflict in dir/b c.txt\n";
+        let paths = parse_conflict_paths(output);
+        assert_eq!(paths, [Path::new("a.txt"), Path::new("dir/b c.txt")]);
+    }
+
+    #[test]
+    fn modify_delete_conflicts() {
+        let output = b"CONFLICT (modify/delete): g.txt deleted in 3be22be and modified \
+                       in a85d1b6. Version a85d1b6 of g.txt left in tree.\n";
+        assert_eq!(parse_conflict_paths(output), [Path::new("g.txt")]);
+    }
+
+    #[test]
+    fn rename_conflicts() {
+        let output = b"CONFLICT (rename/delete): old.txt renamed to new.txt in HEAD, \
+                       but deleted in patched.\n\
+                       CONFLICT (rename/rename): x.txt renamed to y.txt in current \
+                       and to z.txt in patched.\n";
+        assert_eq!(
+            parse_conflict_paths(output),
+            [Path::new("new.txt"), Path::new("y.txt"), Path::new("z.txt")]
+        );
+    }
+
+    #[test]
+    fn file_directory_conflicts() {
+        let output = b"CONFLICT (file/directory): directory in the way of foo from \
+                       patched; moving it to foo~patched instead.\n";
+        assert_eq!(parse_conflict_paths(output), [Path::new("foo")]);
+    }
+
+    #[test]
+    fn duplicates_and_noise() {
+        let output = b"Auto-merging a.txt\n\
+                       CONFLICT (content): Merge conflict in a.txt\n\
+                       CONFLICT (content): Merge conflict in a.txt\n\
+                       CONFLICT (unknown): something unexpected\n\
+                       Not a CONFLICT (content): Merge conflict in b.txt\n";
+        assert_eq!(parse_conflict_paths(output), [Path::new("a.txt")]);
+    }
+
+    #[test]
+    fn checkout_single_path_errors() {
+        let stderr = b"error: Entry 'f.txt' not uptodate. Cannot merge.\n\
+                       error: Entry 'dir/it's.txt' would be overwritten by merge. \
+                       Cannot merge.\n\
+                       error: Untracked working tree file 'a b.txt' would be \
+                       overwritten by merge.\n";
+        assert_eq!(
+            parse_checkout_conflict_paths(stderr),
+            [
+                Path::new("f.txt"),
+                Path::new("dir/it's.txt"),
+                Path::new("a b.txt")
+            ]
+        );
+    }
+
+    #[test]
+    fn checkout_path_list_errors() {
+        let stderr = b"error: The following untracked working tree files would be \
+                       overwritten by merge:\n\
+                       \ta.txt\n\
+                       \tb.txt\n\
+                       Please move or remove them before you merge.\n\
+                       Aborting\n";
+        assert_eq!(
+            parse_checkout_conflict_paths(stderr),
+            [Path::new("a.txt"), Path::new("b.txt")]
+        );
+    }
+}
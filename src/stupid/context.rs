@@ -5,24 +5,34 @@
 //! It is assumed/required that `git` is in `PATH`.
 
 use std::{
-    cell::RefCell,
     ffi::{OsStr, OsString},
     io::Write,
-    path::Path,
+    path::{Path, PathBuf},
     process::{Command, Stdio},
+    sync::OnceLock,
 };
 
 use anyhow::{anyhow, Context, Result};
 use bstr::{BStr, BString, ByteSlice, ByteVec};
+use is_terminal::IsTerminal;
 
 use super::{
-    command::{git_command_error, StupidCommand, StupidExitStatus, StupidOutput},
-    diff::DiffFiles,
+    command::{
+        git_command_error, is_index_lock_contention, pipe_git, retry_if_index_locked,
+        StupidCommand, StupidExitStatus, StupidOutput,
+    },
+    conflict::{
+        parse_checkout_conflict_paths, parse_conflict_paths, CheckoutConflicts, ConflictStyle,
+    },
+    diff::{parse_name_status, DiffFileStatus, DiffFiles, ShowMode},
+    gpgsign::GpgSign,
     oid::parse_oid,
     status::{StatusOptions, Statuses},
     tempindex::TempIndex,
+    trailers::TrailerOptions,
     version::StupidVersion,
 };
+use crate::{ext::TimeExtended, pager::Pager};
 
 /// Context for running stupid commands.
 #[derive(Clone, Debug, Default)]
@@ -30,7 +40,34 @@ pub(crate) struct StupidContext<'repo, 'index> {
     pub(super) git_dir: Option<&'repo Path>,
     pub(super) work_dir: Option<&'repo Path>,
     pub(super) index_filename: Option<&'index Path>,
-    pub(super) git_version: RefCell<Option<StupidVersion>>,
+    /// When true, git commands are prevented from spawning an editor.
+    pub(super) no_editor: bool,
+}
+
+/// Output of `git version`, interrogated at most once per process.
+static GIT_VERSION: OnceLock<String> = OnceLock::new();
+
+/// Whether StGit is running without a terminal, determined at most once per process.
+static NON_INTERACTIVE: OnceLock<bool> = OnceLock::new();
+
+/// Determine whether StGit is running non-interactively.
+///
+/// StGit is considered to be non-interactive when neither stdin nor stderr is a
+/// terminal, in which case nobody is available to answer a prompt.
+fn is_non_interactive() -> bool {
+    *NON_INTERACTIVE
+        .get_or_init(|| !std::io::stdin().is_terminal() && !std::io::stderr().is_terminal())
+}
+
+/// Prevent git from prompting on the terminal when running non-interactively.
+///
+/// Git prompts for, e.g., credentials directly on the terminal, which would hang
+/// indefinitely when StGit is not run interactively. A `GIT_TERMINAL_PROMPT` set by
+/// the user is respected.
+fn setup_terminal_prompt_env(command: &mut Command) {
+    if std::env::var_os("GIT_TERMINAL_PROMPT").is_none() && is_non_interactive() {
+        command.env("GIT_TERMINAL_PROMPT", "0");
+    }
 }
 
 impl StupidContext<'_, '_> {
@@ -49,11 +86,20 @@ impl StupidContext<'_, '_> {
             git_dir: self.git_dir,
             work_dir: self.work_dir,
             index_filename: Some(temp_index.filename()),
-            git_version: RefCell::new(None),
+            no_editor: self.no_editor,
         };
 
         f(&stupid_temp)
     }
+
+    /// Set whether git commands run with this context may spawn an editor.
+    ///
+    /// When `use_editor` is false, `GIT_EDITOR` is set to `:` for each git command,
+    /// as is done for hooks, such that any attempt to spawn an editor is a no-op.
+    pub(crate) fn use_editor(mut self, use_editor: bool) -> Self {
+        self.no_editor = !use_editor;
+        self
+    }
 }
 
 impl StupidContext<'_, '_> {
@@ -80,6 +126,18 @@ impl StupidContext<'_, '_> {
             command.env("GIT_DIR", git_dir);
         }
         command.env("GIT_WORK_TREE", ".");
+        setup_terminal_prompt_env(&mut command);
+        Ok(command)
+    }
+
+    /// Get command for git in the work tree root with messages in the C locale.
+    ///
+    /// Commands run with [`retry_if_index_locked()`] must use this such that index
+    /// lock contention may be recognized from git's stderr regardless of the user's
+    /// locale.
+    fn git_in_work_root_c_locale(&self) -> Result<Command> {
+        let mut command = self.git_in_work_root()?;
+        command.env("LC_ALL", "C");
         Ok(command)
     }
 
@@ -95,29 +153,27 @@ impl StupidContext<'_, '_> {
                     .join(filename),
             )
         });
+        if self.no_editor {
+            command.env("GIT_EDITOR", ":");
+        }
+        setup_terminal_prompt_env(command);
     }
 
     fn at_least_version(&self, version: &StupidVersion) -> Result<bool> {
-        let mut git_version = self.git_version.borrow_mut();
-        if let Some(git_version) = git_version.as_ref() {
-            Ok(git_version >= version)
-        } else {
-            let interrogated_version = self.version()?.parse::<StupidVersion>()?;
-            let is_at_least = &interrogated_version >= version;
-            git_version.replace(interrogated_version);
-            Ok(is_at_least)
-        }
+        Ok(&self.parsed_version()? >= version)
     }
 }
 
 impl StupidContext<'_, '_> {
     /// Apply a patch (diff) to the specified index using `git apply --cached`.
     pub(crate) fn apply_to_index(&self, diff: &BStr) -> Result<()> {
-        self.git_in_work_root()?
-            .args(["apply", "--cached"]) // TODO: use --recount?
-            .stdout(Stdio::null())
-            .in_and_out(diff)?
-            .require_success("apply")?;
+        retry_if_index_locked(|| {
+            self.git_in_work_root_c_locale()?
+                .args(["apply", "--cached"]) // TODO: use --recount?
+                .stdout(Stdio::null())
+                .in_and_out(diff)
+        })?
+        .require_success("apply")?;
         Ok(())
     }
 
@@ -166,9 +222,11 @@ impl StupidContext<'_, '_> {
         }
     }
 
-    /// Apply diff between two trees to specified index.
+    /// Apply diff between two trees to this context's index.
     ///
-    /// Pipes `git diff-tree | git apply --cached`.
+    /// Pipes `git diff-tree | git apply --cached`. The index is the default index
+    /// unless this context uses a temporary index; see
+    /// [`StupidContext::apply_treediff_to_default_index()`].
     ///
     /// Returns `true` if the patch application is successful, `false` otherwise.
     pub(crate) fn apply_treediff_to_index(
@@ -180,45 +238,78 @@ impl StupidContext<'_, '_> {
         if tree1 == tree2 {
             return Ok(true);
         }
-        let mut diff_tree_child = self
-            .git()
-            .args(["diff-tree", "--full-index", "--binary", "--patch"])
-            .arg(tree1.to_string())
-            .arg(tree2.to_string())
-            .arg("--")
-            .stdin(Stdio::null())
-            .stdout(Stdio::piped())
-            .spawn_git()?;
+        let want_3way = want_3way && self.at_least_version(&StupidVersion::new(2, 32, 0))?;
+        let apply_output = retry_if_index_locked(|| {
+            let mut diff_tree_cmd = self.git();
+            diff_tree_cmd
+                .args(["diff-tree", "--full-index", "--binary", "--patch"])
+                .arg(tree1.to_string())
+                .arg(tree2.to_string())
+                .arg("--");
+
+            let mut apply_cmd = self.git_in_work_root_c_locale()?;
+            apply_cmd.args(["apply", "--cached"]);
+            if want_3way {
+                apply_cmd.arg("--3way");
+            }
+            apply_cmd.stdout(Stdio::null());
 
-        let mut apply_cmd = self.git_in_work_root()?;
-        apply_cmd.args(["apply", "--cached"]);
-        if want_3way && self.at_least_version(&StupidVersion::new(2, 32, 0))? {
-            apply_cmd.arg("--3way");
-        }
-        let apply_output = apply_cmd
-            .stdin(diff_tree_child.stdout.take().unwrap())
-            .stdout(Stdio::null())
-            .output_git()?
-            .require_code_less_than("apply", 128)?;
+            let (diff_tree_output, apply_output) = pipe_git(&mut diff_tree_cmd, &mut apply_cmd)?;
+
+            // When `git apply` fails to take the index lock, it exits without reading
+            // all of its input, so `git diff-tree` may fail due to the broken pipe.
+            if !is_index_lock_contention(&apply_output.stderr) {
+                diff_tree_output.require_success("diff-tree")?;
+            }
+            Ok(apply_output)
+        })?
+        .require_code_less_than("apply", 128)?;
 
-        diff_tree_child.require_success("diff-tree")?;
         Ok(apply_output.status.success())
     }
 
+    /// Apply diff between two trees to the repository's default index.
+    ///
+    /// Unlike [`StupidContext::apply_treediff_to_index()`], which applies the diff to
+    /// this context's index, e.g. the temporary index within
+    /// [`StupidContext::with_temp_index()`], the diff is always applied to the default
+    /// index, i.e. without any `GIT_INDEX_FILE` override.
+    ///
+    /// Returns `true` if the patch application is successful, `false` otherwise.
+    pub(crate) fn apply_treediff_to_default_index(
+        &self,
+        tree1: gix::ObjectId,
+        tree2: gix::ObjectId,
+        want_3way: bool,
+    ) -> Result<bool> {
+        StupidContext {
+            index_filename: None,
+            ..self.clone()
+        }
+        .apply_treediff_to_index(tree1, tree2, want_3way)
+    }
+
     /// Apply path limited diff between to trees to specified index.
+    ///
+    /// Returns whether the patch application is successful along with the
+    /// pathspecs, if any, which did not match any changes between the two trees.
     pub(crate) fn apply_pathlimited_treediff_to_index<SpecIter, SpecArg>(
         &self,
         tree1: gix::ObjectId,
         tree2: gix::ObjectId,
         want_3way: bool,
         pathspecs: SpecIter,
-    ) -> Result<bool>
+    ) -> Result<(bool, Vec<OsString>)>
     where
         SpecIter: IntoIterator<Item = SpecArg>,
         SpecArg: AsRef<OsStr>,
     {
+        let pathspecs: Vec<OsString> = pathspecs
+            .into_iter()
+            .map(|spec| spec.as_ref().to_os_string())
+            .collect();
         if tree1 == tree2 {
-            return Ok(true);
+            return Ok((true, pathspecs));
         }
         let mut diff_tree_command = self.git();
         diff_tree_command
@@ -226,7 +317,7 @@ impl StupidContext<'_, '_> {
             .arg(tree1.to_string())
             .arg(tree2.to_string())
             .arg("--")
-            .args(pathspecs);
+            .args(&pathspecs);
 
         let diff = diff_tree_command
             .stdin(Stdio::null())
@@ -236,28 +327,64 @@ impl StupidContext<'_, '_> {
             .stdout;
 
         if diff.is_empty() {
-            return Ok(true);
+            return Ok((true, pathspecs));
         }
 
-        let mut apply_cmd = self.git_in_work_root()?;
-        apply_cmd.args(["apply", "--cached"]);
-        if want_3way && self.at_least_version(&StupidVersion::new(2, 32, 0))? {
-            apply_cmd.arg("--3way");
-        }
-        let apply_output = apply_cmd
-            .stdin(Stdio::piped())
-            .stdout(Stdio::null())
-            .in_and_out(&diff)?;
+        let unmatched = if pathspecs.len() > 1 {
+            let mut unmatched = Vec::new();
+            for spec in pathspecs {
+                if !self.diff_tree_pathspec_quiet(tree1, tree2, &spec)? {
+                    unmatched.push(spec);
+                }
+            }
+            unmatched
+        } else {
+            Vec::new()
+        };
+
+        let want_3way = want_3way && self.at_least_version(&StupidVersion::new(2, 32, 0))?;
+        let apply_output = retry_if_index_locked(|| {
+            let mut apply_cmd = self.git_in_work_root_c_locale()?;
+            apply_cmd.args(["apply", "--cached"]);
+            if want_3way {
+                apply_cmd.arg("--3way");
+            }
+            apply_cmd
+                .stdin(Stdio::piped())
+                .stdout(Stdio::null())
+                .in_and_out(&diff)
+        })?;
 
         if apply_output.status.success() {
-            Ok(true)
+            Ok((true, unmatched))
         } else if apply_output.status.code() == Some(1) {
-            Ok(false)
+            Ok((false, unmatched))
         } else {
             Err(git_command_error("apply", &apply_output.stderr))
         }
     }
 
+    /// Determine whether two trees differ for paths matching `pathspec`.
+    fn diff_tree_pathspec_quiet(
+        &self,
+        tree1: gix::ObjectId,
+        tree2: gix::ObjectId,
+        pathspec: &OsStr,
+    ) -> Result<bool> {
+        let output = self
+            .git()
+            .args(["diff-tree", "-r", "--quiet"])
+            .arg(tree1.to_string())
+            .arg(tree2.to_string())
+            .arg("--")
+            .arg(pathspec)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .output_git()?
+            .require_code_less_than("diff-tree", 2)?;
+        Ok(!output.status.success())
+    }
+
     /// Apply diff between two trees to worktree and index.
     ///
     /// Pipes `git diff-tree | git apply --index`.
@@ -382,15 +509,38 @@ impl StupidContext<'_, '_> {
         message: &[u8],
         tree_id: gix::ObjectId,
         parent_ids: impl IntoIterator<Item = gix::ObjectId>,
-        gpgsign: bool,
+        gpgsign: &GpgSign,
+    ) -> Result<gix::ObjectId> {
+        self.commit_tree_with_dates(
+            author, committer, None, None, message, tree_id, parent_ids, gpgsign,
+        )
+    }
+
+    /// Create a commit using `git commit-tree` with optional pre-formatted dates.
+    ///
+    /// The provided author and committer date strings are passed verbatim as
+    /// `GIT_AUTHOR_DATE` and `GIT_COMMITTER_DATE`, respectively, and may be in any
+    /// format understood by git. When a date string is not provided, the date is
+    /// derived from the corresponding signature's time.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn commit_tree_with_dates(
+        &self,
+        author: &gix::actor::Signature,
+        committer: &gix::actor::Signature,
+        author_date: Option<&str>,
+        committer_date: Option<&str>,
+        message: &[u8],
+        tree_id: gix::ObjectId,
+        parent_ids: impl IntoIterator<Item = gix::ObjectId>,
+        gpgsign: &GpgSign,
     ) -> Result<gix::ObjectId> {
         let mut command = self.git();
         command.arg("commit-tree").arg(tree_id.to_string());
         for parent_id in parent_ids {
             command.arg("-p").arg(parent_id.to_string());
         }
-        if gpgsign {
-            command.arg("-S");
+        if let Some(sign_arg) = gpgsign.commit_tree_arg() {
+            command.arg(sign_arg);
         }
         let author_name = author
             .name
@@ -414,14 +564,13 @@ impl StupidContext<'_, '_> {
             .env("GIT_AUTHOR_EMAIL", author_email)
             .env("GIT_COMMITTER_NAME", committer_name)
             .env("GIT_COMMITTER_EMAIL", committer_email)
-            // TODO: re-encode dates?
             .env(
                 "GIT_AUTHOR_DATE",
-                author.time.format(gix::date::time::format::RAW),
+                author_date.map_or_else(|| author.time.raw_string(), str::to_string),
             )
             .env(
                 "GIT_COMMITTER_DATE",
-                committer.time.format(gix::date::time::format::RAW),
+                committer_date.map_or_else(|| committer.time.raw_string(), str::to_string),
             )
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
@@ -539,7 +688,7 @@ impl StupidContext<'_, '_> {
     }
 
     /// Get names of files that differ between two trees.
-    pub(crate) fn diff_tree_files(
+    pub(crate) fn diff_tree_names(
         &self,
         tree1: gix::ObjectId,
         tree2: gix::ObjectId,
@@ -552,15 +701,38 @@ impl StupidContext<'_, '_> {
             .map(|output| DiffFiles::new(output.stdout))
     }
 
-    /// Interactive diff-tree (for 'stg files').
-    pub(crate) fn diff_tree_files_status(
+    /// Get status and path of files that differ between two trees.
+    ///
+    /// Renames are detected, with the rename source provided by
+    /// [`DiffFileStatus::Renamed`].
+    pub(crate) fn diff_tree_files(
+        &self,
+        tree1: gix::ObjectId,
+        tree2: gix::ObjectId,
+    ) -> Result<Vec<(DiffFileStatus, PathBuf)>> {
+        let output = self
+            .git()
+            .args(["diff-tree", "-r", "-M", "--name-status", "-z"])
+            .args([tree1.to_string(), tree2.to_string()])
+            .output_git()?
+            .require_success("diff-tree")?;
+        parse_name_status(&output.stdout)
+    }
+
+    /// Interactive diff-tree (for 'stg files' and 'stg spill --dry-run').
+    pub(crate) fn diff_tree_files_status<SpecIter, SpecArg>(
         &self,
         tree1: gix::ObjectId,
         tree2: gix::ObjectId,
+        pathspecs: Option<SpecIter>,
         stat: bool,
         name_only: bool,
         use_color: bool,
-    ) -> Result<BString> {
+    ) -> Result<BString>
+    where
+        SpecIter: IntoIterator<Item = SpecArg>,
+        SpecArg: AsRef<OsStr>,
+    {
         let mut command = self.git();
         command.args(["diff-tree", "-r"]);
         if stat {
@@ -576,6 +748,10 @@ impl StupidContext<'_, '_> {
             "--color=never"
         });
         command.args([tree1.to_string(), tree2.to_string()]);
+        if let Some(pathspecs) = pathspecs {
+            command.arg("--");
+            command.args(pathspecs);
+        }
         let output = command.output_git()?.require_success("diff-tree")?;
         Ok(BString::from(output.stdout))
     }
@@ -681,13 +857,18 @@ impl StupidContext<'_, '_> {
     }
 
     /// Add trailers to commit message with `git interpret-trailers`.
+    ///
+    /// The provided [`TrailerOptions`] control the placement of the added trailers and
+    /// how they interact with any existing trailers.
     pub(crate) fn interpret_trailers<'a>(
         &self,
         message: &[u8],
         trailers: impl IntoIterator<Item = (&'a str, &'a str)>,
+        options: &TrailerOptions,
     ) -> Result<Vec<u8>> {
         let mut command = self.git();
         command.arg("interpret-trailers");
+        command.args(options.args());
         for (trailer, by) in trailers {
             command.arg("--trailer");
             command.arg(format!("{trailer}={by}"));
@@ -699,6 +880,20 @@ impl StupidContext<'_, '_> {
         Ok(output.stdout)
     }
 
+    /// Parse the trailers of a message using `git interpret-trailers --parse`.
+    ///
+    /// Returns the (key, value) pairs of the trailers found in the message's trailer
+    /// block, in order, with any continuation lines unfolded.
+    pub(crate) fn parse_trailers(&self, message: &[u8]) -> Result<Vec<(String, String)>> {
+        let output = self
+            .git()
+            .args(["interpret-trailers", "--parse"])
+            .stdout(Stdio::piped())
+            .in_and_out(message)?
+            .require_success("interpret-trailers")?;
+        Ok(super::trailers::parse_trailer_lines(&output.stdout))
+    }
+
     /// Interactively show log
     pub(crate) fn log<SpecIter, SpecArg>(
         &self,
@@ -831,16 +1026,65 @@ impl StupidContext<'_, '_> {
 
     /// Perform three-way merge with `git merge-recursive`.
     ///
+    /// Each of the `strategy_options`, e.g. `ours`, `theirs`, or `renormalize`, is
+    /// forwarded to `git merge-recursive` as `--<option>`, which is equivalent to
+    /// `git merge -X<option>`.
+    ///
+    /// When `conflict_style` is provided, conflict markers are written in that style
+    /// regardless of the `merge.conflictStyle` config. Otherwise the config is honored.
+    ///
     /// Returns `true` if the merge was successful, `false` otherwise.
-    pub(crate) fn merge_recursive(
+    pub(crate) fn merge_recursive<OptIter, OptArg>(
         &self,
         base_tree_id: gix::ObjectId,
         our_tree_id: gix::ObjectId,
         their_tree_id: gix::ObjectId,
-    ) -> Result<bool> {
-        let output = self
-            .git()
-            .arg("merge-recursive")
+        strategy_options: OptIter,
+        conflict_style: Option<ConflictStyle>,
+    ) -> Result<bool>
+    where
+        OptIter: IntoIterator<Item = OptArg>,
+        OptArg: AsRef<str>,
+    {
+        Ok(self
+            .merge_recursive_conflicts(
+                base_tree_id,
+                our_tree_id,
+                their_tree_id,
+                strategy_options,
+                conflict_style,
+            )?
+            .is_none())
+    }
+
+    /// Perform three-way merge with `git merge-recursive`, reporting conflicted paths.
+    ///
+    /// Returns `None` if the merge was successful, or the paths involved in conflicts
+    /// as reported by the `CONFLICT` lines of the merge output otherwise.
+    pub(crate) fn merge_recursive_conflicts<OptIter, OptArg>(
+        &self,
+        base_tree_id: gix::ObjectId,
+        our_tree_id: gix::ObjectId,
+        their_tree_id: gix::ObjectId,
+        strategy_options: OptIter,
+        conflict_style: Option<ConflictStyle>,
+    ) -> Result<Option<Vec<PathBuf>>>
+    where
+        OptIter: IntoIterator<Item = OptArg>,
+        OptArg: AsRef<str>,
+    {
+        let mut command = self.git();
+        if let Some(conflict_style) = conflict_style {
+            command.args([
+                "-c",
+                &format!("merge.conflictStyle={}", conflict_style.config_value()),
+            ]);
+        }
+        command.arg("merge-recursive");
+        for option in strategy_options {
+            command.arg(format!("--{}", option.as_ref()));
+        }
+        let output = command
             .arg(base_tree_id.to_string())
             .arg("--")
             .arg(our_tree_id.to_string())
@@ -851,9 +1095,9 @@ impl StupidContext<'_, '_> {
             .output_git()?;
 
         if output.status.success() {
-            Ok(true)
+            Ok(None)
         } else if output.status.code() == Some(1) {
-            Ok(false)
+            Ok(Some(parse_conflict_paths(&output.stdout)))
         } else {
             Err(git_command_error("merge-recursive", &output.stderr))
         }
@@ -861,29 +1105,59 @@ impl StupidContext<'_, '_> {
 
     /// Perform three-way merge, with optional auto-resolution of conflicts with
     /// `git merge-tool`.
-    pub(crate) fn merge_recursive_or_mergetool(
+    pub(crate) fn merge_recursive_or_mergetool<OptIter, OptArg>(
         &self,
         base_tree_id: gix::ObjectId,
         our_tree_id: gix::ObjectId,
         their_tree_id: gix::ObjectId,
+        strategy_options: OptIter,
+        conflict_style: Option<ConflictStyle>,
         use_mergetool: bool,
-    ) -> Result<bool> {
-        if self.merge_recursive(base_tree_id, our_tree_id, their_tree_id)? {
+    ) -> Result<bool>
+    where
+        OptIter: IntoIterator<Item = OptArg>,
+        OptArg: AsRef<str>,
+    {
+        if self.merge_recursive(
+            base_tree_id,
+            our_tree_id,
+            their_tree_id,
+            strategy_options,
+            conflict_style,
+        )? {
             Ok(true)
         } else if use_mergetool {
-            self.mergetool()
+            Ok(self.mergetool(None)?.is_none())
         } else {
             Ok(false)
         }
     }
 
-    /// Attempt to resolve outstanding merge conflicts with `git merge-tool`.
-    pub(crate) fn mergetool(&self) -> Result<bool> {
-        let output = self.git().arg("merge-tool").output_git()?;
+    /// Attempt to resolve outstanding merge conflicts with `git mergetool`.
+    ///
+    /// Returns `None` if all conflicts were resolved. Otherwise, the paths that remain
+    /// unmerged in the index after the merge tool session are returned. These are
+    /// queried from the index rather than taken from the original merge output since
+    /// the merge tool may have resolved some, but not all, of the conflicts.
+    ///
+    /// When `tool` is provided, it is passed to `git mergetool` with `--tool`,
+    /// overriding any `merge.tool` configuration.
+    pub(crate) fn mergetool(&self, tool: Option<&str>) -> Result<Option<Vec<PathBuf>>> {
+        let mut command = self.git();
+        command.arg("mergetool");
+        if let Some(tool) = tool {
+            command.arg(format!("--tool={tool}"));
+        }
+        let output = command.output_git()?;
         if output.status.success() {
-            Ok(true)
+            Ok(None)
         } else if output.status.code() == Some(1) {
-            Ok(false)
+            Ok(Some(
+                self.diff_unmerged_names()?
+                    .into_iter()
+                    .map(PathBuf::from)
+                    .collect(),
+            ))
         } else {
             Err(git_command_error("mergetool", &output.stderr))
         }
@@ -903,22 +1177,57 @@ impl StupidContext<'_, '_> {
 
     /// Read content of a tree into specified index using `git read-tree`.
     pub(crate) fn read_tree(&self, tree_id: gix::ObjectId) -> Result<()> {
-        self.git_in_work_root()?
-            .arg("read-tree")
-            .arg(tree_id.to_string())
-            .stdout(Stdio::null())
-            .output_git()?
-            .require_success("read-tree")?;
+        retry_if_index_locked(|| {
+            self.git_in_work_root_c_locale()?
+                .arg("read-tree")
+                .arg(tree_id.to_string())
+                .stdout(Stdio::null())
+                .output_git()
+        })?
+        .require_success("read-tree")?;
         Ok(())
     }
 
+    /// Interactively select hunks to reset in the index using `git reset --patch`.
+    ///
+    /// The user chooses which hunks of the difference between `tree_id` and the index
+    /// are reverted, in the index, to their state in `tree_id`.
+    pub(crate) fn reset_patch<SpecIter, SpecArg>(
+        &self,
+        tree_id: gix::ObjectId,
+        pathspecs: Option<SpecIter>,
+    ) -> Result<()>
+    where
+        SpecIter: IntoIterator<Item = SpecArg>,
+        SpecArg: AsRef<OsStr>,
+    {
+        let mut command = self.git();
+        command
+            .args(["reset", "--patch"])
+            .arg(tree_id.to_string())
+            .arg("--");
+        if let Some(pathspecs) = pathspecs {
+            command.args(pathspecs);
+        }
+        let status = command.status().context("could not execute `git`")?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(anyhow!("`git reset --patch` failed"))
+        }
+    }
+
     /// Checkout tree to working tree using `git read-tree`.
+    ///
+    /// If the checkout fails because it would overwrite paths in the working tree, the
+    /// returned error may be downcast to [`CheckoutConflicts`].
     pub(crate) fn read_tree_checkout(
         &self,
         old_tree_id: gix::ObjectId,
         new_tree_id: gix::ObjectId,
     ) -> Result<()> {
-        self.git()
+        let output = self
+            .git()
             .args([
                 "read-tree",
                 "-m",
@@ -928,9 +1237,60 @@ impl StupidContext<'_, '_> {
             .arg(old_tree_id.to_string())
             .arg(new_tree_id.to_string())
             .stdout(Stdio::null())
-            .output_git()?
-            .require_success("read-tree -m -u")?;
-        Ok(())
+            .output_git()?;
+        if output.status.success() {
+            return Ok(());
+        }
+        let paths = parse_checkout_conflict_paths(&output.stderr);
+        if paths.is_empty() {
+            Err(git_command_error("read-tree -m -u", &output.stderr))
+        } else {
+            Err(anyhow::Error::new(CheckoutConflicts {
+                paths,
+                stderr: output.stderr.to_str_lossy().trim_end().to_string(),
+            })
+            .context("`git read-tree -m -u`"))
+        }
+    }
+
+    /// Check whether checking-out a tree with `git read-tree` would succeed.
+    ///
+    /// Uses `git read-tree -m -u --dry-run` such that neither the index nor the
+    /// working tree are modified.
+    ///
+    /// Returns the paths that would be overwritten by the checkout, which is empty if
+    /// the checkout would succeed.
+    pub(crate) fn read_tree_checkout_dry_run(
+        &self,
+        old_tree_id: gix::ObjectId,
+        new_tree_id: gix::ObjectId,
+    ) -> Result<Vec<PathBuf>> {
+        let output = self
+            .git()
+            .args([
+                "read-tree",
+                "-m",
+                "-u",
+                "--dry-run",
+                "--exclude-per-directory=.gitignore",
+            ])
+            .arg(old_tree_id.to_string())
+            .arg(new_tree_id.to_string())
+            .stdout(Stdio::null())
+            .output_git()?;
+        if output.status.success() {
+            Ok(Vec::new())
+        } else {
+            let paths = parse_checkout_conflict_paths(&output.stderr);
+            if paths.is_empty() {
+                Err(git_command_error(
+                    "read-tree -m -u --dry-run",
+                    &output.stderr,
+                ))
+            } else {
+                Ok(paths)
+            }
+        }
     }
 
     /// Hard checkout tree to working tree using `git read-tree`.
@@ -985,6 +1345,27 @@ impl StupidContext<'_, '_> {
         Ok(oids)
     }
 
+    /// Resolve a commit-ish, e.g. `HEAD~2`, a branch name, or a tag, to a commit id
+    /// using `git rev-parse --verify`.
+    ///
+    /// Tags are peeled to the commit they point to. It is an error if `spec` does not
+    /// name an object or the object does not resolve to a commit.
+    pub(crate) fn rev_parse(&self, spec: &str) -> Result<gix::ObjectId> {
+        let verify = |spec: &str| {
+            self.git()
+                .args(["rev-parse", "--verify", "--quiet", "--end-of-options", spec])
+                .output_git()
+        };
+        let output = verify(&format!("{spec}^{{commit}}"))?;
+        if output.status.success() {
+            parse_oid(&output.stdout)
+        } else if verify(spec)?.status.success() {
+            Err(anyhow!("`{spec}` does not resolve to a commit"))
+        } else {
+            Err(anyhow!("invalid revision `{spec}`"))
+        }
+    }
+
     /// Get `cdup` for current directory from `git rev-parse --show-cdup`.
     pub(crate) fn rev_parse_cdup(&self) -> Result<OsString> {
         let output = self
@@ -1048,13 +1429,20 @@ impl StupidContext<'_, '_> {
     }
 
     /// Show objects using `git show`.
+    ///
+    /// With `follow`, renames are detected and shown as such. When limited to a single
+    /// pathspec, `--follow` is used such that a rename source outside of the pathspec
+    /// is still shown as the source of the rename.
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn show<SpecIter, SpecArg, OptIter, OptArg>(
         &self,
         oids: impl IntoIterator<Item = gix::ObjectId>,
         pathspecs: Option<SpecIter>,
-        stat: bool,
-        use_color: bool,
+        mode: ShowMode,
+        color: termcolor::ColorChoice,
+        follow: bool,
         diff_opts: OptIter,
+        pager: &mut Pager,
     ) -> Result<()>
     where
         SpecIter: IntoIterator<Item = SpecArg>,
@@ -1063,31 +1451,63 @@ impl StupidContext<'_, '_> {
         OptArg: AsRef<OsStr>,
     {
         let mut command = self.git();
+        if pager.is_active() {
+            if let Some(pager_color) = crate::color::stgit_pager_color() {
+                command.arg("-c").arg(format!("color.pager={pager_color}"));
+            }
+        }
         command.arg("show");
-        if stat {
-            command.args(["--stat", "--summary"]);
-        } else {
-            command.arg("--patch");
+        command.args(mode.args());
+
+        // With auto color, git decides based on its own config and whether its
+        // stdout is a terminal.
+        match color {
+            termcolor::ColorChoice::Always | termcolor::ColorChoice::AlwaysAnsi => {
+                command.arg("--color=always");
+            }
+            termcolor::ColorChoice::Never => {
+                command.arg("--color=never");
+            }
+            termcolor::ColorChoice::Auto => {}
         }
 
-        command.arg(if use_color {
-            "--color=always"
-        } else {
-            "--color=never"
-        });
+        let pathspecs: Vec<SpecArg> = pathspecs.into_iter().flatten().collect();
+        if follow {
+            command.arg("-M");
+            if pathspecs.len() == 1 {
+                command.arg("--follow");
+            }
+        }
 
         command.args(diff_opts);
         command.args(oids.into_iter().map(|oid| oid.to_string()));
         command.arg("--");
-
-        if let Some(pathspecs) = pathspecs {
-            command.args(pathspecs);
+        command.args(pathspecs);
+
+        if pager.is_active() {
+            // As when git spawns the pager itself, color is decided by `color.pager`,
+            // which is overridden by `stgit.pager.color` above.
+            command.env("GIT_PAGER_IN_USE", "true");
+            let mut child = command.stdout(Stdio::piped()).spawn_git()?;
+            let mut stdout = child.stdout.take().expect("stdout is piped");
+            let copy_result = std::io::copy(&mut stdout, pager);
+            drop(stdout);
+            match copy_result {
+                Ok(_) => {
+                    child.require_success("show")?;
+                }
+                // The user quit the pager before all of the output was shown.
+                Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => {
+                    child.wait()?;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        } else {
+            command
+                .stdout(Stdio::inherit())
+                .output_git()?
+                .require_success("show")?;
         }
-
-        command
-            .stdout(Stdio::inherit())
-            .output_git()?
-            .require_success("show")?;
         Ok(())
     }
 
@@ -1345,7 +1765,20 @@ impl StupidContext<'_, '_> {
     }
 
     /// Get git version with `git version`.
+    ///
+    /// The git version is only interrogated once per process; subsequent calls return
+    /// the cached version.
     pub(crate) fn version(&self) -> Result<String> {
+        if let Some(version_line) = GIT_VERSION.get() {
+            Ok(version_line.clone())
+        } else {
+            let version_line = self.query_version()?;
+            Ok(GIT_VERSION.get_or_init(|| version_line).clone())
+        }
+    }
+
+    /// Interrogate git version with `git version`, bypassing the cache.
+    fn query_version(&self) -> Result<String> {
         let output = self
             .git()
             .arg("version")
@@ -1359,13 +1792,19 @@ impl StupidContext<'_, '_> {
         Ok(version_line)
     }
 
+    /// Get parsed git version, suitable for gating version-dependent features.
+    pub(crate) fn parsed_version(&self) -> Result<StupidVersion> {
+        self.version()?.parse::<StupidVersion>()
+    }
+
     /// Write tree object from content of specified index using `git write-tree`.
     pub(crate) fn write_tree(&self) -> Result<gix::ObjectId> {
-        let output = self
-            .git_in_work_root()?
-            .arg("write-tree")
-            .output_git()?
-            .require_success("write-tree")?;
+        let output = retry_if_index_locked(|| {
+            self.git_in_work_root_c_locale()?
+                .arg("write-tree")
+                .output_git()
+        })?
+        .require_success("write-tree")?;
         parse_oid(&output.stdout)
     }
 }
@@ -2,10 +2,34 @@
 
 //! Support for parsing diff output from `git`.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
+use anyhow::{anyhow, Result};
 use bstr::ByteSlice;
 
+/// How `git show` presents the changes introduced by each commit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ShowMode {
+    /// Full patch, i.e. `--patch`.
+    Patch,
+
+    /// Diffstat and summary, i.e. `--stat --summary`.
+    Stat,
+
+    /// Names and status of changed files, i.e. `--name-status`.
+    NameStatus,
+}
+
+impl ShowMode {
+    pub(super) fn args(self) -> &'static [&'static str] {
+        match self {
+            ShowMode::Patch => &["--patch"],
+            ShowMode::Stat => &["--stat", "--summary"],
+            ShowMode::NameStatus => &["--name-status"],
+        }
+    }
+}
+
 /// Diff output containing only names of differing files.
 ///
 /// E.g. from `git diff-tree --name-only -z`
@@ -52,6 +76,63 @@ impl<'a> Iterator for DiffFilesIter<'a> {
     }
 }
 
+/// Status of a file differing between two trees.
+///
+/// E.g. from `git diff-tree --name-status`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum DiffFileStatus {
+    Added,
+    Modified,
+    Deleted,
+    FileTypeChanged,
+
+    /// The file was renamed from the contained path, possibly with modifications.
+    Renamed {
+        from: PathBuf,
+    },
+}
+
+/// Parse `git diff-tree --name-status -z` output into statuses and paths.
+///
+/// The path paired with [`DiffFileStatus::Renamed`] is the rename destination.
+pub(super) fn parse_name_status(data: &[u8]) -> Result<Vec<(DiffFileStatus, PathBuf)>> {
+    let mut fields = data.split_str(b"\0");
+    let next_path = |fields: &mut bstr::Split<'_, '_>| -> Result<PathBuf> {
+        fields
+            .next()
+            .filter(|field| !field.is_empty())
+            .ok_or_else(|| anyhow!("missing path in diff-tree output"))?
+            .to_path()
+            .map(Path::to_path_buf)
+            .map_err(|_| anyhow!("paths on Windows must be utf8"))
+    };
+
+    let mut files = Vec::new();
+    while let Some(status) = fields.next() {
+        // The output is nul terminated, leaving an empty trailing field.
+        if status.is_empty() {
+            continue;
+        }
+        let status = match status[0] {
+            b'A' => DiffFileStatus::Added,
+            b'M' => DiffFileStatus::Modified,
+            b'D' => DiffFileStatus::Deleted,
+            b'T' => DiffFileStatus::FileTypeChanged,
+            b'R' => DiffFileStatus::Renamed {
+                from: next_path(&mut fields)?,
+            },
+            _ => {
+                return Err(anyhow!(
+                    "unexpected diff-tree status `{}`",
+                    status.as_bstr()
+                ))
+            }
+        };
+        files.push((status, next_path(&mut fields)?));
+    }
+    Ok(files)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -65,4 +146,28 @@ mod tests {
         assert_eq!(it.next(), Some(Path::new("jkl")));
         assert!(it.next().is_none());
     }
+
+    #[test]
+    fn name_status_parsing() {
+        let files =
+            parse_name_status(b"M\0abc\0A\0def ghi\0R087\0old\0new\0D\0jkl\0T\0link\0").unwrap();
+        assert_eq!(
+            files,
+            [
+                (DiffFileStatus::Modified, PathBuf::from("abc")),
+                (DiffFileStatus::Added, PathBuf::from("def ghi")),
+                (
+                    DiffFileStatus::Renamed {
+                        from: PathBuf::from("old")
+                    },
+                    PathBuf::from("new")
+                ),
+                (DiffFileStatus::Deleted, PathBuf::from("jkl")),
+                (DiffFileStatus::FileTypeChanged, PathBuf::from("link")),
+            ]
+        );
+        assert!(parse_name_status(b"").unwrap().is_empty());
+        assert!(parse_name_status(b"R100\0old\0").is_err());
+        assert!(parse_name_status(b"X\0abc\0").is_err());
+    }
 }
@@ -0,0 +1,43 @@
+// SPDX-License-Identifier: GPL-2.0-only
+
+//! GPG signing of commit objects created with `git commit-tree`.
+
+/// How a commit object is to be GPG-signed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) enum GpgSign {
+    /// Do not sign the commit.
+    #[default]
+    No,
+
+    /// Sign with the default key, i.e. as determined by the `user.signingKey`
+    /// configuration or the committer identity.
+    DefaultKey,
+
+    /// Sign with the given key id.
+    Key(String),
+}
+
+impl GpgSign {
+    /// Sign with the default key if `gpgsign` is true, otherwise do not sign.
+    pub(crate) fn from_bool(gpgsign: bool) -> Self {
+        if gpgsign {
+            GpgSign::DefaultKey
+        } else {
+            GpgSign::No
+        }
+    }
+
+    /// Determine whether the commit is to be signed at all.
+    pub(crate) fn is_enabled(&self) -> bool {
+        !matches!(self, GpgSign::No)
+    }
+
+    /// Get the `-S[<keyid>]` option for `git commit-tree`, if the commit is to be signed.
+    pub(super) fn commit_tree_arg(&self) -> Option<String> {
+        match self {
+            GpgSign::No => None,
+            GpgSign::DefaultKey => Some("-S".to_string()),
+            GpgSign::Key(keyid) => Some(format!("-S{keyid}")),
+        }
+    }
+}
@@ -7,18 +7,23 @@
 //! remains until `gitoxide` can replace its behaviors.
 
 mod command;
+mod conflict;
 mod context;
 mod diff;
+mod gpgsign;
 mod oid;
 mod status;
 mod tempindex;
+mod trailers;
 mod version;
 
-use std::cell::RefCell;
-
 pub(crate) use self::{
+    conflict::{CheckoutConflicts, ConflictStyle},
     context::StupidContext,
+    diff::{DiffFileStatus, ShowMode},
+    gpgsign::GpgSign,
     status::{Status, StatusOptions, Statuses},
+    trailers::{TrailerIfExists, TrailerIfMissing, TrailerOptions, TrailerPlacement},
 };
 
 pub(crate) trait Stupid<'repo, 'index> {
@@ -32,7 +37,7 @@ impl<'repo, 'index> Stupid<'repo, 'index> for gix::Repository {
             git_dir: Some(self.git_dir()),
             work_dir: self.workdir(),
             index_filename: None,
-            git_version: RefCell::new(None::<self::version::StupidVersion>),
+            no_editor: false,
         }
     }
 }
@@ -1,9 +1,53 @@
 // SPDX-License-Identifier: GPL-2.0-only
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Result};
 use bstr::ByteSlice;
 
+/// Maximum number of characters of unparseable output to include in error messages.
+const PREVIEW_LEN: usize = 80;
+
+/// Parse object id from the output of a git command.
+///
+/// Output that is not a valid hex object id, e.g. because an error message leaked to
+/// stdout, results in an error that includes a lossy preview of the offending output.
 pub(super) fn parse_oid(output: &[u8]) -> Result<gix::ObjectId> {
-    gix::ObjectId::from_hex(output.as_bstr().trim_end())
-        .with_context(|| format!("converting oid `{}`", output.to_str_lossy().trim_end()))
+    gix::ObjectId::from_hex(output.as_bstr().trim_end()).map_err(|e| {
+        let lossy = output.to_str_lossy();
+        let lossy = lossy.trim_end();
+        let preview = if lossy.chars().count() > PREVIEW_LEN {
+            format!("{}...", lossy.chars().take(PREVIEW_LEN).collect::<String>())
+        } else {
+            lossy.to_string()
+        };
+        anyhow!(
+            "git produced unparseable object id `{}`: {e}",
+            preview.escape_debug()
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_oid;
+
+    #[test]
+    fn parse_valid_oid() {
+        let hex = "0123456789abcdef0123456789abcdef01234567";
+        let oid = parse_oid(format!("{hex}\n").as_bytes()).unwrap();
+        assert_eq!(oid.to_string(), hex);
+    }
+
+    #[test]
+    fn parse_non_utf8_oid() {
+        let err = parse_oid(b"fatal: \xff\xfe bad\n").unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.starts_with("git produced unparseable object id `fatal: \u{fffd}\u{fffd} bad`"));
+    }
+
+    #[test]
+    fn long_output_is_truncated() {
+        let output = "x".repeat(200);
+        let msg = parse_oid(output.as_bytes()).unwrap_err().to_string();
+        assert!(msg.contains(&format!("`{}...`", "x".repeat(80))));
+    }
 }
@@ -142,6 +142,7 @@ impl Status {
 }
 
 /// A snapshot of status information.
+#[derive(PartialEq, Eq)]
 pub(crate) struct Statuses {
     data: Vec<u8>,
     header_ranges: Vec<Range<usize>>,
@@ -216,6 +217,21 @@ impl Statuses {
         }
     }
 
+    /// Determine whether the index differs between this and another status snapshot.
+    ///
+    /// Entries are compared by their [`StatusEntry::index_fields()`], such that
+    /// differences only in the state of the worktree are disregarded.
+    pub(crate) fn index_differs(&self, other: &Statuses) -> bool {
+        let index_entries = |statuses: &Statuses| -> Vec<Vec<u8>> {
+            statuses
+                .iter()
+                .map(|entry| entry.index_fields())
+                .filter(|fields| !fields.is_empty())
+                .collect()
+        };
+        index_entries(self) != index_entries(other)
+    }
+
     /// Get supplemental status headers.
     #[allow(unused)]
     pub(crate) fn headers(&self) -> StatusHeaders<'_> {
@@ -345,6 +361,31 @@ impl<'s> StatusEntry<'s> {
         Status::from_char(self.data[self.range.start + 3])
     }
 
+    /// Get the entry's fields that pertain to the index.
+    ///
+    /// These are the entry's kind, staged status, index file modes and object names,
+    /// and path(s), joined by spaces. Entries without staged changes, as well as
+    /// untracked and ignored entries, have no index fields since the index is the same
+    /// as if the entry were absent.
+    pub(crate) fn index_fields(&self) -> Vec<u8> {
+        let slice = &self.data[self.range.clone()];
+        if matches!(self.kind(), StatusEntryKind::Ordinary)
+            && matches!(self.index_status(), Status::Unmodified)
+        {
+            return Vec::new();
+        }
+        let fields: Vec<&[u8]> = slice.split_str(b" ").collect();
+        let index_field_indices: &[usize] = match self.kind() {
+            StatusEntryKind::Ordinary | StatusEntryKind::Renamed => &[4, 7],
+            StatusEntryKind::Unmerged => &[3, 4, 5, 7, 8, 9],
+            StatusEntryKind::Untracked | StatusEntryKind::Ignored => return Vec::new(),
+        };
+        let mut index_fields = vec![&slice[..1], &slice[2..3]];
+        index_fields.extend(index_field_indices.iter().map(|&i| fields[i]));
+        index_fields.push(self.path_bytes());
+        bstr::join(" ", index_fields)
+    }
+
     pub(crate) fn path_bytes(&self) -> &'s [u8] {
         let slice = &self.data[self.range.clone()];
         match self.kind() {
@@ -688,4 +729,24 @@ mod tests {
         assert_eq!(ignored_iter.next().unwrap().value(), b"# also ignored");
         assert!(ignored_iter.next().is_none());
     }
+
+    #[test]
+    fn index_differs_ignores_worktree() {
+        let statuses = Statuses::from_data(EXAMPLE.to_vec());
+        let worktree_changed = Statuses::from_data(
+            b"\
+            1 .M N... 100644 100644 100644 0dd9459cbf0147f6171368d443e9ea80115d3ef2 0dd9459cbf0147f6171368d443e9ea80115d3ef2 file1\0"
+                .to_vec(),
+        );
+        let worktree_clean = Statuses::from_data(Vec::new());
+        let index_changed = Statuses::from_data(
+            b"\
+            1 M. N... 100644 100644 100644 0dd9459cbf0147f6171368d443e9ea80115d3ef2 ce013625030ba8dba906f756967f9e9ca394464a file1\0"
+                .to_vec(),
+        );
+        assert!(!worktree_changed.index_differs(&worktree_clean));
+        assert!(worktree_changed.index_differs(&index_changed));
+        assert!(statuses.index_differs(&worktree_clean));
+        assert!(!statuses.index_differs(&statuses));
+    }
 }
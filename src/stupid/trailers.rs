@@ -0,0 +1,248 @@
+// SPDX-License-Identifier: GPL-2.0-only
+
+//! Options for adding and parsing trailers with `git interpret-trailers`.
+
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use bstr::ByteSlice;
+
+/// Where added trailers are placed relative to existing trailers.
+///
+/// Corresponds to `git interpret-trailers --where`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TrailerPlacement {
+    After,
+    Before,
+    End,
+    Start,
+}
+
+/// Action taken when a trailer with the same key already exists.
+///
+/// Corresponds to `git interpret-trailers --if-exists`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TrailerIfExists {
+    AddIfDifferentNeighbor,
+    AddIfDifferent,
+    Add,
+    Replace,
+    DoNothing,
+}
+
+/// Action taken when no trailer with the same key exists.
+///
+/// Corresponds to `git interpret-trailers --if-missing`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TrailerIfMissing {
+    Add,
+    DoNothing,
+}
+
+impl TrailerPlacement {
+    fn as_str(self) -> &'static str {
+        match self {
+            TrailerPlacement::After => "after",
+            TrailerPlacement::Before => "before",
+            TrailerPlacement::End => "end",
+            TrailerPlacement::Start => "start",
+        }
+    }
+}
+
+impl TrailerIfExists {
+    fn as_str(self) -> &'static str {
+        match self {
+            TrailerIfExists::AddIfDifferentNeighbor => "addIfDifferentNeighbor",
+            TrailerIfExists::AddIfDifferent => "addIfDifferent",
+            TrailerIfExists::Add => "add",
+            TrailerIfExists::Replace => "replace",
+            TrailerIfExists::DoNothing => "doNothing",
+        }
+    }
+}
+
+impl TrailerIfMissing {
+    fn as_str(self) -> &'static str {
+        match self {
+            TrailerIfMissing::Add => "add",
+            TrailerIfMissing::DoNothing => "doNothing",
+        }
+    }
+}
+
+impl FromStr for TrailerPlacement {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "after" => Ok(TrailerPlacement::After),
+            "before" => Ok(TrailerPlacement::Before),
+            "end" => Ok(TrailerPlacement::End),
+            "start" => Ok(TrailerPlacement::Start),
+            _ => Err(anyhow!("invalid trailer placement `{s}`")),
+        }
+    }
+}
+
+impl FromStr for TrailerIfExists {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "addIfDifferentNeighbor" => Ok(TrailerIfExists::AddIfDifferentNeighbor),
+            "addIfDifferent" => Ok(TrailerIfExists::AddIfDifferent),
+            "add" => Ok(TrailerIfExists::Add),
+            "replace" => Ok(TrailerIfExists::Replace),
+            "doNothing" => Ok(TrailerIfExists::DoNothing),
+            _ => Err(anyhow!("invalid trailer if-exists action `{s}`")),
+        }
+    }
+}
+
+impl FromStr for TrailerIfMissing {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "add" => Ok(TrailerIfMissing::Add),
+            "doNothing" => Ok(TrailerIfMissing::DoNothing),
+            _ => Err(anyhow!("invalid trailer if-missing action `{s}`")),
+        }
+    }
+}
+
+/// Options controlling how trailers are added by
+/// [`super::StupidContext::interpret_trailers()`].
+///
+/// Unset options defer to git's configuration and defaults.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct TrailerOptions {
+    placement: Option<TrailerPlacement>,
+    if_exists: Option<TrailerIfExists>,
+    if_missing: Option<TrailerIfMissing>,
+}
+
+impl TrailerOptions {
+    /// Set where added trailers are placed.
+    pub(crate) fn placement(&mut self, placement: TrailerPlacement) -> &mut Self {
+        self.placement = Some(placement);
+        self
+    }
+
+    /// Set the action taken when a trailer with the same key already exists.
+    pub(crate) fn if_exists(&mut self, if_exists: TrailerIfExists) -> &mut Self {
+        self.if_exists = Some(if_exists);
+        self
+    }
+
+    /// Set the action taken when no trailer with the same key exists.
+    pub(crate) fn if_missing(&mut self, if_missing: TrailerIfMissing) -> &mut Self {
+        self.if_missing = Some(if_missing);
+        self
+    }
+
+    /// Command line arguments for `git interpret-trailers` for these options.
+    pub(super) fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(placement) = self.placement {
+            args.push(format!("--where={}", placement.as_str()));
+        }
+        if let Some(if_exists) = self.if_exists {
+            args.push(format!("--if-exists={}", if_exists.as_str()));
+        }
+        if let Some(if_missing) = self.if_missing {
+            args.push(format!("--if-missing={}", if_missing.as_str()));
+        }
+        args
+    }
+}
+
+/// Parse the output of `git interpret-trailers --parse` into (key, value) pairs.
+///
+/// Each line of output is a single, unfolded trailer with its key and value separated
+/// by a colon. Lines without a separator are ignored.
+pub(super) fn parse_trailer_lines(output: &[u8]) -> Vec<(String, String)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let line = line.to_str_lossy();
+            let (key, value) = line.split_once(':')?;
+            Some((key.trim().to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_options_have_no_args() {
+        assert!(TrailerOptions::default().args().is_empty());
+    }
+
+    #[test]
+    fn all_options_args() {
+        let mut options = TrailerOptions::default();
+        options
+            .placement(TrailerPlacement::End)
+            .if_exists(TrailerIfExists::AddIfDifferent)
+            .if_missing(TrailerIfMissing::DoNothing);
+        assert_eq!(
+            options.args(),
+            [
+                "--where=end",
+                "--if-exists=addIfDifferent",
+                "--if-missing=doNothing",
+            ]
+        );
+    }
+
+    #[test]
+    fn options_round_trip_through_strings() {
+        for placement in [
+            TrailerPlacement::After,
+            TrailerPlacement::Before,
+            TrailerPlacement::End,
+            TrailerPlacement::Start,
+        ] {
+            assert_eq!(
+                placement.as_str().parse::<TrailerPlacement>().unwrap(),
+                placement
+            );
+        }
+        assert_eq!(
+            "addIfDifferentNeighbor".parse::<TrailerIfExists>().unwrap(),
+            TrailerIfExists::AddIfDifferentNeighbor
+        );
+        assert_eq!(
+            "doNothing".parse::<TrailerIfMissing>().unwrap(),
+            TrailerIfMissing::DoNothing
+        );
+        assert!("middle".parse::<TrailerPlacement>().is_err());
+    }
+
+    #[test]
+    fn parse_trailer_output() {
+        assert_eq!(
+            parse_trailer_lines(
+                b"Signed-off-by: A U Thor <author@example.com>\n\
+                  Co-authored-by: Other Person <other@example.com>\n\
+                  Link: https://example.com/a:b\n"
+            ),
+            [
+                (
+                    "Signed-off-by".to_string(),
+                    "A U Thor <author@example.com>".to_string()
+                ),
+                (
+                    "Co-authored-by".to_string(),
+                    "Other Person <other@example.com>".to_string()
+                ),
+                ("Link".to_string(), "https://example.com/a:b".to_string()),
+            ]
+        );
+        assert!(parse_trailer_lines(b"").is_empty());
+    }
+}
@@ -1,8 +1,17 @@
+// SPDX-License-Identifier: GPL-2.0-only
+
+//! Parsed representation of `git version` output.
+
 use std::str::FromStr;
 
 use anyhow::{anyhow, Context};
 
-#[derive(Clone, Debug, PartialEq, PartialOrd)]
+/// Git version with major, minor, and micro components.
+///
+/// Versions are ordered by their numeric components. Any vendor suffix, e.g.
+/// `.windows.1` or `(Apple Git-137.1)`, is ignored. Pre-release versions (e.g.
+/// `-rc0`) retain their suffix in `extra`.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub(crate) struct StupidVersion {
     major: u16,
     minor: u16,
@@ -87,6 +96,8 @@ mod tests {
                 "2.17.123-rc0",
             ),
             (StupidVersion::new(2, 37, 1), "2.37.1 (Apple Git-137.1)"),
+            (StupidVersion::new(2, 39, 1), "2.39.1.windows.1"),
+            (StupidVersion::new(2, 45, 2), "2.45.2.vfs.0.0"),
             (
                 StupidVersion {
                     major: 2,
@@ -138,5 +149,9 @@ mod tests {
         assert!(v3_0_0_rc0 > v2_38_1);
         assert!(v3_0_0_rc0 < v3_0_0_rc1);
         assert!(v3_0_1_rc0 > v3_0_0_rc1);
+        assert_eq!(
+            [&v3_0_0_rc0, &v2_3_15, &v2_38_1, &v2_38_0].iter().max(),
+            Some(&&v3_0_0_rc0)
+        );
     }
 }
@@ -54,4 +54,28 @@ test_expect_success 'Test exec-path and subcommand relationship' '
     cat id-help.txt | grep -i -A1 "Usage:" | grep "stg id "
 '
 
+test_expect_success 'Config override is passed to git subprocesses' '
+    test_config stgit.alias.test-value "!git config test.value" &&
+    stg -c test.value=hello test-value >out &&
+    echo hello >expected &&
+    test_cmp expected out &&
+    stg -c test.value=hello -c "test.value=it'"'"'s !quoted" test-value >out &&
+    echo "it'"'"'s !quoted" >expected &&
+    test_cmp expected out
+'
+
+test_expect_success 'Config override applies to stg' '
+    stg init &&
+    stg -c stgit.namelength=4 new -m "long patch name" &&
+    test "$(stg top)" = "long" &&
+    test_config stgit.namelength 4 &&
+    stg -c stgit.namelength=0 new -m "long patch name" &&
+    test "$(stg top)" = "long-patch-name"
+'
+
+test_expect_success 'Invalid config override' '
+    command_error stg -c novalue top 2>err &&
+    grep -e "invalid config override .novalue." err
+'
+
 test_done
@@ -144,4 +144,50 @@ test_expect_success 'Multiple path limits' '
     grep -e "ccc\.txt" out
 '
 
+test_expect_success 'Show name-status' '
+    stg show --name-status many-paths >out &&
+    grep -e "^A	dir0/aaa\.txt$" out &&
+    grep -e "^A	dir0/dir1/ccc\.txt$" out &&
+    ! grep -e "^diff --git" out
+'
+
+test_expect_success 'Name-status conflicts with stat' '
+    general_error stg show --name-status --stat 2>err &&
+    grep -e "cannot be used with" err
+'
+
+test_expect_success 'Show with color forced' '
+    stg show --color=always many-paths >out &&
+    grep -e "$(printf "\033")" out &&
+    stg show --color=never many-paths >out &&
+    ! grep -e "$(printf "\033")" out
+'
+
+test_expect_success 'Show with auto color respects git config' '
+    test_config color.ui always &&
+    stg show --color=auto many-paths >out &&
+    grep -e "$(printf "\033")" out
+'
+
+test_expect_success 'Show renamed file with follow' '
+    stg new -m rename-source-patch &&
+    test_seq 1 20 >ren-src.txt &&
+    stg add ren-src.txt &&
+    stg refresh &&
+    stg new -m renamed-patch &&
+    git mv ren-src.txt ren-dst.txt &&
+    echo "21" >>ren-dst.txt &&
+    git add ren-dst.txt &&
+    stg refresh --index &&
+    test_config diff.renames false &&
+    stg show --name-status renamed-patch >out &&
+    grep -e "^D.ren-src.txt" out &&
+    stg show --name-status --follow renamed-patch >out &&
+    grep -e "^R[0-9]*.ren-src.txt.ren-dst.txt" out &&
+    stg show --name-status --follow renamed-patch -- ren-dst.txt >out &&
+    grep -e "^R[0-9]*.ren-src.txt.ren-dst.txt" out &&
+    stg show --name-status --follow renamed-patch -- ren-dst.txt ren-src.txt >out &&
+    grep -e "^R[0-9]*.ren-src.txt.ren-dst.txt" out
+'
+
 test_done
@@ -119,4 +119,79 @@ test_expect_success TTY 'Subcommand failure without color' '
    cat output | grep -v "<RED>"
 '
 
+test_expect_success 'Auto color is off through a pipe by default' '
+    (
+        TERM=xterm &&
+        export TERM &&
+        stg branch -l >output
+    ) &&
+    test_decode_color <output >decoded &&
+    ! grep "<RESET>" decoded
+'
+
+test_expect_success 'Redirected auto color ignores stgit.pager.color' '
+    test_config stgit.pager.color true &&
+    (
+        TERM=xterm &&
+        export TERM &&
+        stg branch -l >output
+    ) &&
+    test_decode_color <output >decoded &&
+    ! grep "<RESET>" decoded
+'
+
+test_expect_success 'Auto color under git-spawned pager with stgit.pager.color' '
+    test_config stgit.pager.color true &&
+    test_config color.pager false &&
+    (
+        TERM=xterm &&
+        GIT_PAGER_IN_USE=true &&
+        export TERM GIT_PAGER_IN_USE &&
+        stg branch -l >output
+    ) &&
+    test_decode_color <output >decoded &&
+    grep "<GREEN>master<RESET>" decoded &&
+    (
+        TERM=xterm &&
+        GIT_PAGER_IN_USE=true &&
+        export TERM GIT_PAGER_IN_USE &&
+        stg branch -l --color=never >output
+    ) &&
+    test_decode_color <output >decoded &&
+    ! grep "<RESET>" decoded
+'
+
+test_expect_success 'Auto color under git-spawned pager follows color.pager' '
+    (
+        TERM=xterm &&
+        GIT_PAGER_IN_USE=true &&
+        export TERM GIT_PAGER_IN_USE &&
+        stg branch -l >output
+    ) &&
+    test_decode_color <output >decoded &&
+    grep "<GREEN>master<RESET>" decoded &&
+    test_config color.pager false &&
+    (
+        TERM=xterm &&
+        GIT_PAGER_IN_USE=true &&
+        export TERM GIT_PAGER_IN_USE &&
+        stg branch -l >output
+    ) &&
+    test_decode_color <output >decoded &&
+    ! grep "<RESET>" decoded
+'
+
+test_expect_success 'stgit.pager.color takes precedence over color.pager' '
+    test_config color.pager true &&
+    test_config stgit.pager.color false &&
+    (
+        TERM=xterm &&
+        GIT_PAGER_IN_USE=true &&
+        export TERM GIT_PAGER_IN_USE &&
+        stg branch -l >output
+    ) &&
+    test_decode_color <output >decoded &&
+    ! grep "<RESET>" decoded
+'
+
 test_done
@@ -0,0 +1,149 @@
+#!/bin/sh
+
+test_description='Test stg completion helpers'
+
+. ./test-lib.sh
+
+test_expect_success 'Initialize StGit stack' '
+    stg init &&
+    stg new -m p0 &&
+    stg new -m p1 &&
+    stg new -m p2 &&
+    stg pop p2 &&
+    stg hide p2 &&
+    stg new -m p3 &&
+    stg pop p3
+'
+
+test_expect_success 'List patches' '
+    stg completion --list-patches >out &&
+    cat >expected <<-\EOF &&
+	p0
+	p1
+	p3
+	p2
+	EOF
+    test_cmp expected out
+'
+
+test_expect_success 'List selected patches' '
+    stg completion --list-patches --applied >out &&
+    printf "p0\np1\n" >expected &&
+    test_cmp expected out &&
+    stg completion --list-patches --unapplied --hidden >out &&
+    printf "p3\np2\n" >expected &&
+    test_cmp expected out
+'
+
+test_expect_success 'List patches with descriptions' '
+    stg completion --list-patches --applied --description >out &&
+    printf "p0\tp0\np1\tp1\n" >expected &&
+    test_cmp expected out
+'
+
+test_expect_success 'List patches of another branch' '
+    stg branch --create other &&
+    stg new -m q0 &&
+    stg completion --list-patches >out &&
+    echo q0 >expected &&
+    test_cmp expected out &&
+    stg completion --list-patches --branch=master >out &&
+    printf "p0\np1\np3\np2\n" >expected &&
+    test_cmp expected out
+'
+
+test_expect_success 'List patches is exclusive of subcommands' '
+    general_error stg completion --list-patches bash 2>err &&
+    grep -e "cannot be used with" err
+'
+
+test_expect_success 'Completion scripts use list patches' '
+    stg completion bash >stg.bash &&
+    grep -e "__stg completion --list-patches" stg.bash &&
+    ! grep -e "__stg series" stg.bash &&
+    stg completion fish >stg.fish &&
+    grep -e "__fish_stg completion --list-patches" stg.fish &&
+    ! grep -e "__fish_stg series" stg.fish &&
+    stg completion zsh >stg.zsh &&
+    grep -e "completion --list-patches" stg.zsh &&
+    ! grep -e "stg \${__stg_C_args} series" stg.zsh
+'
+
+test_expect_success 'Check man pages' '
+    stg completion man --output=man --aliases &&
+    stg completion man --output=man --aliases --check >out &&
+    test_must_be_empty out
+'
+
+test_expect_success 'Check stale man pages' '
+    echo stale >man/stg-new.txt &&
+    rm man/stg-aliases.txt &&
+    command_error stg completion man --output=man --aliases --check >out 2>err &&
+    printf "man/stg-new.txt\nman/stg-aliases.txt\n" >expected &&
+    test_cmp expected out &&
+    grep -e "2 man pages are out of date" err &&
+    test "$(cat man/stg-new.txt)" = "stale" &&
+    test_path_is_missing man/stg-aliases.txt
+'
+
+test_expect_success 'Check missing output directory' '
+    command_error stg completion man --output=no-such-dir --check >out &&
+    test_path_is_missing no-such-dir
+'
+
+test_expect_success 'Generate roff man pages' '
+    stg completion man --format=roff --output=roff --aliases &&
+    test_path_is_file roff/stg-aliases.1 &&
+    test_path_is_missing roff/stg-new.txt &&
+    head -n1 roff/stg-new.1 | grep -e "^\.TH \"STG\\\\-NEW\" \"1\"" &&
+    grep -e "^\.SH NAME" roff/stg-new.1 &&
+    grep -e "^\\\\fB\\\\-m\\\\fR" roff/stg-new.1 &&
+    stg completion man --format=roff --output=roff --aliases --check >out &&
+    test_must_be_empty out
+'
+
+test_expect_success 'Man pages end with exactly one newline' '
+    stg completion man --output=man &&
+    for page in man/stg-new.txt roff/stg-new.1
+    do
+        test "$(tail -c 1 $page | od -An -c | tr -d " ")" = "\\n" &&
+        test "$(tail -c 2 $page | od -An -c | tr -d " ")" != "\\n\\n" || return 1
+    done
+'
+
+test_expect_success 'Generate man pages without final newline' '
+    stg completion man --output=man-nonl --no-final-newline &&
+    test "$(tail -c 1 man-nonl/stg-new.txt | od -An -c | tr -d " ")" != "\\n" &&
+    stg completion man --output=man-nonl --no-final-newline --check >out &&
+    test_must_be_empty out &&
+    command_error stg completion man --output=man-nonl --check >out &&
+    grep -e "man-nonl/stg-new.txt" out
+'
+
+test_expect_success 'Man pages include StGit version' '
+    version=$(stg version --short | sed -e "s/^stg //") &&
+    stg completion man --output=man-ver &&
+    tail -n1 man-ver/stg-new.txt | grep -e "^StGit version $version$" &&
+    tail -n1 roff/stg-new.1 | grep -e "^StGit version $version$" &&
+    stg completion man --output=man-nover --no-version &&
+    ! grep -e "StGit version" man-nover/stg-new.txt &&
+    tail -n1 man-nover/stg-new.txt | grep -e "^Part of the StGit suite"
+'
+
+test_expect_success 'Generate index page' '
+    stg completion man --output=man-idx --no-version &&
+    test_path_is_missing man-idx/stg.txt &&
+    stg completion man --output=man-idx --no-version --index &&
+    test_path_is_file man-idx/stg.txt &&
+    head -n1 man-idx/stg.txt | grep -e "^stg(1)$" &&
+    grep -A1 -e "^linkstg:new\[\]::$" man-idx/stg.txt |
+    grep -e "^    Create a new patch" &&
+    test "$(grep -c -e "^linkstg:.*\[\]::$" man-idx/stg.txt)" = \
+         "$(ls man-idx/stg-*.txt | wc -l)" &&
+    stg completion man --output=man-idx --no-version --index --check >out &&
+    test_must_be_empty out &&
+    stg completion man --format=roff --output=roff --index &&
+    grep -e "^\\\\fBstg\\\\-new\\\\fR(1)$" roff/stg.1
+'
+
+test_done
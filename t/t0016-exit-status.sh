@@ -0,0 +1,63 @@
+#!/bin/sh
+
+test_description='Test the exit status of stg'
+
+. ./test-lib.sh
+
+test_expect_success 'Initialize StGit stack' '
+    echo base >a &&
+    stg add a &&
+    git commit -m base &&
+    stg new -m p1 &&
+    echo p1 >a &&
+    stg refresh &&
+    stg new -m p2 &&
+    echo p2 >a &&
+    stg refresh
+'
+
+test_expect_success 'Successful command exits with 0' '
+    test_expect_code 0 stg series
+'
+
+test_expect_success 'Command line error exits with 1' '
+    general_error stg top --no-such-option 2>err &&
+    grep -e "unexpected argument" err
+'
+
+test_expect_success 'Command failure exits with 2' '
+    command_error stg delete no-such-patch 2>err &&
+    grep -e "patch \`no-such-patch\` does not exist" err
+'
+
+test_expect_success 'Command failure with --json reports JSON diagnostics' '
+    command_error stg --json delete no-such-patch 2>err &&
+    test "$(cat err)" = "{\"error\":\"patch \`no-such-patch\` does not exist\"}"
+'
+
+test_expect_success 'Merge conflict exits with 3' '
+    stg pop p1 p2 &&
+    conflict stg push p2 &&
+    stg undo --hard &&
+    test "$(echo $(stg series --unapplied --noprefix))" = "p1 p2"
+'
+
+test_expect_success 'Push merge blocked by local changes exits with 3' '
+    stg push p1 &&
+    echo p1-updated >a &&
+    stg refresh &&
+    echo dirty >a &&
+    conflict stg push --keep p2 &&
+    test "$(echo $(stg series --applied --noprefix))" = "p1" &&
+    test "$(cat a)" = "dirty"
+'
+
+test_expect_success 'Rolled back checkout blocked by local changes exits with 2' '
+    command_error stg pop --keep 2>err &&
+    grep -e "checkout would overwrite local changes to \`a\`" err &&
+    grep -e "command aborted (all changes rolled back)" err &&
+    test "$(echo $(stg series --applied --noprefix))" = "p1" &&
+    test "$(cat a)" = "dirty"
+'
+
+test_done
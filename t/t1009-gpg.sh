@@ -145,4 +145,69 @@ test_expect_success GPG 'Using invalid user.signingkey causes failure' '
     test "$(stg top)" = "p1"
 '
 
+test_expect_success GPG 'Squashing signed patches warns and honors commit.gpgsign' '
+    git config commit.gpgsign true &&
+    git config user.signingkey ${GIT_COMMITTER_EMAIL} &&
+    stg new -m s0 &&
+    stg new -m s1 &&
+    git verify-commit $(stg id s0) &&
+    stg squash -m s01 --name=s01 s0 s1 2>err &&
+    grep -e "GPG signatures of squashed patches are not preserved: .s0., .s1." err &&
+    git verify-commit $(stg id s01)
+'
+
+test_expect_success GPG 'Squash with --no-gpg-sign' '
+    stg new -m s2 &&
+    stg squash -m s012 --name=s012 --no-gpg-sign s01 s2 2>err &&
+    grep -e "not preserved: .s01., .s2." err &&
+    test_must_fail git verify-commit $(stg id s012)
+'
+
+test_expect_success GPG 'Squash with --gpg-sign' '
+    test_unconfig commit.gpgsign &&
+    stg new -m s3 &&
+    stg squash -m s0123 --name=s0123 --gpg-sign s012 s3 2>err &&
+    ! grep -e "not preserved" err &&
+    git verify-commit $(stg id s0123)
+'
+
+test_expect_success GPG 'Squash with --gpg-sign=<keyid>' '
+    test_unconfig commit.gpgsign &&
+    test_config user.signingkey invalid@example.com &&
+    stg new -m s4 &&
+    stg squash -m s01234 --name=s01234 --gpg-sign=discord@example.net s0123 s4 &&
+    git verify-commit $(stg id s01234) 2>verify &&
+    grep -e "Eris Discordia" verify
+'
+
+test_expect_success GPG 'Squash with invalid --gpg-sign=<keyid>' '
+    stg new -m s5 &&
+    command_error stg squash -m s012345 --gpg-sign=invalid@example.com s01234 s5 &&
+    test "$(stg top)" = "s5" &&
+    stg id s01234
+'
+
+test_expect_success GPG 'Signing commits does not allow spawning an editor' '
+    write_script check-editor <<-\EOF &&
+	test "$GIT_EDITOR" = ":" || exit 1
+	exec gpg "$@"
+	EOF
+    test_config commit.gpgsign true &&
+    test_config user.signingkey ${GIT_COMMITTER_EMAIL} &&
+    test_config gpg.program "$PWD/check-editor" &&
+    GIT_EDITOR=false stg new -m editor-guard &&
+    git -c gpg.program=gpg verify-commit $(stg id editor-guard)
+'
+
+test_expect_success GPG 'Signed patch preserves date offsets' '
+    test_config commit.gpgsign true &&
+    test_config user.signingkey ${GIT_COMMITTER_EMAIL} &&
+    GIT_AUTHOR_DATE="1641479527 +0530" GIT_COMMITTER_DATE="1641479600 -0230" \
+        stg new -m date-offsets &&
+    git verify-commit $(stg id date-offsets) &&
+    git cat-file commit $(stg id date-offsets) >commit &&
+    grep -e "^author .* 1641479527 +0530$" commit &&
+    grep -e "^committer .* 1641479600 -0230$" commit
+'
+
 test_done
@@ -46,4 +46,80 @@ test_expect_success 'Resolve the conflict' '
     stg refresh
 '
 
+test_expect_success 'Setup patches for merge tool' '
+    stg new mt-a -m mt-a &&
+    echo a >test3 &&
+    stg add test3 &&
+    stg refresh &&
+    stg pop &&
+    stg new mt-b -m mt-b &&
+    echo b >test3 &&
+    stg add test3 &&
+    stg refresh
+'
+
+test_expect_success 'Push with conflicts unresolved by merge tool' '
+    test_config stgit.autoimerge true &&
+    test_config merge.tool fake &&
+    test_config mergetool.fake.cmd false &&
+    test_config mergetool.fake.trustExitCode true &&
+    test_config mergetool.prompt false &&
+    conflict stg push mt-a &&
+    test "$(git diff --name-only --diff-filter=U)" = "test3" &&
+    stg undo --hard
+'
+
+test_expect_success 'Push with conflicts resolved by merge tool' '
+    test_config stgit.autoimerge true &&
+    test_config merge.tool fake &&
+    test_config mergetool.fake.cmd "cp \"\$REMOTE\" \"\$MERGED\"" &&
+    test_config mergetool.fake.trustExitCode true &&
+    test_config mergetool.keepBackup false &&
+    test_config mergetool.prompt false &&
+    stg push mt-a &&
+    test "$(stg top)" = "mt-a" &&
+    test "$(cat test3)" = "a"
+'
+
+test_expect_success 'Setup patches for conflict style' '
+    echo base >test4 &&
+    stg add test4 &&
+    stg new cs-base -m cs-base &&
+    stg refresh &&
+    stg new cs-a -m cs-a &&
+    echo a >test4 &&
+    stg refresh &&
+    stg pop &&
+    stg new cs-b -m cs-b &&
+    echo b >test4 &&
+    stg refresh
+'
+
+test_expect_success 'Push with diff3 conflict style' '
+    conflict stg push --conflict-style=diff3 cs-a &&
+    test_when_finished "stg undo --hard" &&
+    grep -e "^|||||||" test4 &&
+    grep -e "^base$" test4
+'
+
+test_expect_success 'Conflict style option overrides config' '
+    test_config merge.conflictStyle diff3 &&
+    conflict stg push --conflict-style=merge cs-a &&
+    test_when_finished "stg undo --hard" &&
+    grep -e "^<<<<<<<" test4 &&
+    ! grep -e "^|||||||" test4
+'
+
+test_expect_success 'Push honors conflict style config by default' '
+    test_config merge.conflictStyle diff3 &&
+    conflict stg push cs-a &&
+    test_when_finished "stg undo --hard" &&
+    grep -e "^|||||||" test4
+'
+
+test_expect_success 'Invalid conflict style' '
+    general_error stg push --conflict-style=bogus cs-a 2>err &&
+    grep -e "invalid conflict style .bogus." err
+'
+
 test_done
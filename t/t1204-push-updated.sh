@@ -61,4 +61,18 @@ test_expect_success 'Push indicates p1 was not modified' '
     test_cmp expected out
 '
 
+test_expect_success 'Push queries git version at most once' '
+    stg pop -a &&
+    echo "qux" >qux.txt &&
+    git add qux.txt &&
+    git commit -m "qux" &&
+    mkdir wrapper &&
+    write_script wrapper/git <<-EOF &&
+	test "\$1" = version && echo version >>"$PWD/version-calls"
+	exec "$(command -v git)" "\$@"
+	EOF
+    PATH="$PWD/wrapper:$PATH" stg push -a &&
+    test_line_count = 1 version-calls
+'
+
 test_done
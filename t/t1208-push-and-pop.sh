@@ -139,4 +139,15 @@ test_expect_success 'Attempt to push already applied patches' '
     grep -e "patch \`p99999\` does not exist" err
 '
 
+test_expect_success 'Push patch with diff larger than a pipe buffer' '
+    test_seq 1 100000 >big.txt &&
+    stg add big.txt &&
+    stg new -rm "big-patch" &&
+    stg sink big-patch &&
+    [ "$(echo $(stg series --applied --noprefix))" = "big-patch p0 p1 p2 p3 p5 p6 p8 p7" ] &&
+    test_seq 1 100000 >expected &&
+    git show "$(stg id big-patch):big.txt" >actual &&
+    test_cmp expected actual
+'
+
 test_done
@@ -125,9 +125,27 @@ test_expect_success 'Uncommit when top != head' '
     test "$(echo $(stg series))" = "+ bar > foo"
 '
 
+test_expect_success 'Uncommit to a commit that is not an ancestor' '
+    git checkout -b side HEAD^ &&
+    git commit --allow-empty -m side-commit &&
+    git checkout master &&
+    command_error stg uncommit --to side 2>err &&
+    grep -e "error: target \`side\` is not an ancestor of the stack base" err &&
+    test "$(echo $(stg series --applied --noprefix))" = "bar foo" &&
+    git branch -D side
+'
+
 test_expect_success 'Uncommit to something that does not resolve to a commit' '
     command_error stg uncommit --to HEAD^{tree} 2>err &&
-    grep -e "error: target \`HEAD^{tree}\` does not resolve to a commit" err
+    grep -e "error: \`HEAD^{tree}\` does not resolve to a commit" err &&
+    command_error stg uncommit --to HEAD:test 2>err &&
+    grep -e "error: \`HEAD:test\` does not resolve to a commit" err
+'
+
+test_expect_success 'Uncommit to an invalid revision' '
+    command_error stg uncommit --to no-such-rev 2>err &&
+    grep -e "error: invalid revision \`no-such-rev\`" err &&
+    test "$(echo $(stg series --applied --noprefix))" = "bar foo"
 '
 
 test_expect_success 'Uncommit to an annotated tag' '
@@ -57,4 +57,25 @@ test_expect_success 'Encoding after edit' '
     git cat-file -p HEAD | grep -e "Signed-off-by: C Ó Mitter <committer@example.com>"
 '
 
+test_expect_success 'Author date offset survives non-utf-8 commit' '
+    test_config i18n.commitencoding ISO8859-1 &&
+    stg edit --authdate "2013-01-28 22:30:00 +0530" &&
+    git cat-file -p HEAD | grep -e "^encoding windows-1252" &&
+    test "$(git log -1 --pretty=format:%ai)" = "2013-01-28 22:30:00 +0530"
+'
+
+test_expect_success !WINDOWS 'New patch round-trips non-utf-8 message' '
+    test_config i18n.commitencoding ISO8859-1 &&
+    stg new -m "Ünïcödé sübject" encoded-patch &&
+    git cat-file commit HEAD >commit-data.txt &&
+    grep -e "^encoding windows-1252" commit-data.txt &&
+    sed -n "/^$/,\$p" commit-data.txt | sed 1d | iconv -f ISO8859-1 -t UTF-8 >message.txt &&
+    echo "Ünïcödé sübject" >expected-message.txt &&
+    test_cmp expected-message.txt message.txt &&
+    test "$(git -c i18n.logOutputEncoding=UTF-8 log -1 --pretty=format:%s)" = "Ünïcödé sübject" &&
+    stg edit --save-template template.txt encoded-patch &&
+    sed -n 5p template.txt >message.txt &&
+    test_cmp expected-message.txt message.txt
+'
+
 test_done
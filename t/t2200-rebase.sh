@@ -61,4 +61,19 @@ test_expect_success 'Rebase without argument' '
     grep bar file2
 '
 
+test_expect_success 'Git commands do not prompt when run non-interactively' '
+    write_script rebase-cmd <<-\EOF &&
+	echo "${GIT_TERMINAL_PROMPT-unset}" >>prompt-env &&
+	exec git reset --hard "$@"
+	EOF
+    test_config stgit.rebasecmd ./rebase-cmd &&
+    stg rebase master~1 </dev/null 2>err &&
+    echo 0 >expected &&
+    test_cmp expected prompt-env &&
+    rm prompt-env &&
+    GIT_TERMINAL_PROMPT=1 stg rebase master </dev/null 2>err &&
+    echo 1 >expected &&
+    test_cmp expected prompt-env
+'
+
 test_done
@@ -221,6 +221,20 @@ test_expect_success 'Fix succeeds' '
     test "$(stg series -c)" = "1"
 '
 
+test_expect_success 'Fix patches with different authors' '
+    stg delete $(stg series --all --noprefix --no-description) &&
+    stg new -m p0 &&
+    stg new -m p1 --author "Other Contributor <another@example.com>" &&
+    write_script fake-editor <<-\EOF &&
+	printf "keep p0\nfix p1\n" >"$1"
+	EOF
+    test_set_editor "$(pwd)/fake-editor" &&
+    test_when_finished test_set_editor false &&
+    stg rebase --interactive &&
+    git diff-index --quiet HEAD &&
+    test "$(stg series -c)" = "1"
+'
+
 test_expect_success 'Two independent squash chains succeed' '
     stg delete $(stg series --all --noprefix --no-description) &&
     stg new -m p0 &&
@@ -12,11 +12,59 @@ test_expect_success 'Initialize StGit stack' '
     done
 '
 
+# Create patches named a-patch, b-patch, and so on, each appending to baz.txt. Each
+# argument is the author of the respective patch, with "-" for the default author.
+new_authored_patches () {
+    for p in a b c
+    do
+        test $# -gt 0 || break
+        echo "$p" >>baz.txt &&
+        if test "$1" = "-"
+        then
+            stg new -rm "$p-patch"
+        else
+            stg new -rm "$p-patch" --author "$1"
+        fi || return 1
+        shift
+    done
+}
+
+OTHER="Other Contributor <another@example.com>"
+THIRD="Third Person <third@example.com>"
+
+test_expect_success 'Setup no-op editor' '
+    write_script noop-editor <<-\EOF
+	true
+	EOF
+'
+
 test_expect_success 'Too few arguments' '
     command_error stg squash p0 2>err &&
     grep -e "need at least two patches" err
 '
 
+test_expect_success 'Squash single patch with --allow-single' '
+    stg new -m "single-patch" &&
+    echo single >single.txt &&
+    stg add single.txt &&
+    stg refresh &&
+    EDITOR=./noop-editor stg squash --allow-single --edit --author "$OTHER" single-patch &&
+    test "$(echo $(stg series --applied --noprefix | tail -n 1))" = "single-patch" &&
+    git log -1 --format="%an <%ae>" $(stg id single-patch) >out &&
+    echo "Other Contributor <another@example.com>" >expected &&
+    test_cmp expected out &&
+    git log -1 --format=%B $(stg id single-patch) >out &&
+    cat >expected <<-\EOF &&
+	single-patch
+
+	Co-authored-by: A Ú Thor <author@example.com>
+
+	EOF
+    test_cmp expected out &&
+    test "$(cat single.txt)" = "single" &&
+    stg delete single-patch
+'
+
 test_expect_success 'Attempt duplicate patch name' '
     command_error stg squash -n p3 -- p0 p1 2>err &&
     grep -e "patch name \`p3\` already taken" err
@@ -27,17 +75,81 @@ test_expect_success 'Attempt invalid patch name' '
     grep -e "invalid value .invalid..name. for .--name <name>.: invalid patch name" err
 '
 
+test_expect_success 'Attempt patch names with disallowed content' '
+    general_error stg squash -n "bad@{name" -- p0 p1 2>err &&
+    grep -e "patch name may not contain .@{." err &&
+    general_error stg squash -n "$(printf "bad\001name")" -- p0 p1 2>err &&
+    grep -e "patch name may not contain control characters" err &&
+    command_error stg squash -n -bad -- p0 p1 2>err &&
+    grep -e "patch name \`-bad\` may not start with .-." err
+'
+
 test_expect_success 'Attempt out of order' '
     conflict stg squash --name=q4 p5 p4 &&
     stg undo --hard
 '
 
+test_expect_success 'Squash out of order with strategy option' '
+    stg squash --name=q4 --strategy=theirs -m q4 p5 p4 &&
+    test "$(echo $(stg series --applied --noprefix))" = "p0 p1 p2 p3 q4" &&
+    test "$(cat foo.txt)" = "foo 4" &&
+    stg undo
+'
+
+test_expect_success 'Squash out of order with --ours or --theirs' '
+    stg squash --name=q4 --theirs -m q4 p5 p4 &&
+    test "$(echo $(stg series --applied --noprefix))" = "p0 p1 p2 p3 q4" &&
+    test "$(cat foo.txt)" = "foo 4" &&
+    stg undo &&
+    stg squash --name=q4 --ours -m q4 p5 p4 >out &&
+    grep -e "^+ p5 (empty)$" out &&
+    test "$(echo $(stg series --applied --noprefix))" = "p0 p1 p2 p3 q4" &&
+    test "$(cat foo.txt)" = "foo 4" &&
+    stg undo &&
+    general_error stg squash --ours --theirs -m q4 p5 p4 2>err &&
+    grep -e "cannot be used with" err &&
+    general_error stg squash --ours --strategy=theirs -m q4 p5 p4 2>err &&
+    grep -e "cannot be used with" err
+'
+
+test_expect_success 'Squash strategy option does not apply to other pushes' '
+    conflict stg squash --name=q3 --theirs -m q3 p4 p3 >out &&
+    grep -e "^> p5 (conflict)$" out &&
+    test "$(echo $(stg series --applied --noprefix))" = "p0 p1 p2 q3 p5" &&
+    test "$(git show $(stg id q3):foo.txt)" = "foo 3" &&
+    stg undo --hard &&
+    test "$(echo $(stg series --applied --noprefix))" = "p0 p1 p2 p3 p4 p5"
+'
+
+test_expect_success 'Squash conflict style does not apply to other pushes' '
+    conflict stg squash --name=q3 --theirs --conflict-style=diff3 -m q3 p4 p3 &&
+    grep -e "^<<<<<<< " foo.txt &&
+    ! grep -e "^||||||| " foo.txt &&
+    stg undo --hard &&
+    test "$(echo $(stg series --applied --noprefix))" = "p0 p1 p2 p3 p4 p5"
+'
+
+test_expect_success 'Squash fallback reports push progress' '
+    stg squash --name=q4 --strategy=theirs -m q4 p5 p4 >out &&
+    test_when_finished "stg undo" &&
+    cat >expected <<-\EOF &&
+	pushing p5 [1/2]
+	pushing p4 [2/2]
+	EOF
+    grep -e "^pushing " out >actual &&
+    test_cmp expected actual &&
+    stg undo &&
+    stg squash --quiet --name=q4 --strategy=theirs -m q4 p5 p4 >out &&
+    ! grep -e "^pushing " out
+'
+
 test_expect_success 'Squash out of order no conflict' '
     echo hello >bar.txt &&
     stg add bar.txt &&
     stg new -m bar-patch &&
     stg refresh &&
-    stg squash -n q5 bar-patch p5 &&
+    stg squash -n q5 bar-patch p5 2>err &&
+    ! grep -e "not contiguous" err &&
     [ "$(echo $(stg series --applied --noprefix))" = "p0 p1 p2 p3 p4 q5" ]
 '
 
@@ -72,12 +184,7 @@ test_expect_success 'Squash at stack top' '
 '
 
 test_expect_success 'Squash patches with all non-default author' '
-    echo "a" >>baz.txt &&
-    stg new -rm "a-patch" --author "Other Contributor <another@example.com>" &&
-    echo "b" >>baz.txt &&
-    stg new -rm "b-patch" --author "Other Contributor <another@example.com>" &&
-    echo "c" >>baz.txt &&
-    stg new -rm "c-patch" --author "Other Contributor <another@example.com>" &&
+    new_authored_patches "$OTHER" "$OTHER" "$OTHER" &&
     stg squash -m "abc-patch" a-patch b-patch c-patch &&
     test_when_finished "stg delete abc-patch" &&
     stg show abc-patch | grep "Author:" >out &&
@@ -88,12 +195,7 @@ test_expect_success 'Squash patches with all non-default author' '
 '
 
 test_expect_success 'Squash patches with some non-default author' '
-    echo "a" >>baz.txt &&
-    stg new -rm "a-patch" &&
-    echo "b" >>baz.txt &&
-    stg new -rm "b-patch" --author "Other Contributor <another@example.com>" &&
-    echo "c" >>baz.txt &&
-    stg new -rm "c-patch" &&
+    new_authored_patches - "$OTHER" - &&
     stg squash -m "abc-patch" a-patch b-patch c-patch &&
     test_when_finished "stg delete abc-patch" &&
     stg show abc-patch | grep "Author:" >out &&
@@ -104,12 +206,7 @@ test_expect_success 'Squash patches with some non-default author' '
 '
 
 test_expect_success 'Squash patches with author override' '
-    echo "a" >>baz.txt &&
-    stg new -rm "a-patch" --author "Other Contributor <another@example.com>" &&
-    echo "b" >>baz.txt &&
-    stg new -rm "b-patch" --author "Other Contributor <another@example.com>" &&
-    echo "c" >>baz.txt &&
-    stg new -rm "c-patch" --author "Other Contributor <another@example.com>" &&
+    new_authored_patches "$OTHER" "$OTHER" "$OTHER" &&
     stg squash -m "abc-patch" --author "Override Author <override@example.com>" a-patch b-patch c-patch &&
     test_when_finished "stg delete abc-patch" &&
     stg show abc-patch | grep "Author:" >out &&
@@ -119,6 +216,409 @@ test_expect_success 'Squash patches with author override' '
     test_cmp expected out
 '
 
+test_expect_success 'Squash patches with author override adds displaced author as co-author' '
+    new_authored_patches "$OTHER" "$OTHER" &&
+    EDITOR=./noop-editor stg squash -e --name=ab-patch \
+        --author "Override Author <override@example.com>" a-patch b-patch &&
+    test_when_finished "stg delete ab-patch" &&
+    git log -1 --format="%an <%ae>" $(stg id ab-patch) >out &&
+    echo "Override Author <override@example.com>" >expected &&
+    test_cmp expected out &&
+    git log -1 --format=%B $(stg id ab-patch) >out &&
+    cat >expected <<-\EOF &&
+	a-patch
+
+	b-patch
+
+	Co-authored-by: Other Contributor <another@example.com>
+
+	EOF
+    test_cmp expected out
+'
+
+test_expect_success 'Squash patches with author override matching an original author' '
+    new_authored_patches "$OTHER" - &&
+    EDITOR=./noop-editor stg squash -e --name=ab-patch \
+        --author "$OTHER" a-patch b-patch &&
+    test_when_finished "stg delete ab-patch" &&
+    git log -1 --format=%B $(stg id ab-patch) >out &&
+    grep -e "^Co-authored-by: A Ú Thor <author@example.com>$" out &&
+    ! grep -e "Co-authored-by: Other Contributor" out
+'
+
+test_expect_success 'Squash patches with multiple authors adds co-authors' '
+    new_authored_patches "$OTHER" "$THIRD" - &&
+    EDITOR=./noop-editor stg squash --name=abc-patch a-patch b-patch c-patch &&
+    test_when_finished "stg delete abc-patch" &&
+    git log -1 --format=%B $(stg id abc-patch) >out &&
+    cat >expected <<-\EOF &&
+	a-patch
+
+	b-patch
+
+	c-patch
+
+	Co-authored-by: Other Contributor <another@example.com>
+	Co-authored-by: Third Person <third@example.com>
+
+	EOF
+    test_cmp expected out
+'
+
+test_expect_success 'Squash patches reports authorship summary' '
+    new_authored_patches "$OTHER" "$THIRD" "$OTHER" &&
+    stg squash --name=abc-patch -m "abc" a-patch b-patch c-patch 2>err &&
+    test_when_finished "stg delete abc-patch" &&
+    grep -e "squashed patch authored by A Ú Thor <author@example.com>; co-authors: Other Contributor <another@example.com> (2), Third Person <third@example.com> (1)" err
+'
+
+test_expect_success 'Squash patches authorship summary suppressed when quiet' '
+    new_authored_patches "$OTHER" - &&
+    stg squash --quiet --name=ab-patch -m "ab" a-patch b-patch 2>err &&
+    test_when_finished "stg delete ab-patch" &&
+    test_must_be_empty err &&
+    git log -1 --format=%B $(stg id ab-patch) >out &&
+    grep -e "^Co-authored-by: Other Contributor <another@example.com>$" out
+'
+
+test_expect_success 'Squash patches does not repeat existing co-author trailers' '
+    new_authored_patches "$OTHER" &&
+    echo "b" >>baz.txt &&
+    stg new -r -m "b-patch
+
+Co-authored-by: Other Contributor <another@example.com>" &&
+    EDITOR=./noop-editor stg squash --name=ab-patch a-patch b-patch &&
+    test_when_finished "stg delete ab-patch" &&
+    git log -1 --format=%B $(stg id ab-patch) >out &&
+    grep -c "^Co-authored-by: Other Contributor <another@example.com>$" out >count &&
+    echo 1 >expected &&
+    test_cmp expected count
+'
+
+test_expect_success 'Squash patches adds co-authors to existing trailer block' '
+    new_authored_patches "$OTHER" &&
+    echo "b" >>baz.txt &&
+    stg new -r -m "b-patch
+
+Signed-off-by: A Ú Thor <author@example.com>
+
+
+" &&
+    EDITOR=./noop-editor stg squash --name=ab-patch a-patch b-patch &&
+    test_when_finished "stg delete ab-patch" &&
+    git log -1 --format=%B $(stg id ab-patch) >out &&
+    cat >expected <<-\EOF &&
+	a-patch
+
+	b-patch
+
+	Signed-off-by: A Ú Thor <author@example.com>
+	Co-authored-by: Other Contributor <another@example.com>
+
+	EOF
+    test_cmp expected out
+'
+
+test_expect_success 'Squash patches with multiple authors without co-authors' '
+    new_authored_patches "$OTHER" - &&
+    EDITOR=./noop-editor stg squash --no-coauthors --name=ab-patch a-patch b-patch &&
+    test_when_finished "stg delete ab-patch" &&
+    git log -1 --format=%B $(stg id ab-patch) >out &&
+    ! grep -e "Co-authored-by" out &&
+    stg show ab-patch | grep "Author:" >out &&
+    cat >expected <<-\EOF &&
+	Author: A Ú Thor <author@example.com>
+	EOF
+    test_cmp expected out
+'
+
+test_expect_success 'Squash patches with configured co-author trailer key' '
+    test_config stgit.squash.coauthortrailer Co-developed-by &&
+    new_authored_patches "$OTHER" - &&
+    EDITOR=./noop-editor stg squash --name=ab-patch a-patch b-patch &&
+    test_when_finished "stg delete ab-patch" &&
+    git log -1 --format=%B $(stg id ab-patch) >out &&
+    grep -e "^Co-developed-by: Other Contributor <another@example.com>$" out &&
+    ! grep -e "Co-authored-by" out
+'
+
+test_expect_success 'Squash with invalid co-author trailer key' '
+    test_config stgit.squash.coauthortrailer "Co authored" &&
+    new_authored_patches "$OTHER" - &&
+    test_when_finished "stg delete a-patch b-patch" &&
+    command_error env EDITOR=./noop-editor stg squash --name=ab-patch a-patch b-patch 2>err &&
+    grep -e "invalid trailer key \`Co authored\` for \`stgit.squash.coauthortrailer\`" err
+'
+
+test_expect_success 'Squash with message from stdin adds co-authors' '
+    new_authored_patches "$OTHER" - &&
+    printf "Supplied subject\n\nSupplied body.\n" |
+    stg squash --file - --name=ab-patch a-patch b-patch &&
+    test_when_finished "stg delete ab-patch" &&
+    git log -1 --format=%B $(stg id ab-patch) >out &&
+    cat >expected <<-\EOF &&
+	Supplied subject
+
+	Supplied body.
+
+	Co-authored-by: Other Contributor <another@example.com>
+
+	EOF
+    test_cmp expected out
+'
+
+test_expect_success 'Squash with message file without co-authors' '
+    new_authored_patches "$OTHER" - &&
+    echo "Supplied subject" >msg-file &&
+    stg squash --file msg-file --no-coauthors --name=ab-patch a-patch b-patch &&
+    test_when_finished "stg delete ab-patch" &&
+    git log -1 --format=%B $(stg id ab-patch) >out &&
+    printf "Supplied subject\n\n" >expected &&
+    test_cmp expected out
+'
+
+test_expect_success 'Squash message file conflicts with message' '
+    general_error stg squash --file msg-file -m "msg" p0 p1 2>err &&
+    grep -e "cannot be used with" err
+'
+
+test_expect_success 'Squash patches and message cannot both come from stdin' '
+    general_error stg squash --stdin --file - 2>err &&
+    grep -e "the argument .--stdin. cannot be used with .--file -." err
+'
+
+test_expect_success 'Squash with signoff does not duplicate existing signoff' '
+    new_authored_patches - - &&
+    stg squash --signoff -m "ab-patch
+
+Signed-off-by: C Ó Mitter <committer@example.com>" --name=ab-patch a-patch b-patch &&
+    test_when_finished "stg delete ab-patch" &&
+    git log -1 --format=%B $(stg id ab-patch) >out &&
+    cat >expected <<-\EOF &&
+	ab-patch
+
+	Signed-off-by: C Ó Mitter <committer@example.com>
+
+	EOF
+    test_cmp expected out
+'
+
+test_expect_success 'Squash with signoff after co-authors' '
+    new_authored_patches "$OTHER" - &&
+    EDITOR=./noop-editor stg squash --edit --signoff --name=ab-patch a-patch b-patch &&
+    test_when_finished "stg delete ab-patch" &&
+    git log -1 --format=%B $(stg id ab-patch) >out &&
+    cat >expected <<-\EOF &&
+	a-patch
+
+	b-patch
+
+	Co-authored-by: Other Contributor <another@example.com>
+	Signed-off-by: C Ó Mitter <committer@example.com>
+
+	EOF
+    test_cmp expected out
+'
+
+test_expect_success 'Squash with signoff ignores trailer.where' '
+    new_authored_patches "$OTHER" - &&
+    test_config trailer.where start &&
+    EDITOR=./noop-editor stg squash --edit --signoff --name=ab-patch a-patch b-patch &&
+    test_when_finished "stg delete ab-patch" &&
+    git log -1 --format=%B $(stg id ab-patch) >out &&
+    cat >expected <<-\EOF &&
+	a-patch
+
+	b-patch
+
+	Co-authored-by: Other Contributor <another@example.com>
+	Signed-off-by: C Ó Mitter <committer@example.com>
+
+	EOF
+    test_cmp expected out
+'
+
+test_expect_success 'Squash with porcelain output' '
+    stg new -m "x-patch" &&
+    stg new -m "y-patch" &&
+    test_when_finished "stg delete xy-patch" &&
+    stg squash --porcelain --name=xy-patch -m "xy" x-patch y-patch >out 2>err &&
+    echo "squashed x-patch,y-patch -> xy-patch $(git rev-parse --short $(stg id xy-patch))" >expected &&
+    test_cmp expected out &&
+    test_must_be_empty err
+'
+
+test_expect_success 'Squash with unreadable template' '
+    stg new -m "x-patch" &&
+    stg new -m "y-patch" &&
+    command_error stg squash --template=no-such-file -m xy x-patch y-patch 2>err &&
+    grep -e "reading template .no-such-file." err &&
+    test "$(echo $(stg series --applied --noprefix | tail -n 2))" = "x-patch y-patch"
+'
+
+test_expect_success 'Squash with template' '
+    cat >squash-template <<-\EOF &&
+	Team subject
+
+	Team body
+	EOF
+    write_script fake-editor <<-\EOF &&
+	cp "$1" editor-input
+	EOF
+    EDITOR=./fake-editor stg squash --template=squash-template --name=xy-patch x-patch y-patch &&
+    test_when_finished "stg delete xy-patch" &&
+    grep -e "^# x-patch" editor-input &&
+    grep -e "^# y-patch" editor-input &&
+    git log -1 --format=%B $(stg id xy-patch) >out &&
+    cat >expected <<-\EOF &&
+	Team subject
+
+	Team body
+
+	EOF
+    test_cmp expected out
+'
+
+test_expect_success 'Squash with template and message' '
+    stg new -m "x-patch" &&
+    stg new -m "y-patch" &&
+    stg squash --template=squash-template -m "explicit message" --name=xy-patch x-patch y-patch &&
+    test_when_finished "stg delete xy-patch" &&
+    git log -1 --format=%B $(stg id xy-patch) >out &&
+    printf "explicit message\n\n" >expected &&
+    test_cmp expected out
+'
+
+test_expect_success 'Squash with commit.template' '
+    stg new -m "x-patch" &&
+    stg new -m "y-patch" &&
+    test_config commit.template squash-template &&
+    EDITOR=./noop-editor stg squash --name=xy-patch x-patch y-patch &&
+    test_when_finished "stg delete xy-patch" &&
+    git log -1 --format=%B $(stg id xy-patch) >out &&
+    cat >expected <<-\EOF &&
+	Team subject
+
+	Team body
+
+	x-patch
+
+	y-patch
+
+	EOF
+    test_cmp expected out
+'
+
+test_expect_success 'Squash ignores commit.template with explicit message or template' '
+    test_config commit.template squash-template &&
+    stg new -m "x-patch" &&
+    stg new -m "y-patch" &&
+    stg squash -m "explicit message" --name=xy-patch x-patch y-patch &&
+    git log -1 --format=%B $(stg id xy-patch) >out &&
+    printf "explicit message\n\n" >expected &&
+    test_cmp expected out &&
+    stg delete xy-patch &&
+    printf "Other template\n" >other-template &&
+    stg new -m "x-patch" &&
+    stg new -m "y-patch" &&
+    EDITOR=./noop-editor stg squash --template=other-template --name=xy-patch x-patch y-patch &&
+    test_when_finished "stg delete xy-patch" &&
+    git log -1 --format=%B $(stg id xy-patch) >out &&
+    printf "Other template\n\n" >expected &&
+    test_cmp expected out
+'
+
+test_expect_success 'Squash with missing commit.template file' '
+    test_config commit.template no-such-template &&
+    stg new -m "x-patch" &&
+    stg new -m "y-patch" &&
+    test_when_finished "stg delete x-patch y-patch" &&
+    command_error stg squash --name=xy-patch x-patch y-patch 2>err &&
+    grep -e "reading \`commit.template\` file" err
+'
+
+test_expect_success 'Squash quietly' '
+    stg new -m "x-patch" &&
+    stg new -m "y-patch" &&
+    test_when_finished "stg delete xy-patch" &&
+    stg squash --quiet --name=xy-patch -m "xy" x-patch y-patch >out 2>err &&
+    test_must_be_empty out &&
+    test_must_be_empty err &&
+    test "$(stg top)" = "xy-patch"
+'
+
+test_expect_success 'Save template quietly' '
+    test_when_finished "rm -f quiet-template" &&
+    stg squash -q --save-template quiet-template p0 q1 2>err &&
+    test_path_is_file quiet-template &&
+    test_must_be_empty err
+'
+
+test_expect_success 'Expose squashed tree' '
+    echo "x" >x.txt &&
+    stg add x.txt &&
+    stg new -rm "x-patch" &&
+    echo "y" >y.txt &&
+    stg add y.txt &&
+    stg new -rm "y-patch" &&
+    head=$(git rev-parse HEAD) &&
+    stg squash --expose=refs/heads/squash-preview x-patch y-patch &&
+    test "$(git rev-parse HEAD)" = "$head" &&
+    test "$(echo $(stg series --applied --noprefix | tail -n 2))" = "x-patch y-patch" &&
+    test "$(git rev-parse squash-preview^{tree})" = "$(git rev-parse HEAD^{tree})" &&
+    test "$(git rev-parse squash-preview^)" = "$(git rev-parse $(stg id x-patch)^)"
+'
+
+test_expect_success 'Expose patches given out of stack order' '
+    stg squash --expose=refs/heads/squash-reversed y-patch x-patch &&
+    test "$(git rev-parse squash-reversed^{tree})" = "$(git rev-parse HEAD^{tree})" &&
+    test "$(git rev-parse squash-reversed^)" = "$(git rev-parse $(stg id x-patch)^)"
+'
+
+test_expect_success 'Expose refuses to overwrite an existing ref' '
+    head=$(git rev-parse HEAD) &&
+    git update-ref refs/heads/squash-preview HEAD &&
+    command_error stg squash --expose=refs/heads/squash-preview x-patch y-patch 2>err &&
+    grep -e "the ref already exists" err &&
+    test "$(git rev-parse squash-preview)" = "$head" &&
+    stg squash --force --expose=refs/heads/squash-preview x-patch y-patch &&
+    test "$(git rev-parse squash-preview^)" = "$(git rev-parse $(stg id x-patch)^)" &&
+    test "$(git rev-parse HEAD)" = "$head"
+'
+
+test_expect_success 'Expose refuses to write the branch and stack refs' '
+    head=$(git rev-parse HEAD) &&
+    stack=$(git rev-parse refs/stacks/master) &&
+    for ref in refs/heads/master HEAD refs/stacks/master refs/patches/master/x-patch
+    do
+        command_error stg squash --force --expose=$ref x-patch y-patch 2>err &&
+        grep -e "belongs to the current branch or its stack" err || return 1
+    done &&
+    test "$(git rev-parse HEAD)" = "$head" &&
+    test "$(git rev-parse refs/stacks/master)" = "$stack" &&
+    stg refresh &&
+    stg delete x-patch y-patch
+'
+
+test_expect_success 'Expose fails when patches do not combine' '
+    echo "c1" >c.txt &&
+    stg add c.txt &&
+    stg new -rm "c1-patch" &&
+    echo "c2" >c.txt &&
+    stg new -rm "c2-patch" &&
+    echo "c3" >c.txt &&
+    stg new -rm "c3-patch" &&
+    test_when_finished "stg delete c1-patch c2-patch c3-patch" &&
+    command_error stg squash --expose=refs/heads/squash-conflict c1-patch c3-patch 2>err &&
+    grep -e "patches do not combine without conflicts" err &&
+    test_must_fail git rev-parse --verify -q refs/heads/squash-conflict
+'
+
+test_expect_success 'Expose with invalid ref name' '
+    command_error stg squash --expose="bad..ref" p0 q1 2>err &&
+    grep -e "invalid ref name .bad..ref." err
+'
+
 test_expect_success 'Empty commit message aborts the squash' '
     write_script fake-editor <<-\EOF &&
 	echo "" >"$1"
@@ -130,6 +630,363 @@ test_expect_success 'Empty commit message aborts the squash' '
     test "$(echo $(stg series))" = "+ p0 > q1"
 '
 
+test_expect_success 'Squash patches whose changes cancel out' '
+    stg new -m "add-patch" &&
+    echo cancel >cancel.txt &&
+    stg add cancel.txt &&
+    stg refresh &&
+    stg new -m "remove-patch" &&
+    stg rm cancel.txt &&
+    stg refresh &&
+    command_error stg squash --name=cancel-patch -m cancel add-patch remove-patch 2>err &&
+    grep -e "cancel out, leaving an empty patch" err &&
+    test "$(echo $(stg series --applied --noprefix | tail -n 2))" = "add-patch remove-patch"
+'
+
+test_expect_success 'Squash patches whose changes cancel out with --keep-empty' '
+    stg squash --keep-empty --name=cancel-patch -m cancel add-patch remove-patch &&
+    test "$(stg top)" = "cancel-patch" &&
+    test -z "$(stg files cancel-patch)" &&
+    stg delete cancel-patch
+'
+
+test_expect_success 'Squash message ending with indented block' '
+    printf "block-patch\n\nExample:\n\n    indented line\n        deeper line\n\n\n" >block-msg &&
+    stg new --file=block-msg block-patch &&
+    stg new -m "other-patch" &&
+    write_script fake-editor <<-\EOF &&
+	cp "$1" editor-input
+	EOF
+    EDITOR=./fake-editor stg squash --name=block-other-patch block-patch other-patch &&
+    test_when_finished "stg delete block-other-patch" &&
+    sed -n "/^# Commit message from patch #1/,/^# Commit message from patch #2/p" editor-input >out &&
+    cat >expected <<-\EOF &&
+	# Commit message from patch #1: block-patch
+	block-patch
+
+	Example:
+
+	    indented line
+	        deeper line
+
+	# Commit message from patch #2: other-patch
+	EOF
+    test_cmp expected out &&
+    git log -1 --format=%B $(stg id block-other-patch) >out &&
+    grep -e "^    indented line$" out &&
+    grep -e "^        deeper line$" out
+'
+
+test_expect_success 'Squash non-contiguous patches warns' '
+    stg new -m "n1-patch" &&
+    stg new -m "n2-patch" &&
+    stg new -m "n3-patch" &&
+    stg squash --name=n13-patch -m "n13" n1-patch n3-patch 2>err &&
+    grep -e "squashed patches .n1-patch., .n3-patch. are not contiguous in applied order" err &&
+    test "$(echo $(stg series --applied --noprefix | tail -n 2))" = "n13-patch n2-patch" &&
+    stg squash --name=n123-patch -m "n123" n13-patch n2-patch 2>err &&
+    ! grep -e "not contiguous" err &&
+    stg delete n123-patch
+'
+
+test_expect_success 'Squash keeping the original patches hidden' '
+    stg new -m "k1-patch" &&
+    stg new -m "k2-patch" &&
+    stg new -m "k3-patch" &&
+    stg squash --keep --name=k12-patch -m "k12" k1-patch k2-patch &&
+    test "$(echo $(stg series --applied --noprefix | tail -n 2))" = "k12-patch k3-patch" &&
+    stg series --hidden --noprefix >hidden &&
+    grep -e "^k1-patch$" hidden &&
+    grep -e "^k2-patch$" hidden &&
+    stg delete k12-patch k3-patch &&
+    stg unhide k1-patch k2-patch &&
+    stg delete k1-patch k2-patch
+'
+
+test_expect_success 'Squash keeping original patches cannot reuse their names' '
+    stg new -m "k1-patch" &&
+    stg new -m "k2-patch" &&
+    command_error stg squash --keep --name=k1-patch -m "k12" k1-patch k2-patch 2>err &&
+    grep -e "patch name .k1-patch. already taken" err &&
+    stg delete k1-patch k2-patch
+'
+
+test_expect_success 'Squash patches read from stdin' '
+    stg new -m "s1-patch" &&
+    stg new -m "s2-patch" &&
+    stg new -m "s3-patch" &&
+    printf "s1-patch\n\ns2-patch..s3-patch\n" |
+    stg squash --stdin --name=s123-patch -m "s123" &&
+    test "$(echo $(stg series --applied --noprefix | tail -n 1))" = "s123-patch" &&
+    stg delete s123-patch
+'
+
+test_expect_success 'Squash patches in reverse order' '
+    echo "r1" >r1.txt &&
+    stg add r1.txt &&
+    stg new -rm "r1-patch" &&
+    echo "r2" >r2.txt &&
+    stg add r2.txt &&
+    stg new -rm "r2-patch" &&
+    git rev-parse "$(stg id r1-patch)^" >expected-parent &&
+    EDITOR=./noop-editor stg squash --reverse --name=r21-patch r1-patch r2-patch &&
+    test_when_finished "stg delete r21-patch" &&
+    git log -1 --format=%B $(stg id r21-patch) | grep . >out &&
+    cat >expected <<-\EOF &&
+	r2-patch
+	r1-patch
+	EOF
+    test_cmp expected out &&
+    git rev-parse "$(stg id r21-patch)^" >parent &&
+    test_cmp expected-parent parent &&
+    test_path_is_file r1.txt &&
+    test_path_is_file r2.txt
+'
+
+test_expect_success 'Squash modifying patches in reverse order keeps all changes' '
+    echo base >f0.txt &&
+    echo base >f1.txt &&
+    stg add f0.txt f1.txt &&
+    stg new -rm "f-base-patch" &&
+    echo A >f0.txt &&
+    stg new -rm "fa-patch" &&
+    echo B >f1.txt &&
+    stg new -rm "fb-patch" &&
+    stg squash --reverse --name=fab-patch -m "fab" fa-patch fb-patch 2>err &&
+    test_when_finished "stg delete f-base-patch fab-patch" &&
+    ! grep -e "not contiguous" err &&
+    test "$(git show $(stg id fab-patch):f0.txt)" = "A" &&
+    test "$(git show $(stg id fab-patch):f1.txt)" = "B" &&
+    test "$(git rev-parse $(stg id fab-patch)^)" = "$(stg id f-base-patch)" &&
+    test "$(cat f0.txt)" = "A" &&
+    test "$(cat f1.txt)" = "B"
+'
+
+test_expect_success 'Squash from stdin conflicts with patch arguments' '
+    general_error stg squash --stdin p0 p1 </dev/null 2>err &&
+    grep -e "cannot be used with" err
+'
+
+test_expect_success 'Preview squashed diff' '
+    stg new -m "v1-patch" &&
+    echo v1 >v1.txt &&
+    stg add v1.txt &&
+    stg refresh &&
+    stg new -m "v2-patch" &&
+    echo v2 >v2.txt &&
+    stg add v2.txt &&
+    stg refresh &&
+    stg series >series-before &&
+    stg squash --preview v1-patch v2-patch >out &&
+    grep -e "^+++ b/v1.txt" out &&
+    grep -e "^+++ b/v2.txt" out &&
+    stg series >series-after &&
+    test_cmp series-before series-after
+'
+
+test_expect_success 'Preview squashed diff limited by path' '
+    stg squash --preview --preview-path=v2.txt v1-patch v2-patch >out &&
+    ! grep -e "v1.txt" out &&
+    grep -e "^+++ b/v2.txt" out
+'
+
+test_expect_success 'Preview patches given out of stack order' '
+    stg squash --preview v2-patch v1-patch >out &&
+    grep -e "^+++ b/v1.txt" out &&
+    grep -e "^+++ b/v2.txt" out &&
+    stg delete v1-patch v2-patch
+'
+
+test_expect_success 'Preview path requires preview' '
+    general_error stg squash --preview-path=v2.txt v1-patch v2-patch 2>err &&
+    grep -e "--preview" err
+'
+
+test_expect_success 'Preview with conflicting patches' '
+    stg new -m "c1-patch" &&
+    echo c1 >c.txt &&
+    stg add c.txt &&
+    stg refresh &&
+    stg new -m "c2-patch" &&
+    echo c2 >c.txt &&
+    stg refresh &&
+    stg new -m "c3-patch" &&
+    echo c3 >c.txt &&
+    stg refresh &&
+    command_error stg squash --preview c1-patch c3-patch 2>err &&
+    grep -e "cannot preview squashed diff: patches do not combine without conflicts" err &&
+    stg delete c1-patch c2-patch c3-patch
+'
+
+test_expect_success 'Squash with dirty work tree fails without autostash' '
+    stg new -m "s1-patch" &&
+    printf "a\nb\nc\nd\ne\n" >s.txt &&
+    stg add s.txt &&
+    stg refresh &&
+    stg new -m "s2-patch" &&
+    printf "A\nb\nc\nd\ne\n" >s.txt &&
+    stg refresh &&
+    stg pop s2-patch &&
+    printf "a\nb\nc\nd\nE\n" >s.txt &&
+    command_error stg squash -m "s" --name=s-patch s1-patch s2-patch &&
+    test "$(stg top)" = "s1-patch" &&
+    test -z "$(git stash list)"
+'
+
+test_expect_success 'Squash with autostash' '
+    stg squash --autostash -m "s" --name=s-patch s1-patch s2-patch &&
+    test "$(stg top)" = "s-patch" &&
+    printf "A\nb\nc\nd\nE\n" >expected &&
+    test_cmp expected s.txt &&
+    test -z "$(git stash list)" &&
+    git checkout s.txt &&
+    stg delete s-patch
+'
+
+test_expect_success 'Squash with --date=earliest' '
+    stg new -m "d1-patch" --author "$OTHER" \
+        --authdate "2005-05-05 05:05:05 +0500" &&
+    stg new -m "d2-patch" --authdate "2003-03-03 03:03:03 +0300" &&
+    stg new -m "d3-patch" --authdate "2004-04-04 04:04:04 +0400" &&
+    stg squash -m "d-patch" --name=d-patch --date=earliest d1-patch d2-patch d3-patch &&
+    test "$(git log -1 --format=%ai $(stg id d-patch))" = "2003-03-03 03:03:03 +0300" &&
+    test "$(git log -1 --format=%an $(stg id d-patch))" = "$GIT_AUTHOR_NAME" &&
+    stg undo
+'
+
+test_expect_success 'Squash with --date=first' '
+    stg squash -m "d-patch" --name=d-patch --date=first d1-patch d2-patch d3-patch &&
+    test "$(git log -1 --format=%ai $(stg id d-patch))" = "2005-05-05 05:05:05 +0500" &&
+    stg undo
+'
+
+test_expect_success 'Squash with --date and --authdate' '
+    stg squash -m "d-patch" --name=d-patch --date=earliest \
+        --authdate "2006-06-06 06:06:06 +0600" d1-patch d2-patch d3-patch &&
+    test "$(git log -1 --format=%ai $(stg id d-patch))" = "2006-06-06 06:06:06 +0600" &&
+    stg delete d-patch
+'
+
+test_expect_success 'Setup patches for --onto' '
+    for p in o0 o1 o2 o3
+    do
+        stg new -m "$p" &&
+        echo "$p" >"$p.txt" &&
+        stg add "$p.txt" &&
+        stg refresh || return 1
+    done
+'
+
+test_expect_success 'Squash onto a lower patch' '
+    stg squash -m "o23" --name=o23 --onto=o0 o2 o3 &&
+    test "$(echo $(stg series --noprefix))" = "p0 q1 o0 o23 o1" &&
+    test "$(stg top)" = "o1" &&
+    test "$(echo $(stg files --bare o23))" = "o2.txt o3.txt" &&
+    git diff-index --quiet HEAD
+'
+
+test_expect_success 'Squash onto a higher patch' '
+    stg squash -m "o023" --name=o023 --onto=o1 o0 o23 &&
+    test "$(echo $(stg series --noprefix))" = "p0 q1 o1 o023" &&
+    test "$(stg top)" = "o023" &&
+    test "$(echo $(stg files --bare o023))" = "o0.txt o2.txt o3.txt"
+'
+
+test_expect_success 'Squash onto the stack base' '
+    stg squash -m "o0123" --name=o0123 --onto="{base}" o1 o023 &&
+    test "$(echo $(stg series --noprefix))" = "o0123 p0 q1" &&
+    test "$(stg top)" = "q1" &&
+    stg undo
+'
+
+test_expect_success 'Squash onto invalid targets' '
+    command_error stg squash -m "x" --onto=o1 o1 o023 2>err &&
+    grep -e "cannot squash onto .o1., which is being squashed" err &&
+    stg pop o023 &&
+    command_error stg squash -m "x" --onto=o023 p0 q1 2>err &&
+    grep -e "cannot squash onto unapplied patch .o023." err &&
+    stg push o023 &&
+    command_error stg squash -m "x" --onto=HEAD~ p0 q1 2>err &&
+    grep -e "target must be an applied patch or the stack.s base" err &&
+    command_error stg squash -m "x" --onto=HEAD^{tree} p0 q1 2>err &&
+    grep -e "error: .HEAD^{tree}. does not resolve to a commit" err &&
+    command_error stg squash -m "x" --onto=HEAD~99 p0 q1 &&
+    test "$(echo $(stg series --noprefix))" = "p0 q1 o1 o023" &&
+    stg delete o1 o023
+'
+
+test_expect_success 'Squash with verbose shows combined diff' '
+    echo "verbose-1" >>baz.txt &&
+    stg new -rm "v1" &&
+    echo "verbose-2" >>baz.txt &&
+    stg new -rm "v2" &&
+    write_script fake-editor <<-\EOF &&
+	cp "$1" template.txt
+	EOF
+    EDITOR=./fake-editor stg squash --verbose --name=v12 v1 v2 &&
+    grep -e "^# ------------------------ >8 ------------------------$" template.txt &&
+    grep -e "^+verbose-1$" template.txt &&
+    grep -e "^+verbose-2$" template.txt &&
+    git log -1 --format=%B $(stg id v12) >out &&
+    printf "v1\n\nv2\n\n" >expected &&
+    test_cmp expected out &&
+    stg delete v12
+'
+
+test_expect_success 'Squash patches named by prefix' '
+    echo "feature" >>baz.txt &&
+    stg new -rm "feature-x" &&
+    echo "bugfix" >>baz.txt &&
+    stg new -rm "bugfix-y" &&
+    echo "bugfix2" >>baz.txt &&
+    stg new -rm "bugfix-z" &&
+    command_error stg squash -m "x" fea bugfix-y 2>err &&
+    grep -e "patch .fea. does not exist" err &&
+    command_error stg squash -m "x" --prefix-match fea bug 2>err &&
+    grep -e "ambiguous patch name prefix .bug. matches patches .bugfix-y. and .bugfix-z." err &&
+    stg squash -m "fb" --name=fb --prefix-match fea bugfix-y &&
+    test "$(echo $(stg series --noprefix | tail -n2))" = "fb bugfix-z" &&
+    stg delete fb bugfix-z
+'
+
+test_expect_success 'Squash dry run reports plan without changes' '
+    echo "dry-1" >>baz.txt &&
+    stg new -rm "d1" &&
+    echo "dry-2" >>baz.txt &&
+    stg new -rm "d2" --author "$OTHER" &&
+    echo "dry-3" >>baz.txt &&
+    stg new -rm "d3" &&
+    series_before="$(stg series)" &&
+    head_before="$(git rev-parse HEAD)" &&
+    stg squash --dry-run --name=d12 d1 d2 >out &&
+    cat >expected <<-EOF &&
+	squash: d1 d2 -> d12
+	method: combine
+	author: $GIT_AUTHOR_NAME <$GIT_AUTHOR_EMAIL>
+	co-author: Other Contributor <another@example.com> (1)
+	delete: d1 d2
+	pop: d3
+	push: d12 d3
+	EOF
+    test_cmp expected out &&
+    stg squash --dry-run --keep --no-coauthors d1 d3 >out &&
+    cat >expected <<-EOF &&
+	squash: d1 d3 -> <squashed>
+	method: push
+	author: $GIT_AUTHOR_NAME <$GIT_AUTHOR_EMAIL>
+	hide: d1 d3
+	pop: d2
+	push: <squashed> d2
+	EOF
+    test_cmp expected out &&
+    stg squash --dry-run --name=d23 --onto={base} d2 d3 >out &&
+    grep -e "^pop: .* d1$" out &&
+    grep -e "^push: d23 .* d1$" out &&
+    test "$(stg series)" = "$series_before" &&
+    test "$(git rev-parse HEAD)" = "$head_before" &&
+    stg delete d1 d2 d3
+'
+
 test_expect_success 'Squash with top != head' '
     write_script fake-editor <<-\EOF &&
 	#!/bin/sh
@@ -58,6 +58,22 @@ test_expect_success 'refresh with succeeding hook' '
     assert_pre_commit_hook_did_run
 '
 
+test_expect_success 'refresh with succeeding hook relays its stderr' '
+    write_script "$HOOK" <<-EOF &&
+	touch "$HOOK_OUTPUT_FILE"
+	echo "hook stderr note" >&2
+	exit 0
+	EOF
+    echo "pre-commit-hook-success-stderr" >>file &&
+    stg refresh 2>err &&
+    grep -e "^hook stderr note$" err &&
+    assert_pre_commit_hook_did_run &&
+    write_script "$HOOK" <<-EOF
+	touch "$HOOK_OUTPUT_FILE"
+	exit 0
+	EOF
+'
+
 test_expect_success 'refresh from subdir with succeeding hook' '
    mkdir -p dir0/dir1 &&
    (
@@ -119,6 +135,37 @@ test_expect_success 'refresh with path limiting, failing hook' '
     assert_pre_commit_hook_did_run
 '
 
+# now a hook that fails with diagnostics on stderr
+write_script "$HOOK" <<-EOF
+	touch "$HOOK_OUTPUT_FILE"
+	echo "hook stdout" &&
+	for i in \$(seq 1 30); do echo "hook stderr line \$i" >&2; done &&
+	exit 1
+EOF
+
+test_expect_success 'refresh with failing hook reports its stderr' '
+    echo "pre-commit-hook-fail-stderr" >>file &&
+    command_error stg refresh >out 2>err &&
+    grep -e "hook stdout" out &&
+    grep -e "\`pre-commit\` hook returned 1: hook stderr line 11$" err &&
+    test "$(grep -c -e "^hook stderr line 30$" err)" = "2" &&
+    test "$(grep -c -e "hook stderr line 10$" err)" = "1" &&
+    git reset HEAD &&
+    assert_pre_commit_hook_did_run
+'
+
+test_expect_success 'refresh --json with failing hook reports JSON diagnostics' '
+    echo "pre-commit-hook-fail-json" >>file &&
+    command_error stg --json refresh 2>err &&
+    tail -n 1 err >record &&
+    grep -e "^{\"error\":\"\`pre-commit\` hook returned 1: hook stderr line 11\\\\n" record &&
+    grep -e "\"hook\":{\"exit_code\":1,\"name\":\"pre-commit\",\"stderr\":\"hook stderr line 11\\\\n" record &&
+    grep -e "hook stderr line 30\"}}$" record &&
+    ! grep -e "^error:" err &&
+    git reset HEAD &&
+    assert_pre_commit_hook_did_run
+'
+
 chmod -x "$HOOK"
 test_expect_success 'refresh --no-verify with non-executable hook' '
     echo "no-verify pre-commit-hook-non-executable" >>file &&
@@ -214,4 +261,27 @@ test_expect_success 'refresh with path limiting, failing hook that modifies file
     assert_pre_commit_hook_did_run
 '
 
+# now a hook that edits the files added in index without adding them to index
+write_script "$HOOK" <<-EOF
+	git diff-index --check HEAD -- && exit
+	sed -e 's/[[:space:]]*$//' file >file.new
+	mv -- file.new file
+	touch "$HOOK_OUTPUT_FILE"
+EOF
+
+test_expect_success 'refresh with hook that modifies file, does not add to index' '
+    echo "pre-commit-hook-remove-whitespace-no-add  " >>file &&
+    stg refresh 2>err &&
+    grep -e "\`pre-commit\` hook modified the worktree" err &&
+    [ "$(tail -1 file)" = "pre-commit-hook-remove-whitespace-no-add" ] &&
+    [ "$(git show HEAD:file | tail -1)" = "pre-commit-hook-remove-whitespace-no-add  " ] &&
+    assert_pre_commit_hook_did_run
+'
+
+test_expect_success 'refresh with hook that does not modify file does not warn' '
+    stg refresh 2>err &&
+    ! grep -e "hook modified the worktree" err &&
+    [ "$(git show HEAD:file | tail -1)" = "pre-commit-hook-remove-whitespace-no-add" ]
+'
+
 test_done
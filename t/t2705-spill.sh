@@ -134,6 +134,67 @@ test_expect_success 'Create patch over subset of files' '
     test_cmp expected.txt status.txt &&
     stg refresh
 '
+test_expect_success 'Spill dry-run' '
+    stg spill --dry-run >files.txt &&
+    cat >expected-files.txt <<-\EOF &&
+	M dir0/a.txt
+	M dir0/dir1/e.txt
+	M dir0/dir2/i.txt
+	EOF
+    test_cmp expected-files.txt files.txt &&
+    (
+        cd dir0 &&
+        stg spill -n dir1 ../dir0/a.txt >../files.txt
+    ) &&
+    cat >expected-files.txt <<-\EOF &&
+	M dir0/a.txt
+	M dir0/dir1/e.txt
+	EOF
+    test_cmp expected-files.txt files.txt &&
+    stg status >status.txt &&
+    test_must_be_empty status.txt &&
+    stg files >files.txt &&
+    cat >expected-files.txt <<-\EOF &&
+	M dir0/a.txt
+	M dir0/dir1/e.txt
+	M dir0/dir2/i.txt
+	EOF
+    test_cmp expected-files.txt files.txt
+'
+
+test_expect_success 'Spill log records pathspecs' '
+    stg spill dir0/dir1 dir0/a.txt --annotate pear &&
+    stg log -n1 | grep -e "spill upper-vowels -- dir0/dir1 dir0/a.txt" &&
+    stg log -f -n1 | grep -e "pear" &&
+    stg undo --hard
+'
+
+test_expect_success 'Spill hunks interactively' '
+    printf "y\nn\ny\n" | stg spill --interactive &&
+    stg status >status.txt &&
+    cat >expected-status.txt <<-\EOF &&
+	M  dir0/a.txt
+	M  dir0/dir2/i.txt
+	EOF
+    test_cmp expected-status.txt status.txt &&
+    stg files >files.txt &&
+    cat >expected-files.txt <<-\EOF &&
+	M dir0/dir1/e.txt
+	EOF
+    test_cmp expected-files.txt files.txt &&
+    stg undo --hard
+'
+
+test_expect_success 'Spill hunks interactively with pathspec' '
+    printf "y\n" | stg spill -i dir0/dir2 &&
+    stg status >status.txt &&
+    cat >expected-status.txt <<-\EOF &&
+	M  dir0/dir2/i.txt
+	EOF
+    test_cmp expected-status.txt status.txt &&
+    stg undo --hard
+'
+
 test_expect_success 'Spill subsets of files' '
     stg spill dir0/dir1 &&
     stg status >status.txt &&
@@ -181,6 +242,76 @@ test_expect_success 'Spill uptree pathspec' '
     stg undo --hard
 '
 
+test_expect_success 'Spill with --keep-index' '
+    stg spill --keep-index dir0/dir1 &&
+    stg status >status.txt &&
+    cat >expected-status.txt <<-\EOF &&
+	M  dir0/dir1/e.txt
+	EOF
+    test_cmp expected-status.txt status.txt &&
+    stg undo --hard
+'
+
+test_expect_success 'Spill with --keep-index and --reset' '
+    general_error stg spill --keep-index --reset 2>err &&
+    grep -e "cannot be used with" err &&
+    rm err
+'
+
+test_expect_success 'Spill with --keep-index and --to-patch' '
+    general_error stg spill --keep-index --to-patch new-p 2>err &&
+    grep -e "cannot be used with" err &&
+    rm err
+'
+
+test_expect_success 'Spill to new patch conflicts with --reset' '
+    general_error stg spill --to-patch new-p --reset 2>err &&
+    grep -e "cannot be used with" err &&
+    rm err
+'
+
+test_expect_success 'Spill to existing patch name' '
+    command_error stg spill --to-patch p0 2>err &&
+    grep -e "patch \`p0\` already exists" err &&
+    rm err
+'
+
+test_expect_success 'Spill subset of files to new patch' '
+    stg spill --to-patch vowels-dir1 dir0/dir1 &&
+    test "$(echo $(stg series --applied --noprefix))" = "p0 vowels-dir1 upper-vowels" &&
+    stg status >status.txt &&
+    test_must_be_empty status.txt &&
+    stg files vowels-dir1 >files.txt &&
+    cat >expected-files.txt <<-\EOF &&
+	M dir0/dir1/e.txt
+	EOF
+    test_cmp expected-files.txt files.txt &&
+    stg files >files.txt &&
+    cat >expected-files.txt <<-\EOF &&
+	M dir0/a.txt
+	M dir0/dir2/i.txt
+	EOF
+    test_cmp expected-files.txt files.txt &&
+    stg undo --hard &&
+    test "$(echo $(stg series --applied --noprefix))" = "p0 upper-vowels"
+'
+
+test_expect_success 'Spill whole patch to new patch' '
+    stg spill --to-patch all-vowels &&
+    stg status >status.txt &&
+    test_must_be_empty status.txt &&
+    stg files >files.txt &&
+    test_must_be_empty files.txt &&
+    stg files all-vowels >files.txt &&
+    cat >expected-files.txt <<-\EOF &&
+	M dir0/a.txt
+	M dir0/dir1/e.txt
+	M dir0/dir2/i.txt
+	EOF
+    test_cmp expected-files.txt files.txt &&
+    stg undo --hard
+'
+
 test_expect_success 'Spill with modified worktree' '
     echo "modification" >>dir0/a.txt &&
     stg spill dir0/dir1 &&
@@ -345,4 +476,147 @@ test_expect_success 'Spill removed file' '
     stg delete --top
 '
 
+test_expect_success 'Spill non-topmost patch' '
+    echo B >dir0/b.txt &&
+    stg new -rm upper-b &&
+    stg spill -p upper-vowels dir0/dir1 &&
+    test "$(echo $(stg top))" = "upper-b" &&
+    stg status >status.txt &&
+    cat >expected-status.txt <<-\EOF &&
+	M  dir0/dir1/e.txt
+	EOF
+    test_cmp expected-status.txt status.txt &&
+    stg files upper-vowels >files.txt &&
+    cat >expected-files.txt <<-\EOF &&
+	M dir0/a.txt
+	M dir0/dir2/i.txt
+	EOF
+    test_cmp expected-files.txt files.txt &&
+    stg undo --hard
+'
+
+test_expect_success 'Spill and reset non-topmost patch' '
+    stg spill -r -p upper-vowels dir0/dir1 &&
+    stg status >status.txt &&
+    cat >expected-status.txt <<-\EOF &&
+	 M dir0/dir1/e.txt
+	EOF
+    test_cmp expected-status.txt status.txt &&
+    stg undo --hard
+'
+
+test_expect_success 'Spill non-topmost patch to new patch' '
+    stg spill -p upper-vowels --to-patch upper-a dir0/a.txt &&
+    test "$(echo $(stg series --applied --noprefix))" = "p0 upper-a upper-vowels upper-b" &&
+    stg status >status.txt &&
+    test_must_be_empty status.txt &&
+    stg files upper-a >files.txt &&
+    cat >expected-files.txt <<-\EOF &&
+	M dir0/a.txt
+	EOF
+    test_cmp expected-files.txt files.txt &&
+    stg undo --hard
+'
+
+test_expect_success 'Spill with edited message' '
+    write_script spilledit <<-\EOF &&
+	echo "reduced-vowels" >"$1"
+	EOF
+    EDITOR=./spilledit stg spill -e -p upper-vowels dir0/a.txt &&
+    echo "reduced-vowels" >expected &&
+    git log -1 --format=%s $(stg id upper-vowels) >actual &&
+    test_cmp expected actual &&
+    stg files upper-vowels >files.txt &&
+    ! grep -e "dir0/a.txt" files.txt &&
+    stg undo --hard
+'
+
+test_expect_success 'Spill with edit conflicts with to-patch' '
+    general_error stg spill -e --to-patch upper-a dir0/a.txt 2>err &&
+    grep -e "cannot be used with" err
+'
+
+test_expect_success 'Spill warns about unmatched paths' '
+    stg spill -p upper-vowels dir0/a.txt no-such-path 2>err &&
+    grep -e "no changes matched path '"'"'no-such-path'"'"'" err &&
+    ! grep -e "dir0/a.txt" err &&
+    stg undo --hard
+'
+
+test_expect_success 'Spill to patch warns about unmatched paths' '
+    stg spill -p upper-vowels --to-patch upper-a dir0/a.txt no-such-path 2>err &&
+    grep -e "no changes matched path '"'"'no-such-path'"'"'" err &&
+    stg undo --hard
+'
+
+test_expect_success 'Undo spill restores the patch' '
+    stg id upper-vowels >expected &&
+    stg spill -p upper-vowels dir0/a.txt &&
+    stg spill --undo -p upper-vowels &&
+    stg id upper-vowels >actual &&
+    test_cmp expected actual &&
+    git diff --cached --quiet &&
+    git diff --quiet &&
+    stg files upper-vowels >files.txt &&
+    grep -e "dir0/a.txt" files.txt
+'
+
+test_expect_success 'Undo spill keeps unrelated staged changes' '
+    stg id upper-vowels >expected &&
+    stg spill -p upper-vowels dir0/a.txt &&
+    echo unrelated >unrelated.txt &&
+    git add unrelated.txt &&
+    test_when_finished "git rm -qf unrelated.txt" &&
+    stg spill --undo -p upper-vowels &&
+    stg id upper-vowels >actual &&
+    test_cmp expected actual &&
+    git diff --cached --name-status >status.txt &&
+    cat >expected-status.txt <<-\EOF &&
+	A	unrelated.txt
+	EOF
+    test_cmp expected-status.txt status.txt &&
+    git diff --quiet
+'
+
+test_expect_success 'Undo spill with --reset keeps unrelated staged changes' '
+    stg spill -r -p upper-vowels dir0/a.txt &&
+    echo unrelated >unrelated.txt &&
+    git add unrelated.txt &&
+    test_when_finished "git rm -qf unrelated.txt" &&
+    stg spill --undo -p upper-vowels &&
+    git diff --cached --name-status >status.txt &&
+    cat >expected-status.txt <<-\EOF &&
+	A	unrelated.txt
+	EOF
+    test_cmp expected-status.txt status.txt &&
+    git diff --quiet
+'
+
+test_expect_success 'Undo spill of modified patch' '
+    stg spill -p upper-vowels dir0/a.txt &&
+    stg refresh -p upper-vowels &&
+    command_error stg spill --undo -p upper-vowels 2>err &&
+    grep -e "\`upper-vowels\` was modified since it was last spilled" err
+'
+
+test_expect_success 'Undo spill of never spilled patch' '
+    stg new -m never-spilled &&
+    command_error stg spill --undo 2>err &&
+    grep -e "no spill of \`never-spilled\` found in the stack log" err &&
+    stg delete never-spilled
+'
+
+test_expect_success 'Undo spill conflicts with --reset' '
+    general_error stg spill --undo --reset 2>err &&
+    grep -e "cannot be used with" err
+'
+
+test_expect_success 'Attempt to spill unapplied patch' '
+    stg pop &&
+    command_error stg spill -p upper-b 2>err &&
+    grep -e "unapplied patch \`upper-b\` is not allowed" err &&
+    rm err &&
+    stg push
+'
+
 test_done
@@ -31,4 +31,12 @@ test_expect_success 'Push with dirty worktree' '
     [ "$(echo $(cat a))" = "4" ]
 '
 
+test_expect_success 'Pop with dirty worktree reports overwritten paths' '
+    command_error stg pop --keep 2>err &&
+    grep -e "checkout would overwrite local changes to \`a\`" err &&
+    grep -e "command aborted (all changes rolled back)" err &&
+    [ "$(echo $(stg series --applied --noprefix))" = "p1" ] &&
+    [ "$(echo $(cat a))" = "4" ]
+'
+
 test_done
@@ -72,6 +72,14 @@ test_expect_success 'Use custom template' '
     grep -e "^author@example.com -- patch-1" patches-master/patch-1.patch
 '
 
+test_expect_success 'Use custom template with RFC 2822 date' '
+    echo "Date: %(authdate_rfc2822)s" >template &&
+    stg export -t template -p patch-1 &&
+    echo "Date: $(git log -1 --format=%aD $(stg id patch-1))" >expected &&
+    head -n 1 patches-master/patch-1.patch >out &&
+    test_cmp expected out
+'
+
 test_expect_success 'Export numbered patches with custom extension' '
     stg export -d export5 -n -e mydiff patch-1 patch-2 &&
     test_path_is_file export5/01-patch-1.mydiff &&
@@ -380,4 +380,41 @@ test_expect_success "squash hook doesn't edit commit message (editor)" '
     commit_msg_is "more plus"
 '
 
+test_expect_success 'hook CRLF line endings normalized with core.autocrlf' '
+    cp "$HOOK" hook.orig &&
+    test_when_finished "cp hook.orig \"\$HOOK\"" &&
+    write_script "$HOOK" <<-\EOF &&
+	printf "crlf subject\r\n\r\ncrlf body\r\n" >"$1"
+	EOF
+    stg new -m "crlf" crlf-kept &&
+    test_when_finished "stg delete crlf-kept" &&
+    git cat-file commit HEAD >out &&
+    grep -e "$(printf "\r")" out &&
+    test_config core.autocrlf true &&
+    stg new -m "crlf" crlf-normalized &&
+    test_when_finished "stg delete crlf-normalized" &&
+    git cat-file commit HEAD >out &&
+    ! grep -e "$(printf "\r")" out &&
+    commit_msg_is "crlf subjectcrlf body"
+'
+
+test_expect_success 'stale temporary message files are removed' '
+    echo "stale" >.stgit-msg-temp-1 &&
+    touch -t 200001010000 .stgit-msg-temp-1 &&
+    echo "fresh" >.stgit-msg-temp-2 &&
+    test_when_finished "rm -f .stgit-msg-temp-1 .stgit-msg-temp-2" &&
+    stg series &&
+    test_path_is_missing .stgit-msg-temp-1 &&
+    test_path_is_file .stgit-msg-temp-2
+'
+
+test_expect_success 'stale temporary message files are removed without hook' '
+    echo "stale" >.stgit-msg-temp-1 &&
+    touch -t 200001010000 .stgit-msg-temp-1 &&
+    test_when_finished "rm -f .stgit-msg-temp-1" &&
+    stg new --no-verify -m "temp-cleanup" &&
+    test_when_finished "stg delete temp-cleanup" &&
+    test_path_is_missing .stgit-msg-temp-1
+'
+
 test_done
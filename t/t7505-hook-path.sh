@@ -0,0 +1,79 @@
+#!/bin/sh
+
+test_description='Test stg hook path'
+
+. ./test-lib.sh
+
+test_expect_success 'Missing hook' '
+    command_error stg hook path pre-commit 2>err &&
+    grep -e "no executable \`pre-commit\` hook found in \`.*hooks\`$" err
+'
+
+test_expect_success 'Non-executable hook' '
+    echo "exit 0" >"$(git rev-parse --git-path hooks/pre-commit)" &&
+    command_error stg hook path pre-commit 2>err &&
+    grep -e "no executable \`pre-commit\` hook found" err
+'
+
+test_expect_success 'Default hooks directory' '
+    write_script "$(git rev-parse --git-path hooks/pre-commit)" <<-\EOF &&
+	exit 0
+	EOF
+    echo "$(cd "$(git rev-parse --git-path hooks)" && pwd -P)/pre-commit" >expected &&
+    stg hook path pre-commit >out 2>err &&
+    test_cmp expected out &&
+    test_must_be_empty err
+'
+
+test_expect_success 'Hooks directory from core.hooksPath' '
+    mkdir my-hooks &&
+    test_config core.hooksPath my-hooks &&
+    command_error stg hook path pre-commit 2>err &&
+    grep -e "no executable \`pre-commit\` hook found in \`.*my-hooks\` (set by core.hooksPath)" err &&
+    write_script my-hooks/pre-commit <<-\EOF &&
+	exit 0
+	EOF
+    echo "$(cd my-hooks && pwd -P)/pre-commit" >expected &&
+    stg hook path pre-commit >out 2>err &&
+    test_cmp expected out &&
+    grep -e "using hooks directory \`.*my-hooks\` (set by core.hooksPath)" err
+'
+
+test_expect_success 'Supplemental hooks directories' '
+    mkdir extra1 extra2 &&
+    git config --add stgit.hooksPath extra1 &&
+    git config --add stgit.hooksPath extra2 &&
+    test_when_finished "git config --unset-all stgit.hooksPath" &&
+    command_error stg hook path commit-msg 2>err &&
+    grep -e "no executable \`commit-msg\` hook found in \`.*hooks\`, \`.*extra1\` (set by stgit.hooksPath), \`.*extra2\` (set by stgit.hooksPath)$" err &&
+    write_script extra2/commit-msg <<-\EOF &&
+	echo "extra2 hook" >&2
+	exit 1
+	EOF
+    echo "$(cd extra2 && pwd -P)/commit-msg" >expected &&
+    stg hook path commit-msg >out 2>err &&
+    test_cmp expected out &&
+    grep -e "using hooks directory \`.*extra2\` (set by stgit.hooksPath)" err &&
+    write_script extra1/commit-msg <<-\EOF &&
+	exit 0
+	EOF
+    echo "$(cd extra1 && pwd -P)/commit-msg" >expected &&
+    stg hook path commit-msg >out &&
+    test_cmp expected out
+'
+
+test_expect_success 'Hook from supplemental directory is run' '
+    stg init &&
+    git config --add stgit.hooksPath extra2 &&
+    test_when_finished "git config --unset-all stgit.hooksPath" &&
+    command_error stg new -m "new-patch" 2>err &&
+    grep -e "extra2 hook" err &&
+    stg new --no-verify -m "new-patch" &&
+    stg delete new-patch
+'
+
+test_expect_success 'Subcommand is required' '
+    general_error stg hook
+'
+
+test_done